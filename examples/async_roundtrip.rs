@@ -0,0 +1,51 @@
+use std::time::Duration;
+
+use mediator_play::{AsyncColleague, AsyncMediator, AsyncUserMediator, Error, User, UserId};
+use tokio_util::sync::CancellationToken;
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    let user_id1 = UserId::new("user-1");
+    let user_id2 = UserId::new("user-2");
+    
+    let mut mediator = AsyncUserMediator::default();
+    
+    let managed1 = User::new(user_id1.clone()).belong_to(mediator.clone());
+    let managed2 = User::new(user_id2.clone()).belong_to(mediator.clone());
+    
+    let reg1 = mediator.register(user_id1.clone(), managed1).await?;
+    let reg2 = mediator.register(user_id2.clone(), managed2).await?;
+    
+    reg1.send_msg(&user_id2, "ping").await?;
+    reg2.send_msg(&user_id1, "pong").await?;
+    mediator.consultation_timeout(&reg1, &user_id2, "in time".to_string(), Duration::from_millis(100)).await?;
+    
+    let disconnected = CancellationToken::new();
+    disconnected.cancel();
+    if let Err(e) = mediator.consultation_cancellable(&reg1, &user_id2, "never sent".to_string(), &disconnected).await {
+        println!("[Main] {}", e);
+    }
+    
+    let user_id4 = UserId::new("user-4");
+    let stopped = CancellationToken::new();
+    stopped.cancel();
+    tokio::select! {
+        found = mediator.await_registration(&user_id4, Duration::from_secs(1)) => { found?; }
+        _ = stopped.cancelled() => println!("[Main] stopped waiting for user-4"),
+    }
+    
+    let user_id3 = UserId::new("user-3");
+    let mut late = mediator.clone();
+    let latecomer = User::new(user_id3.clone()).belong_to(mediator.clone());
+    let joining = tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        late.register(UserId::new("user-3"), latecomer).await.map(|_| ())
+    });
+    let reg3 = mediator.await_registration(&user_id3, Duration::from_secs(1)).await?;
+    println!("[Main] {:?} showed up", reg3.id());
+    joining.await.expect("registering task panicked")?;
+    let reached = mediator.broadcast_concurrent(&reg1, "all at once".to_string()).await?;
+    println!("[Main] concurrent broadcast reached {} colleague(s)", reached);
+    
+    Ok(())
+}
@@ -0,0 +1,48 @@
+//! A circuit breaker sparing a jammed printer from further jobs until it had time to recover:
+//! `cargo run --example breaker`.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use mediator_play::{Colleague, ColleagueError, Error, GenericMediator, Mediator, MockClock};
+
+#[derive(Colleague)]
+#[colleague(mediator = GenericMediator<Printer>, message = String)]
+struct Printer {
+    #[colleague(id)]
+    id: u32,
+    jammed: AtomicBool,
+}
+
+impl Printer {
+    fn on_message(&self, job: String) -> Result<(), ColleagueError> {
+        if self.jammed.load(Ordering::Relaxed) {
+            return Err(ColleagueError::new("paper jam"));
+        }
+        println!("[printer-{}] printed {}", self.id, job);
+        Ok(())
+    }
+}
+
+fn main() -> Result<(), Error> {
+    let clock = MockClock::new();
+    let mediator = GenericMediator::<Printer>::builder()
+        .clock(clock.clone())
+        .circuit_breaker(2, Duration::from_secs(30))
+        .build();
+    let desk = mediator.register(0, Printer { id: 0, jammed: AtomicBool::new(false) }.belong_to(mediator.clone()))?;
+    let printer = mediator.register(1, Printer { id: 1, jammed: AtomicBool::new(true) }.belong_to(mediator.clone()))?;
+    
+    for job in ["report", "invoice", "memo"] {
+        match mediator.consultation(&desk, &1, job.to_string()) {
+            Ok(outcome) => println!("[Main] {}: {:?}", job, outcome),
+            Err(e) => println!("[Main] {}: {}", job, e),
+        }
+    }
+    
+    printer.jammed.store(false, Ordering::Relaxed);
+    clock.advance(Duration::from_secs(30));
+    println!("[Main] after 30s: {:?}", mediator.consultation(&desk, &1, "memo".to_string())?);
+    
+    Ok(())
+}
@@ -0,0 +1,51 @@
+//! A payload streamed to an uploader in pieces and put back together there:
+//! `cargo run --example chunked`.
+
+use std::sync::Mutex;
+
+use mediator_play::{Colleague, ColleagueError, Error, GenericMediator, Mediator};
+
+#[derive(Colleague)]
+#[colleague(mediator = GenericMediator<Uploader>, message = String, read_chunk = on_chunk, read_end = on_end)]
+struct Uploader {
+    #[colleague(id)]
+    id: u32,
+    received: Mutex<Vec<u8>>,
+}
+
+impl Uploader {
+    fn new(id: u32) -> Self {
+        Self { id, received: Mutex::new(Vec::new()) }
+    }
+    
+    fn on_message(&self, msg: String) -> Result<(), ColleagueError> {
+        println!("[uploader-{}] {}", self.id, msg);
+        Ok(())
+    }
+    
+    fn on_chunk(&self, chunk: Vec<u8>) -> Result<(), ColleagueError> {
+        println!("[uploader-{}] got {} byte(s)", self.id, chunk.len());
+        self.received.lock().unwrap().extend(chunk);
+        Ok(())
+    }
+    
+    fn on_end(&self) -> Result<(), ColleagueError> {
+        let received = self.received.lock().unwrap();
+        println!("[uploader-{}] reassembled {:?}", self.id, String::from_utf8_lossy(&received));
+        Ok(())
+    }
+}
+
+fn main() -> Result<(), Error> {
+    let mediator = GenericMediator::<Uploader>::builder()
+        .deadletter(true)
+        .build();
+    let client = mediator.register(0, Uploader::new(0).belong_to(mediator.clone()))?;
+    mediator.register(1, Uploader::new(1).belong_to(mediator.clone()))?;
+    
+    let chunks = ["large ", "payload ", "in pieces"].map(|piece| piece.as_bytes().to_vec());
+    println!("[Main] {:?}", mediator.consult_stream(&client, &1, chunks.clone().into_iter())?);
+    println!("[Main] {:?}", mediator.consult_stream(&client, &2, chunks.into_iter())?);
+    
+    Ok(())
+}
@@ -0,0 +1,37 @@
+//! Rough comparison of the lock backends under concurrent registration:
+//! `cargo run --release --example contention [--features parking_lot]`.
+
+use std::thread;
+use std::time::Instant;
+
+use mediator_play::{Colleague, Mediator, User, UserId, UserMediator};
+
+const THREADS: usize = 8;
+const PER_THREAD: usize = 10_000;
+
+fn main() {
+    let mediator = UserMediator::default();
+    let started = Instant::now();
+    
+    let handles = (0..THREADS)
+        .map(|t| {
+            let mediator = mediator.clone();
+            thread::spawn(move || {
+                for i in 0..PER_THREAD {
+                    let id = UserId::new(format!("user-{}-{}", t, i));
+                    let managed = User::new(id.clone()).belong_to(mediator.clone());
+                    mediator.register(id.clone(), managed).expect("unique id");
+                    mediator.is_registered(&id).expect("registry readable");
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    
+    for handle in handles {
+        handle.join().expect("registering thread panicked");
+    }
+    
+    let backend = if cfg!(feature = "parking_lot") { "parking_lot" } else { "std" };
+    println!("[{}] {} registrations across {} threads in {:?}",
+        backend, THREADS * PER_THREAD, THREADS, started.elapsed());
+}
@@ -0,0 +1,81 @@
+//! The `alloc`-only core a `no_std` target would use; run with
+//! `cargo run --example embedded --no-default-features --features no-std`.
+
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use mediator_play::embedded::{BTreeMediator, Colleague, FixedMediator, Managed, Mediator};
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+struct Pin(u8);
+
+struct Led {
+    pin: Pin,
+    toggles: AtomicU32,
+}
+
+impl Colleague for Led {
+    type Identifier = Pin;
+    type Mediator = BTreeMediator<Led>;
+    type Message = bool;
+    
+    fn id(&self) -> &Pin {
+        &self.pin
+    }
+    
+    fn belong_to(self, mediator: Self::Mediator) -> Managed<Self> {
+        Managed::new(self, mediator)
+    }
+    
+    fn read_msg(&self, on: bool) -> Result<(), String> {
+        self.toggles.fetch_add(1, Ordering::Relaxed);
+        println!("[{:?}] {}", self.pin, if on { "on" } else { "off" });
+        Ok(())
+    }
+}
+
+/// Lives on a bus with room for exactly two buttons, set aside when the program is built.
+struct Button {
+    pin: Pin,
+}
+
+impl Colleague for Button {
+    type Identifier = Pin;
+    type Mediator = &'static FixedMediator<Button, 2>;
+    type Message = ();
+    
+    fn id(&self) -> &Pin {
+        &self.pin
+    }
+    
+    fn belong_to(self, mediator: Self::Mediator) -> Managed<Self> {
+        Managed::new(self, mediator)
+    }
+    
+    fn read_msg(&self, _: ()) -> Result<(), String> {
+        println!("[{:?}] pressed", self.pin);
+        Ok(())
+    }
+}
+
+fn main() -> Result<(), mediator_play::embedded::Error> {
+    let mut bus = BTreeMediator::default();
+    let status = bus.register(Pin(13), Led { pin: Pin(13), toggles: AtomicU32::new(0) }.belong_to(bus.clone()))?;
+    let power = bus.register(Pin(2), Led { pin: Pin(2), toggles: AtomicU32::new(0) }.belong_to(bus.clone()))?;
+    
+    status.as_mediator().consultation(&status, &Pin(2), true)?;
+    power.as_mediator().consultation(&power, &Pin(13), false)?;
+    if let Err(e) = bus.consultation(&status, &Pin(7), true) {
+        println!("[Main] {}", e);
+    }
+    
+    println!("[Main] pins {:?}, pin 2 toggled {} time(s)", bus.registered_ids(), power.toggles.load(Ordering::Relaxed));
+    
+    static BUTTONS: FixedMediator<Button, 2> = FixedMediator::new();
+    let reset = BUTTONS.register(Pin(4), Button { pin: Pin(4) }.belong_to(&BUTTONS))?;
+    BUTTONS.register(Pin(5), Button { pin: Pin(5) }.belong_to(&BUTTONS))?;
+    BUTTONS.with(&reset, |reset| BUTTONS.consultation(reset, &Pin(5), ())).unwrap_or(Ok(()))?;
+    if let Err(e) = BUTTONS.register(Pin(6), Button { pin: Pin(6) }.belong_to(&BUTTONS)) {
+        println!("[Main] {}", e);
+    }
+    Ok(())
+}
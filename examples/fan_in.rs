@@ -0,0 +1,44 @@
+//! Three senders feeding one colleague through a bounded inbox it drains on its own thread:
+//! `cargo run --example fan_in`.
+
+use std::thread;
+
+use mediator_play::{Colleague, Error, Mediator, User, UserId, UserMediator};
+
+const SENDERS: usize = 3;
+const PER_SENDER: usize = 5;
+
+fn main() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    let collector_id = UserId::new("collector");
+    let (collector, inbox) = mediator.register_bounded_inbox(collector_id.clone(), User::new(collector_id.clone()).belong_to(mediator.clone()), 4)?;
+    
+    let consumer = thread::spawn(move || inbox.iter().count());
+    
+    let senders = (0..SENDERS)
+        .map(|s| {
+            let id = UserId::new(format!("sender-{}", s));
+            let reg = mediator.register(id.clone(), User::new(id).belong_to(mediator.clone()))?;
+            let to = collector_id.clone();
+            Ok(thread::spawn(move || {
+                for i in 0..PER_SENDER {
+                    // A full inbox pushes back; wait for the collector to catch up.
+                    while let Err(Error::MailboxFull { .. }) = reg.send_msg(&to, format!("message {}", i)) {
+                        thread::yield_now();
+                    }
+                }
+            }))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    
+    for sender in senders {
+        sender.join().expect("sending thread panicked");
+    }
+    
+    // The inbox runs dry for good once the collector's registration is gone.
+    mediator.deregister(&collector_id)?;
+    drop(collector);
+    let received = consumer.join().expect("consuming thread panicked");
+    println!("[Main] collector received {} of {} message(s)", received, SENDERS * PER_SENDER);
+    Ok(())
+}
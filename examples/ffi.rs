@@ -0,0 +1,31 @@
+//! Drives the C API the way a C host would, from Rust.
+
+use std::ffi::{c_char, c_void, CStr};
+use std::ptr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use mediator_play::ffi::{mediator_free, mediator_new, mediator_register, mediator_send, MEDIATOR_NOT_REGISTERED, MEDIATOR_OK};
+
+extern "C" fn on_message(user_data: *mut c_void, from: *const c_char, to: *const c_char, msg: *const c_char) {
+    let delivered = unsafe { &*user_data.cast::<AtomicUsize>() };
+    delivered.fetch_add(1, Ordering::Relaxed);
+    let [from, to, msg] = [from, to, msg].map(|s| unsafe { CStr::from_ptr(s) }.to_string_lossy());
+    println!("[{}] {} (from {})", to, msg, from);
+}
+
+fn main() {
+    let delivered = AtomicUsize::new(0);
+    let user_data = ptr::from_ref(&delivered).cast_mut().cast();
+    
+    unsafe {
+        let mediator = mediator_new(on_message, user_data);
+        assert_eq!(mediator_register(mediator, c"user-1".as_ptr()), MEDIATOR_OK);
+        assert_eq!(mediator_register(mediator, c"user-2".as_ptr()), MEDIATOR_OK);
+        
+        assert_eq!(mediator_send(mediator, c"user-1".as_ptr(), c"user-2".as_ptr(), c"hello over C".as_ptr()), MEDIATOR_OK);
+        assert_eq!(mediator_send(mediator, c"user-1".as_ptr(), c"user-3".as_ptr(), c"anyone?".as_ptr()), MEDIATOR_NOT_REGISTERED);
+        mediator_free(mediator);
+    }
+    
+    println!("[Main] {} message(s) crossed the C API", delivered.load(Ordering::Relaxed));
+}
@@ -0,0 +1,51 @@
+use std::hash::{BuildHasherDefault, Hasher};
+
+use mediator_play::{Colleague, ColleagueError, Error, HashedMediator, Mediator};
+
+/// FNV-1a, cheaper than SipHash for the short ids here and fine as long as they aren't
+/// picked by someone trying to collide them.
+#[derive(Default)]
+struct Fnv(u64);
+
+impl Hasher for Fnv {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+    
+    fn write(&mut self, bytes: &[u8]) {
+        if self.0 == 0 {
+            self.0 = 0xcbf2_9ce4_8422_2325;
+        }
+        for byte in bytes {
+            self.0 = (self.0 ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+type FnvMediator<C> = HashedMediator<C, BuildHasherDefault<Fnv>>;
+
+#[derive(Colleague)]
+#[colleague(mediator = FnvMediator<Port>, message = &'static str)]
+struct Port {
+    #[colleague(id)]
+    id: u16,
+}
+
+impl Port {
+    fn on_message(&self, msg: &'static str) -> Result<(), ColleagueError> {
+        println!("[port-{}] received {}", self.id, msg);
+        Ok(())
+    }
+}
+
+fn main() -> Result<(), Error> {
+    let mediator = FnvMediator::<Port>::default();
+    
+    let http = mediator.register(80, Port { id: 80 }.belong_to(mediator.clone()))?;
+    let https = mediator.register(443, Port { id: 443 }.belong_to(mediator.clone()))?;
+    mediator.register(8080, Port { id: 8080 }.belong_to(mediator.clone()))?;
+    mediator.consultation(&http, &443, "upgrade")?;
+    mediator.consultation(&https, &8080, "proxied")?;
+    println!("[Main] {} ports registered", mediator.registered_ids()?.len());
+    Ok(())
+}
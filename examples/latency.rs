@@ -0,0 +1,43 @@
+//! Finding the slow colleague: `cargo run --example latency`.
+
+use std::time::Duration;
+
+use mediator_play::{Colleague, ColleagueError, Error, MapMediator, Mediator, MockClock};
+
+/// Takes `pace` over every message, on the mediator's clock.
+#[derive(Colleague)]
+#[colleague(mediator = MapMediator<Worker>, message = String)]
+struct Worker {
+    #[colleague(id)]
+    name: &'static str,
+    pace: Duration,
+    clock: MockClock,
+}
+
+impl Worker {
+    fn on_message(&self, _job: String) -> Result<(), ColleagueError> {
+        self.clock.advance(self.pace);
+        Ok(())
+    }
+}
+
+fn main() -> Result<(), Error> {
+    let clock = MockClock::new();
+    let mediator = MapMediator::<Worker>::builder().clock(clock.clone()).build();
+    let worker = |name, millis| Worker { name, pace: Duration::from_millis(millis), clock: clock.clone() };
+    let boss = mediator.register("boss", worker("boss", 0).belong_to(mediator.clone()))?;
+    mediator.register("swift", worker("swift", 5).belong_to(mediator.clone()))?;
+    mediator.register("sluggish", worker("sluggish", 250).belong_to(mediator.clone()))?;
+    
+    for job in ["report", "review", "release"] {
+        mediator.consultation(&boss, &"swift", job.to_string())?;
+        mediator.consultation(&boss, &"sluggish", job.to_string())?;
+    }
+    for name in ["swift", "sluggish"] {
+        if let Some(stats) = mediator.latency_stats(&name) {
+            println!("[Main] {} took {:?} on average, {:?} at most, over {} job(s)", name, stats.mean().unwrap_or_default(), stats.max, stats.count);
+        }
+    }
+    
+    Ok(())
+}
@@ -0,0 +1,61 @@
+//! One mediator carrying several kinds of payload as variants of a single message enum:
+//! `cargo run --example multi_payload`.
+
+use std::sync::Mutex;
+
+use mediator_play::{Colleague, ColleagueError, Error, MapMediator, Mediator};
+
+#[derive(Debug, Clone)]
+enum Command {
+    Mute,
+    Rename(String),
+}
+
+/// Everything a terminal can be sent, whatever the context.
+#[derive(Debug, Clone)]
+enum AnyMessage {
+    Text(String),
+    Command(Command),
+}
+
+#[derive(Colleague)]
+#[colleague(mediator = MapMediator<Terminal>, message = AnyMessage)]
+struct Terminal {
+    #[colleague(id)]
+    id: u32,
+    title: Mutex<String>,
+    muted: Mutex<bool>,
+}
+
+impl Terminal {
+    fn new(id: u32) -> Self {
+        Self { id, title: Mutex::new(format!("terminal-{}", id)), muted: Mutex::new(false) }
+    }
+    
+    fn on_message(&self, msg: AnyMessage) -> Result<(), ColleagueError> {
+        match msg {
+            AnyMessage::Text(_) if *self.muted.lock().unwrap() => {
+                return Err(ColleagueError::new("muted"));
+            }
+            AnyMessage::Text(text) => println!("[{}] {}", self.title.lock().unwrap(), text),
+            AnyMessage::Command(Command::Mute) => *self.muted.lock().unwrap() = true,
+            AnyMessage::Command(Command::Rename(title)) => *self.title.lock().unwrap() = title,
+        }
+        Ok(())
+    }
+}
+
+fn main() -> Result<(), Error> {
+    let mediator = MapMediator::<Terminal>::default();
+    let console = mediator.register(0, Terminal::new(0).belong_to(mediator.clone()))?;
+    let shell = mediator.register(1, Terminal::new(1).belong_to(mediator.clone()))?;
+    
+    mediator.consultation(&console, &1, AnyMessage::Text("hello".to_string()))?;
+    mediator.consultation(&console, &1, AnyMessage::Command(Command::Rename("shell".to_string())))?;
+    mediator.consultation(&console, &1, AnyMessage::Text("renamed".to_string()))?;
+    mediator.consultation(&console, &1, AnyMessage::Command(Command::Mute))?;
+    let outcome = mediator.consultation(&console, &1, AnyMessage::Text("anyone there?".to_string()))?;
+    println!("[Main] {} is muted: {}, last text {:?}", shell.title.lock().unwrap(), shell.muted.lock().unwrap(), outcome);
+    
+    Ok(())
+}
@@ -0,0 +1,23 @@
+//! The async mediator hands out its futures as they are, without boxing them:
+//! `cargo run --example native_async --features tokio`.
+
+use std::any::type_name_of_val;
+
+use mediator_play::{AsyncColleague, AsyncMediator, AsyncUserMediator, Error, User, UserId};
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    let user_id1 = UserId::new("user-1");
+    let user_id2 = UserId::new("user-2");
+    
+    let mut mediator = AsyncUserMediator::default();
+    let reg1 = mediator.register(user_id1.clone(), User::new(user_id1).belong_to(mediator.clone())).await?;
+    mediator.register(user_id2.clone(), User::new(user_id2.clone()).belong_to(mediator.clone())).await?;
+    
+    let delivery = mediator.consultation(&reg1, &user_id2, "no box involved".to_string());
+    // The compiler's own future type rather than `Pin<Box<dyn Future>>`.
+    println!("[Main] consultation is a {}", type_name_of_val(&delivery));
+    delivery.await?;
+    
+    Ok(())
+}
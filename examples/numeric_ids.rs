@@ -0,0 +1,69 @@
+use mediator_play::{Colleague, ColleagueError, Error, MapMediator, Mediator};
+
+/// A colleague keyed by a plain `u64` instead of a newtype.
+#[derive(Colleague)]
+#[colleague(mediator = MapMediator<Sensor>, message = f64, on_ping = is_healthy)]
+struct Sensor {
+    #[colleague(id)]
+    id: u64,
+}
+
+impl Sensor {
+    fn on_message(&self, reading: f64) -> Result<(), ColleagueError> {
+        if reading.is_nan() {
+            return Err(ColleagueError::new("reading is not a number"));
+        }
+        println!("[sensor-{}] received reading {:.1}", self.id, reading);
+        Ok(())
+    }
+    
+    /// Sensor 7 is stuck and never reports healthy.
+    fn is_healthy(&self) -> bool {
+        self.id != 7
+    }
+}
+
+fn main() -> Result<(), Error> {
+    let mut mediator = MapMediator::<Sensor>::default();
+    
+    let first = mediator.register(1, Sensor { id: 1 }.belong_to(mediator.clone()))?;
+    mediator.register(2, Sensor { id: 2 }.belong_to(mediator.clone()))?;
+    
+    let batch = (3..=5).map(|id| (id, Sensor { id }.belong_to(mediator.clone()))).collect();
+    mediator.register_batch(batch)?;
+    let more: Vec<_> = (6..=7).map(|id| (id, Sensor { id }.belong_to(mediator.clone()))).collect();
+    mediator.extend(more);
+    let clashing = vec![(8, Sensor { id: 8 }.belong_to(mediator.clone())), (2, Sensor { id: 2 }.belong_to(mediator.clone()))];
+    if let Err(e) = mediator.register_batch(clashing) {
+        println!("[Main] {}, sensor-8 registered: {}", e, mediator.is_registered(&8)?);
+    }
+    
+    mediator.consultation(&first, &2, 21.5)?;
+    if let Err(e) = mediator.consultation(&first, &2, f64::NAN) {
+        println!("[Main] {}", e);
+    }
+    
+    let by_name = mediator.clone().map_identifier(|name: &str| name.trim_start_matches("sensor-").parse().unwrap_or(0));
+    println!("[Main] by name: {:?}", by_name.consultation(&first, &"sensor-3", 19.0)?);
+    println!("[Main] registered: {:?}", mediator.registered_ids()?);
+    println!("[Main] sensor-1 healthy: {}, sensor-7 healthy: {}", mediator.ping(&1)?, mediator.ping(&7)?);
+    if let Err(e) = mediator.ping(&99) {
+        println!("[Main] ping failed: {}", e);
+    }
+    println!("[Main] cleared {} sensor(s), {} left", mediator.clear()?, mediator.registered_count()?);
+    let grown = mediator.capacity()?;
+    mediator.shrink_to_fit()?;
+    println!("[Main] capacity shrunk from {} to {}", grown, mediator.capacity()?);
+    
+    let sorted = MapMediator::<Sensor>::builder()
+        .deterministic_broadcast(true)
+        .build();
+    let hub = sorted.register(0, Sensor { id: 0 }.belong_to(sorted.clone()))?;
+    for id in [30, 10, 20] {
+        sorted.register(id, Sensor { id }.belong_to(sorted.clone()))?;
+    }
+    println!("[Main] broadcasting in id order");
+    sorted.broadcast(&hub, 0.0)?;
+    
+    Ok(())
+}
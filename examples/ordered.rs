@@ -0,0 +1,57 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use mediator_play::{Colleague, ColleagueError, Error, GenericMediator, Mediator};
+
+const SENDERS: u32 = 4;
+const PER_SENDER: u32 = 250;
+
+/// Records `(sender, seq)` pairs and panics if two of them are ever read at the same time.
+#[derive(Colleague)]
+#[colleague(mediator = GenericMediator<Ledger>, message = (u32, u32))]
+struct Ledger {
+    #[colleague(id)]
+    id: u32,
+    busy: AtomicBool,
+    log: Mutex<Vec<(u32, u32)>>,
+}
+
+impl Ledger {
+    fn new(id: u32) -> Ledger {
+        Self { id, busy: AtomicBool::new(false), log: Mutex::default() }
+    }
+    
+    fn on_message(&self, msg: (u32, u32)) -> Result<(), ColleagueError> {
+        assert!(!self.busy.swap(true, Ordering::SeqCst), "read_msg entered concurrently");
+        self.log.lock().unwrap().push(msg);
+        self.busy.store(false, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+fn main() -> Result<(), Error> {
+    let mediator = GenericMediator::<Ledger>::default();
+    let ledger = mediator.register(0, Ledger::new(0).belong_to(mediator.clone()).ordered())?;
+    
+    let handles: Vec<_> = (1..=SENDERS)
+        .map(|id| {
+            let sender = mediator.register(id, Ledger::new(id).belong_to(mediator.clone()))?.sender();
+            Ok(thread::spawn(move || {
+                (0..PER_SENDER).try_for_each(|seq| sender.send_msg(&0, (id, seq)).map(drop))
+            }))
+        })
+        .collect::<Result<_, Error>>()?;
+    for handle in handles {
+        handle.join().expect("sender thread panicked")?;
+    }
+    
+    let log = ledger.log.lock().unwrap();
+    for sender in 1..=SENDERS {
+        let seqs = log.iter().filter(|(from, _)| *from == sender).map(|(_, seq)| *seq);
+        assert!(seqs.eq(0..PER_SENDER), "messages from {} arrived out of order", sender);
+    }
+    println!("[Main] {} messages from {} senders read one at a time, each sender in order", log.len(), SENDERS);
+    
+    Ok(())
+}
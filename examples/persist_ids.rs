@@ -0,0 +1,32 @@
+use mediator_play::{Colleague, Error, GroupId, Mediator, MediatorState, User, UserId, UserMediator};
+
+fn main() -> Result<(), Error> {
+    let mediator = UserMediator::with_deadletter();
+    for id in ["user-1", "user-2"].map(UserId::new) {
+        let managed = User::new(id.clone()).belong_to(mediator.clone());
+        mediator.register(id, managed)?;
+    }
+    
+    let json = serde_json::to_string(&mediator.export_ids()).expect("ids serialize");
+    println!("[Main] exported {}", json);
+    
+    let ids: Vec<UserId> = serde_json::from_str(&json).expect("ids deserialize");
+    let mut restored = UserMediator::default();
+    let imported = restored.import_ids(ids)?;
+    println!("[Main] restored {} colleague(s): {:?}", imported.len(), restored.registered_ids()?);
+    
+    let sender = mediator.get(&UserId::new("user-1"))?.expect("user-1 is registered");
+    mediator.join_group(&UserId::new("user-2"), GroupId::new("night-shift"))?;
+    sender.send_msg(&UserId::new("user-3"), "see you after the reload")?;
+    
+    let json = serde_json::to_string(&mediator.export_state()?).expect("state serializes");
+    println!("[Main] exported {}", json);
+    
+    let state: MediatorState<UserId, String> = serde_json::from_str(&json).expect("state deserializes");
+    let mut reloaded = UserMediator::with_deadletter();
+    reloaded.import_ids(state.ids.clone())?;
+    reloaded.import_state(state)?;
+    println!("[Main] reloaded {:?} with {} deadletter(s)", reloaded.snapshot()?, reloaded.deadletters()?.len());
+    
+    Ok(())
+}
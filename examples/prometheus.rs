@@ -0,0 +1,21 @@
+//! Metrics as a Prometheus scrape would see them: `cargo run --example prometheus --features prometheus`.
+
+use mediator_play::{Colleague, Mediator, User, UserId, UserMediator};
+use prometheus::{Encoder, Registry, TextEncoder};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mediator = UserMediator::with_deadletter();
+    let registry = Registry::new();
+    mediator.register_metrics(&registry)?;
+    let (alice, bob) = (UserId::new("alice"), UserId::new("bob"));
+    let sender = mediator.register(alice.clone(), User::new(alice).belong_to(mediator.clone()))?;
+    mediator.register(bob.clone(), User::new(bob.clone()).belong_to(mediator.clone()))?;
+    
+    sender.send_msg(&bob, "scrape me")?;
+    sender.send_msg(&UserId::new("carol"), "nobody home")?;
+    let mut text = Vec::new();
+    TextEncoder::new().encode(&registry.gather(), &mut text)?;
+    print!("{}", String::from_utf8(text)?);
+    
+    Ok(())
+}
@@ -0,0 +1,39 @@
+//! Checking what arrived without writing a colleague, and replaying what happened on a
+//! fresh mediator: `cargo run --example recorder`.
+
+use mediator_play::testing::{self, Divergence, TestRecorder};
+use mediator_play::{Colleague, Error, GenericMediator, Mediator};
+
+type Recorders = GenericMediator<TestRecorder<&'static str, String>>;
+
+fn main() -> Result<(), Error> {
+    let mediator = Recorders::builder()
+        .event_log(true)
+        .deadletter(true)
+        .build();
+    let probe = mediator.register("probe", TestRecorder::new("probe").belong_to(mediator.clone()))?;
+    let sink = mediator.register("sink", TestRecorder::new("sink").belong_to(mediator.clone()))?;
+    
+    mediator.consultation(&probe, &"sink", "first".to_string())?;
+    mediator.consultation(&probe, &"sink", "second".to_string())?;
+    mediator.consultation(&probe, &"nowhere", "lost".to_string())?;
+    println!("[Main] sink recorded {:?}", sink.recorded());
+    println!("[Main] probe recorded {:?}", probe.recorded());
+    
+    let events = mediator.event_log()?;
+    let mut fresh = Recorders::builder()
+        .deadletter(true)
+        .build();
+    let report = testing::replay(&events, &mut fresh, |id| TestRecorder::new(*id))?;
+    println!("[Main] replayed faithfully: {}, {:?}", report.is_faithful(), report);
+    
+    let mut strict = Recorders::default();
+    let report = testing::replay(&events, &mut strict, |id| TestRecorder::new(*id))?;
+    for divergence in report.divergences {
+        if let Divergence::Consultation { index, to, recorded, replayed, .. } = divergence {
+            println!("[Main] without deadletters, event {} to {} came out as {:?} instead of {:?}", index, to, replayed, recorded);
+        }
+    }
+    
+    Ok(())
+}
@@ -0,0 +1,55 @@
+//! At-least-once delivery to a worker that turns its first job down and gets it again:
+//! `cargo run --example redelivery`.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+use mediator_play::{AckHandle, Clock, Colleague, ColleagueError, Error, GenericMediator, Mediator, MemoryAckStore, MockClock};
+
+#[derive(Colleague)]
+#[colleague(mediator = GenericMediator<Worker>, message = String, read_reliably = on_job)]
+struct Worker {
+    #[colleague(id)]
+    id: u32,
+    attempts: AtomicU32,
+}
+
+impl Worker {
+    fn on_message(&self, job: String) -> Result<(), ColleagueError> {
+        println!("[worker-{}] {}", self.id, job);
+        Ok(())
+    }
+    
+    fn on_job(&self, job: String, ack: AckHandle) -> Result<(), ColleagueError> {
+        self.attempts.fetch_add(1, Ordering::Relaxed);
+        if ack.attempt() == 1 {
+            println!("[worker-{}] too busy for {}, nack", self.id, job);
+            ack.nack();
+            return Ok(());
+        }
+        println!("[worker-{}] {} done on attempt {}, ack", self.id, job, ack.attempt());
+        ack.ack();
+        Ok(())
+    }
+}
+
+fn main() -> Result<(), Error> {
+    let clock = MockClock::new();
+    let mediator = GenericMediator::<Worker>::builder()
+        .clock(clock.clone())
+        .ack_store(MemoryAckStore::default())
+        .build();
+    let desk = mediator.register(0, Worker { id: 0, attempts: AtomicU32::new(0) }.belong_to(mediator.clone()))?;
+    let worker = mediator.register(1, Worker { id: 1, attempts: AtomicU32::new(0) }.belong_to(mediator.clone()))?;
+    
+    let backup = mediator.consult_reliably(&desk, &1, "backup".to_string(), Duration::from_secs(5))?;
+    println!("[Main] in flight: {}", mediator.in_flight_count());
+    println!("[Main] redelivered: {}", mediator.redeliver(clock.now())?);
+    println!("[Main] redelivered: {}", mediator.redeliver(clock.now())?);
+    println!("[Main] attempts: {}, in flight: {}", worker.attempts.load(Ordering::Relaxed), mediator.in_flight_count());
+    for record in mediator.ack_history(backup) {
+        println!("[Main] audit: {:?} for worker-{}", record.outcome, record.recipient);
+    }
+    
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use mediator_play::{Colleague, ColleagueError, Error, GenericMediator, Mediator, Message, ReplyContext};
+
+/// Acknowledges every question it is asked by replying to whoever asked it.
+#[derive(Colleague)]
+#[colleague(mediator = GenericMediator<Clerk>, message = String, read_with_context = on_mail)]
+struct Clerk {
+    #[colleague(id)]
+    name: &'static str,
+    replies: AtomicUsize,
+}
+
+impl Clerk {
+    fn new(name: &'static str) -> Clerk {
+        Self { name, replies: AtomicUsize::new(0) }
+    }
+    
+    fn on_message(&self, msg: String) -> Result<(), ColleagueError> {
+        if !msg.ends_with('?') {
+            self.replies.fetch_add(1, Ordering::Relaxed);
+        }
+        Ok(())
+    }
+    
+    fn on_mail(&self, msg: Message<String>, ctx: ReplyContext<'_, Self>) -> Result<(), ColleagueError> {
+        println!("[{}] {:?} from {}", self.name, msg.body, ctx.from());
+        if ctx.deadline().is_some_and(|deadline| deadline.is_expired()) {
+            println!("[{}] too late to answer {:?}", self.name, msg.body);
+            return Err(ColleagueError::new("deadline passed"));
+        }
+        if let Some(question) = msg.body.strip_prefix("ask carol: ") {
+            return ctx.mediator().transfer_message(&ctx, &"carol", question.to_string())
+                .map(drop)
+                .map_err(|e| ColleagueError::new(e.to_string()));
+        }
+        if msg.body.ends_with('?') {
+            ctx.reply("got it".to_string())
+                .map_err(|e| ColleagueError::new(e.to_string()))?;
+        }
+        self.on_message(msg.body)
+    }
+}
+
+fn main() -> Result<(), Error> {
+    let mediator = GenericMediator::<Clerk>::default();
+    let alice = mediator.register("alice", Clerk::new("alice").belong_to(mediator.clone()))?;
+    mediator.register("bob", Clerk::new("bob").belong_to(mediator.clone()))?;
+    mediator.register("carol", Clerk::new("carol").belong_to(mediator.clone()))?;
+    
+    mediator.consultation(&alice, &"bob", "did you get the invoice?".to_string())?;
+    mediator.consultation(&alice, &"bob", "and the receipt?".to_string())?;
+    mediator.consultation(&alice, &"bob", "ask carol: is the invoice paid?".to_string())?;
+    mediator.consult_with_deadline(&alice, &"bob", "before lunch?".to_string(), Instant::now() + Duration::from_secs(60))?;
+    let late = mediator.consult_with_deadline(&alice, &"bob", "by yesterday?".to_string(), Instant::now())?;
+    println!("[Main] asking for yesterday: {:?}", late);
+    
+    println!("[Main] alice got {} replies", alice.replies.load(Ordering::Relaxed));
+    Ok(())
+}
@@ -0,0 +1,44 @@
+//! Broadcasting one allocation to every recipient: `cargo run --example shared`.
+//!
+//! With `Arc<str>` as the message type, each recipient gets a clone of the `Arc` rather than a
+//! copy of the text.
+
+use std::sync::{Arc, Mutex};
+
+use mediator_play::{Colleague, ColleagueError, Error, MapMediator, Mediator};
+
+/// Keeps every message it hears, so the references to them stay around.
+#[derive(Colleague)]
+#[colleague(mediator = MapMediator<Listener>, message = Arc<str>)]
+struct Listener {
+    #[colleague(id)]
+    id: u32,
+    heard: Mutex<Vec<Arc<str>>>,
+}
+
+impl Listener {
+    fn new(id: u32) -> Self {
+        Self { id, heard: Mutex::new(Vec::new()) }
+    }
+    
+    fn on_message(&self, msg: Arc<str>) -> Result<(), ColleagueError> {
+        self.heard.lock().unwrap().push(msg);
+        Ok(())
+    }
+}
+
+fn main() -> Result<(), Error> {
+    let mediator = MapMediator::<Listener>::default();
+    let announcer = mediator.register(0, Listener::new(0).belong_to(mediator.clone()))?;
+    for id in 1..=100 {
+        mediator.register(id, Listener::new(id).belong_to(mediator.clone()))?;
+    }
+    
+    let news: Arc<str> = Arc::from("the same text for everyone");
+    let reached = mediator.broadcast(&announcer, Arc::clone(&news))?;
+    // One reference for `news` itself and one for each listener, all to the same text.
+    println!("[Main] broadcast reached {} listener(s), {} reference(s) to one allocation",
+        reached, Arc::strong_count(&news));
+    
+    Ok(())
+}
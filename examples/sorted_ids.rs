@@ -0,0 +1,33 @@
+//! A mediator backed by a `BTreeMap`, listing its colleagues in id order:
+//! `cargo run --example sorted_ids`.
+
+use mediator_play::{Colleague, ColleagueError, Error, Mediator, OrderedMediator};
+
+#[derive(Colleague)]
+#[colleague(mediator = OrderedMediator<Station>, message = String)]
+struct Station {
+    #[colleague(id)]
+    id: u32,
+}
+
+impl Station {
+    fn on_message(&self, msg: String) -> Result<(), ColleagueError> {
+        println!("[station-{}] {}", self.id, msg);
+        Ok(())
+    }
+}
+
+fn main() -> Result<(), Error> {
+    let mediator = OrderedMediator::<Station>::default();
+    
+    let mut stations = Vec::new();
+    for id in [42, 7, 19, 3] {
+        stations.push(mediator.register(id, Station { id }.belong_to(mediator.clone()))?);
+    }
+    println!("[Main] registered: {:?}", mediator.registered_ids()?);
+    
+    let reached = mediator.broadcast(&stations[0], "roll call".to_string())?;
+    println!("[Main] roll call reached {} station(s) in order", reached);
+    
+    Ok(())
+}
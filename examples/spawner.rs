@@ -0,0 +1,42 @@
+//! Registering colleagues from inside a handler: `cargo run --example spawner`.
+
+use mediator_play::{Colleague, ColleagueError, Error, MapMediator, Mediator};
+
+/// Opens a door for every name it's sent, registering it right away, unless it's open already.
+#[derive(Colleague)]
+#[colleague(mediator = MapMediator<Door>, message = String)]
+struct Door {
+    #[colleague(id)]
+    name: String,
+    mediator: MapMediator<Door>,
+}
+
+impl Door {
+    fn new(name: impl Into<String>, mediator: &MapMediator<Door>) -> Self {
+        Self { name: name.into(), mediator: mediator.clone() }
+    }
+    
+    fn on_message(&self, name: String) -> Result<(), ColleagueError> {
+        let door = Door::new(name.clone(), &self.mediator).belong_to(self.mediator.clone());
+        match self.mediator.register(name.clone(), door) {
+            Ok(_) => println!("[{}] opened a door for {}", self.name, name),
+            Err(Error::AlreadyRegistered { .. }) => println!("[{}] {} is open already", self.name, name),
+            Err(e) => return Err(ColleagueError::new(e.to_string())),
+        }
+        Ok(())
+    }
+}
+
+fn main() -> Result<(), Error> {
+    let mediator = MapMediator::<Door>::default();
+    let front = mediator.register("front".to_string(), Door::new("front", &mediator).belong_to(mediator.clone()))?;
+    mediator.register("lobby".to_string(), Door::new("lobby", &mediator).belong_to(mediator.clone()))?;
+    
+    mediator.consultation(&front, &"lobby".to_string(), "kitchen".to_string())?;
+    mediator.consultation(&front, &"kitchen".to_string(), "pantry".to_string())?;
+    // Every door reached registers while the broadcast is still going on.
+    mediator.broadcast(&front, "cellar".to_string())?;
+    println!("[Main] {} door(s) registered", mediator.registered_count()?);
+    
+    Ok(())
+}
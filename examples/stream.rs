@@ -0,0 +1,35 @@
+//! A colleague consuming its messages as a `Stream`:
+//! `cargo run --example stream --features tokio`.
+
+use futures::StreamExt;
+use mediator_play::{Colleague, Error, Mediator, User, UserId, UserMediator};
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    let user_id1 = UserId::new("user-1");
+    let user_id2 = UserId::new("user-2");
+    
+    let mediator = UserMediator::default();
+    let reg1 = mediator.register(user_id1.clone(), User::new(user_id1).belong_to(mediator.clone()))?;
+    let (_reg2, stream) = mediator.register_stream(user_id2.clone(), User::new(user_id2.clone()).belong_to(mediator.clone()))?;
+    
+    reg1.send_msg(&user_id2, "first")?;
+    reg1.send_msg(&user_id2, "second")?;
+    
+    let received: Vec<String> = stream.take(2).collect().await;
+    println!("[Main] user-2 streamed {:?}", received);
+    
+    let user_id3 = UserId::new("user-3");
+    let (reg3, task) = mediator.spawn_colleague(user_id3.clone(), User::new(user_id3.clone()).belong_to(mediator.clone()))?;
+    reg1.send_msg(&user_id3, "read on a task of its own")?;
+    reg1.send_msg(&user_id3, "and another")?;
+    while reg3.received() < 2 {
+        tokio::task::yield_now().await;
+    }
+    mediator.deregister(&user_id3)?;
+    drop(reg3);
+    task.await.expect("colleague task panicked");
+    println!("[Main] user-3 finished reading");
+    
+    Ok(())
+}
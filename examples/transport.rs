@@ -0,0 +1,61 @@
+//! Two mediators, standing in for two processes, reaching each other through a broker:
+//! `cargo run --example transport --features tokio`.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use futures::channel::mpsc::{self, UnboundedSender};
+use futures::stream::{BoxStream, StreamExt};
+use mediator_play::{Colleague, ColleagueError, Error, Mediator, Transport, User, UserId, UserMediator};
+
+type Subscribers = HashMap<String, Vec<UnboundedSender<Vec<u8>>>>;
+
+/// An in-memory broker handing every payload published to a topic to its subscribers.
+#[derive(Clone, Default)]
+struct Broker(Arc<Mutex<Subscribers>>);
+
+impl Transport for Broker {
+    fn publish(&self, topic: &str, payload: Vec<u8>) -> Result<(), ColleagueError> {
+        println!("[broker] {} <- {} byte(s)", topic, payload.len());
+        let mut topics = self.0.lock().unwrap();
+        let subscribers = topics.entry(topic.to_string()).or_default();
+        subscribers.retain(|subscriber| subscriber.unbounded_send(payload.clone()).is_ok());
+        Ok(())
+    }
+    
+    fn subscribe(&self, topic: &str) -> Result<BoxStream<'static, Vec<u8>>, ColleagueError> {
+        let (tx, rx) = mpsc::unbounded();
+        self.0.lock().unwrap().entry(topic.to_string()).or_default().push(tx);
+        Ok(rx.boxed())
+    }
+}
+
+fn encode(from: &UserId, msg: &String) -> Vec<u8> {
+    format!("{}\n{}", from.as_ref(), msg).into_bytes()
+}
+
+fn decode(payload: &[u8]) -> Option<(UserId, String)> {
+    let (from, msg) = std::str::from_utf8(payload).ok()?.split_once('\n')?;
+    Some((UserId::new(from), msg.to_string()))
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    let broker = Broker::default();
+    let here = UserMediator::builder().transport(broker.clone(), encode, decode).build();
+    let there = UserMediator::builder().transport(broker, encode, decode).build();
+    
+    let (alice, bob) = (UserId::new("alice"), UserId::new("bob"));
+    let sender = here.register(alice.clone(), User::new(alice).belong_to(here.clone()))?;
+    there.register(bob.clone(), User::new(bob.clone()).belong_to(there.clone()))?;
+    
+    println!("[Main] here routes bob {:?}", here.route_preview(&bob)?);
+    println!("[Main] {:?}", sender.send_msg(&bob, "hello from another process")?);
+    // Delivery happens on a task of its own.
+    while there.metrics().delivered == 0 {
+        tokio::task::yield_now().await;
+    }
+    println!("[Main] there delivered {} remote message(s)", there.metrics().delivered);
+    
+    Ok(())
+}
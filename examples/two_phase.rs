@@ -0,0 +1,46 @@
+//! All-or-nothing delivery: `cargo run --example two_phase`.
+
+use std::sync::Mutex;
+
+use mediator_play::{Colleague, ColleagueError, Error, MapMediator, Mediator};
+
+/// Books transfers, but only up to its limit.
+#[derive(Colleague)]
+#[colleague(mediator = MapMediator<Ledger>, message = u32, can_accept = within_limit)]
+struct Ledger {
+    #[colleague(id)]
+    name: &'static str,
+    limit: u32,
+    booked: Mutex<Vec<u32>>,
+}
+
+impl Ledger {
+    fn new(name: &'static str, limit: u32) -> Self {
+        Self { name, limit, booked: Mutex::new(Vec::new()) }
+    }
+    
+    fn within_limit(&self, amount: &u32) -> bool {
+        *amount <= self.limit
+    }
+    
+    fn on_message(&self, amount: u32) -> Result<(), ColleagueError> {
+        self.booked.lock().unwrap().push(amount);
+        println!("[{}] booked {}", self.name, amount);
+        Ok(())
+    }
+}
+
+fn main() -> Result<(), Error> {
+    let mediator = MapMediator::<Ledger>::default();
+    let bank = mediator.register("bank", Ledger::new("bank", u32::MAX).belong_to(mediator.clone()))?;
+    let checking = mediator.register("checking", Ledger::new("checking", 1_000).belong_to(mediator.clone()))?;
+    let savings = mediator.register("savings", Ledger::new("savings", 100).belong_to(mediator.clone()))?;
+    
+    mediator.consult_2pc(&bank, &["checking", "savings"], 50)?;
+    if let Err(e) = mediator.consult_2pc(&bank, &["checking", "savings"], 500) {
+        println!("[Main] {}", e);
+    }
+    println!("[Main] checking booked {:?}, savings booked {:?}", checking.booked.lock().unwrap(), savings.booked.lock().unwrap());
+    
+    Ok(())
+}
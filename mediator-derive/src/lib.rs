@@ -0,0 +1,239 @@
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, Ident, Type};
+
+/// Derives `mediator_play::Colleague`.
+///
+/// ```ignore
+/// #[derive(Colleague)]
+/// #[colleague(mediator = UserMediator, message = String, request = UserRequest, response = UserResponse)]
+/// pub struct User {
+///     #[colleague(id)]
+///     id: UserId,
+/// }
+/// ```
+///
+/// The identifier is either the field marked `#[colleague(id)]` or the one named by `id = field`
+/// on the struct. `request`/`response` default to `()`. Messages and requests are handed to the
+/// inherent methods `on_message(&self, msg) -> Result<(), ColleagueError>` and
+/// `on_request(&self, req) -> Response`, which can
+/// be renamed with `read = method` and `handle = method`. When `request` is omitted no
+/// `on_request` is needed. With `read_with_headers = method`, messages sent with headers go to
+/// `method(&self, msg: Message<_>) -> Result<(), ColleagueError>` instead, and with
+/// `read_with_context = method` every message goes to
+/// `method(&self, msg: Message<_>, ctx: ReplyContext<'_, Self>) -> Result<(), ColleagueError>`.
+/// With `read_msg_ref = method`, messages lent out by reference go to
+/// `method(&self, msg: &_) -> Result<(), ColleagueError>` rather than being cloned for `read`.
+/// With `read_reliably = method`, messages sent at least once go to
+/// `method(&self, msg, ack: AckHandle) -> Result<(), ColleagueError>`, which settles `ack` itself.
+/// Payloads streamed in chunks go to `read_chunk = method` as
+/// `method(&self, chunk: Vec<u8>) -> Result<(), ColleagueError>`, followed by a call to
+/// `read_end = method`, `method(&self) -> Result<(), ColleagueError>`.
+/// With `on_ping = method`, health checks go to `method(&self) -> bool`, and with
+/// `on_shutdown = method`, shutdowns notify `method(&self)`. Two-phase deliveries ask
+/// `can_accept = method` as `method(&self, msg: &_) -> bool`.
+#[proc_macro_derive(Colleague, attributes(colleague))]
+pub fn derive_colleague(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let mut mediator: Option<Type> = None;
+    let mut message: Option<Type> = None;
+    let mut request: Option<Type> = None;
+    let mut response: Option<Type> = None;
+    let mut id_field: Option<Ident> = None;
+    let mut read = Ident::new("on_message", Span::call_site());
+    let mut handle = Ident::new("on_request", Span::call_site());
+    let mut read_with_headers: Option<Ident> = None;
+    let mut read_with_context: Option<Ident> = None;
+    let mut read_msg_ref: Option<Ident> = None;
+    let mut read_reliably: Option<Ident> = None;
+    let mut read_chunk: Option<Ident> = None;
+    let mut read_end: Option<Ident> = None;
+    let mut on_ping: Option<Ident> = None;
+    let mut on_shutdown: Option<Ident> = None;
+    let mut can_accept: Option<Ident> = None;
+    
+    for attr in input.attrs.iter().filter(|attr| attr.path().is_ident("colleague")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("mediator") {
+                mediator = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("message") {
+                message = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("request") {
+                request = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("response") {
+                response = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("id") {
+                id_field = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("read") {
+                read = meta.value()?.parse()?;
+            } else if meta.path.is_ident("read_with_headers") {
+                read_with_headers = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("read_with_context") {
+                read_with_context = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("read_msg_ref") {
+                read_msg_ref = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("read_reliably") {
+                read_reliably = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("read_chunk") {
+                read_chunk = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("read_end") {
+                read_end = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("on_ping") {
+                on_ping = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("on_shutdown") {
+                on_shutdown = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("can_accept") {
+                can_accept = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("handle") {
+                handle = meta.value()?.parse()?;
+            } else {
+                return Err(meta.error("unsupported colleague attribute"));
+            }
+            Ok(())
+        })?;
+    }
+    
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(&input.ident, "Colleague can only be derived for structs"));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new_spanned(&input.ident, "Colleague requires named fields"));
+    };
+    
+    let mut marked = None;
+    for field in &fields.named {
+        for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("colleague")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("id") {
+                    marked = Some(field);
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `#[colleague(id)]`"))
+                }
+            })?;
+        }
+    }
+    
+    let id = match (marked, &id_field) {
+        (Some(field), _) => field,
+        (None, Some(name)) => fields.named.iter()
+            .find(|field| field.ident.as_ref() == Some(name))
+            .ok_or_else(|| syn::Error::new_spanned(name, "no field with this name"))?,
+        (None, None) => return Err(syn::Error::new_spanned(
+            &input.ident,
+            "mark the identifier field with `#[colleague(id)]` or name it with `#[colleague(id = field)]`",
+        )),
+    };
+    let id_ident = id.ident.as_ref().expect("named field");
+    let id_ty = &id.ty;
+    
+    let mediator = mediator.ok_or_else(|| syn::Error::new_spanned(&input.ident, "missing `#[colleague(mediator = ...)]`"))?;
+    let message = message.ok_or_else(|| syn::Error::new_spanned(&input.ident, "missing `#[colleague(message = ...)]`"))?;
+    
+    let handle_body = if request.is_some() {
+        quote! { Self::#handle(self, req) }
+    } else {
+        quote! { let _ = req; }
+    };
+    let read_with_headers = read_with_headers.map(|method| quote! {
+        fn read_with_headers(&self, msg: ::mediator_play::Message<Self::Message>) -> ::core::result::Result<(), ::mediator_play::ColleagueError> {
+            Self::#method(self, msg)
+        }
+    });
+    let read_with_context = read_with_context.map(|method| quote! {
+        fn read_with_context(&self, msg: ::mediator_play::Message<Self::Message>, ctx: ::mediator_play::ReplyContext<'_, Self>) -> ::core::result::Result<(), ::mediator_play::ColleagueError> {
+            Self::#method(self, msg, ctx)
+        }
+    });
+    let read_msg_ref = read_msg_ref.map(|method| quote! {
+        fn read_msg_ref(&self, msg: &Self::Message) -> ::core::result::Result<(), ::mediator_play::ColleagueError> {
+            Self::#method(self, msg)
+        }
+    });
+    let read_reliably = read_reliably.map(|method| quote! {
+        fn read_reliably(&self, msg: Self::Message, ack: ::mediator_play::AckHandle) -> ::core::result::Result<(), ::mediator_play::ColleagueError> {
+            Self::#method(self, msg, ack)
+        }
+    });
+    let read_chunk = read_chunk.map(|method| quote! {
+        fn read_chunk(&self, chunk: ::std::vec::Vec<u8>) -> ::core::result::Result<(), ::mediator_play::ColleagueError> {
+            Self::#method(self, chunk)
+        }
+    });
+    let read_end = read_end.map(|method| quote! {
+        fn read_end(&self) -> ::core::result::Result<(), ::mediator_play::ColleagueError> {
+            Self::#method(self)
+        }
+    });
+    let on_ping = on_ping.map(|method| quote! {
+        fn on_ping(&self) -> bool {
+            Self::#method(self)
+        }
+    });
+    let on_shutdown = on_shutdown.map(|method| quote! {
+        fn on_shutdown(&self) {
+            Self::#method(self)
+        }
+    });
+    let can_accept = can_accept.map(|method| quote! {
+        fn can_accept(&self, msg: &Self::Message) -> bool {
+            Self::#method(self, msg)
+        }
+    });
+    let request = request.unwrap_or_else(|| syn::parse_quote!(()));
+    let response = response.unwrap_or_else(|| syn::parse_quote!(()));
+    
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    
+    Ok(quote! {
+        impl #impl_generics ::mediator_play::Colleague for #name #ty_generics #where_clause {
+            type Identifier = #id_ty;
+            type Mediator = #mediator;
+            type Message = #message;
+            type Request = #request;
+            type Response = #response;
+            
+            fn id(&self) -> &Self::Identifier {
+                &self.#id_ident
+            }
+            
+            fn belong_to(self, mediator: Self::Mediator) -> ::mediator_play::Managed<Self> {
+                ::mediator_play::Managed::new(self, mediator)
+            }
+            
+            fn read_msg(&self, msg: Self::Message) -> ::core::result::Result<(), ::mediator_play::ColleagueError> {
+                Self::#read(self, msg)
+            }
+            
+            #read_with_headers
+            
+            #read_with_context
+            
+            #read_msg_ref
+            
+            #read_reliably
+            
+            #read_chunk
+            
+            #read_end
+            
+            #on_ping
+            
+            #on_shutdown
+            
+            #can_accept
+            
+            fn handle_request(&self, req: Self::Request) -> Self::Response {
+                #handle_body
+            }
+        }
+    })
+}
@@ -0,0 +1,68 @@
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender, TryRecvError};
+use std::time::Duration;
+
+use crate::error::ColleagueError;
+use crate::message::MessageId;
+
+/// Resolves once the recipient has read a message sent with
+/// [`GenericMediator::consult_with_ack`](crate::GenericMediator::consult_with_ack),
+/// reporting whether its `read_msg` accepted it.
+///
+/// A message that was never read, because it was filtered, deadlettered or discarded from a
+/// mailbox on shutdown, resolves to an error too. Colleagues registered with a channel
+/// acknowledge as soon as the message is sent down the channel.
+pub struct Ack {
+    id: MessageId,
+    rx: Receiver<Result<(), ColleagueError>>,
+}
+
+impl Ack {
+    pub(crate) fn new(id: MessageId, rx: Receiver<Result<(), ColleagueError>>) -> Ack {
+        Self { id, rx }
+    }
+    
+    pub fn message_id(&self) -> MessageId {
+        self.id
+    }
+    
+    /// Blocks until the recipient has read the message.
+    pub fn wait(self) -> Result<(), ColleagueError> {
+        self.rx.recv().unwrap_or_else(|_| Err(never_read()))
+    }
+    
+    /// Like [`Ack::wait`], but gives up with `None` after `timeout`.
+    pub fn wait_timeout(&self, timeout: Duration) -> Option<Result<(), ColleagueError>> {
+        match self.rx.recv_timeout(timeout) {
+            Ok(ack) => Some(ack),
+            Err(RecvTimeoutError::Timeout) => None,
+            Err(RecvTimeoutError::Disconnected) => Some(Err(never_read())),
+        }
+    }
+    
+    /// Returns `None` while the message is still waiting to be read.
+    pub fn try_wait(&self) -> Option<Result<(), ColleagueError>> {
+        match self.rx.try_recv() {
+            Ok(ack) => Some(ack),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err(never_read())),
+        }
+    }
+}
+
+/// The sending half of an [`Ack`], travelling with its message until it is read.
+pub(crate) struct Acker(SyncSender<Result<(), ColleagueError>>);
+
+impl Acker {
+    pub(crate) fn ack(self, result: &Result<(), ColleagueError>) {
+        let _ = self.0.send(result.clone());
+    }
+    
+    pub(crate) fn pair() -> (Acker, Receiver<Result<(), ColleagueError>>) {
+        let (tx, rx) = mpsc::sync_channel(1);
+        (Acker(tx), rx)
+    }
+}
+
+fn never_read() -> ColleagueError {
+    ColleagueError::new("the message was dropped before it was read")
+}
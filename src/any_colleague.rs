@@ -0,0 +1,156 @@
+use std::any::{type_name, Any};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::AtomicU64;
+use std::sync::Arc;
+
+use crate::colleague::Colleague;
+use crate::error::{ColleagueError, Error};
+use crate::lock::{self, RwLock};
+use crate::message::MessageId;
+
+/// Identifier of any hashable type, so colleagues keyed by `UserId`, `BotId`, ... can share
+/// one registry. Keys of different types never compare equal.
+pub struct AnyId(Box<dyn AnyKey>);
+
+impl AnyId {
+    pub fn new<K>(key: K) -> AnyId
+        where K: Eq + Hash + Debug + Send + Sync + 'static
+    {
+        Self(Box::new(key))
+    }
+    
+    pub fn downcast_ref<K: 'static>(&self) -> Option<&K> {
+        self.0.as_any().downcast_ref()
+    }
+}
+
+impl PartialEq for AnyId {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.eq_key(other.0.as_ref())
+    }
+}
+
+impl Eq for AnyId {}
+
+impl Hash for AnyId {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.as_any().type_id().hash(state);
+        self.0.hash_key(state);
+    }
+}
+
+impl Debug for AnyId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&self.0, f)
+    }
+}
+
+trait AnyKey: Debug + Send + Sync {
+    fn as_any(&self) -> &dyn Any;
+    fn eq_key(&self, other: &dyn AnyKey) -> bool;
+    fn hash_key(&self, state: &mut dyn Hasher);
+}
+
+impl<K> AnyKey for K
+    where K: Eq + Hash + Debug + Send + Sync + 'static
+{
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    
+    fn eq_key(&self, other: &dyn AnyKey) -> bool {
+        other.as_any().downcast_ref::<K>().is_some_and(|other| self.eq(other))
+    }
+    
+    fn hash_key(&self, mut state: &mut dyn Hasher) {
+        self.hash(&mut state);
+    }
+}
+
+/// Object-safe view of a [`Colleague`] whose message type is only known at runtime.
+pub trait AnyColleague: Send + Sync {
+    fn message_type(&self) -> &'static str;
+    /// Reads `msg` if it is this colleague's message type, otherwise hands it back. The inner
+    /// result is what the colleague's `read_msg` returned.
+    fn read_any(&self, msg: Box<dyn Any + Send>) -> Result<Result<(), ColleagueError>, Box<dyn Any + Send>>;
+}
+
+impl<T> AnyColleague for T
+    where T: Colleague + Send + Sync,
+          T::Message: Send + 'static
+{
+    fn message_type(&self) -> &'static str {
+        type_name::<T::Message>()
+    }
+    
+    fn read_any(&self, msg: Box<dyn Any + Send>) -> Result<Result<(), ColleagueError>, Box<dyn Any + Send>> {
+        let msg = msg.downcast::<T::Message>()?;
+        Ok(self.read_msg(*msg))
+    }
+}
+
+/// A bus for colleagues of different types, dispatching through [`AnyColleague`].
+#[derive(Default)]
+pub struct AnyMediator {
+    colleagues: Arc<RwLock<HashMap<AnyId, Arc<dyn AnyColleague>>>>,
+    sequence: Arc<AtomicU64>,
+}
+
+impl AnyMediator {
+    pub fn register<T>(&mut self, colleague: T) -> Result<Arc<T>, Error>
+        where T: Colleague + Send + Sync + 'static,
+              T::Identifier: Eq + Hash + Debug + Clone + Send + Sync + 'static,
+              T::Message: Send + 'static
+    {
+        let colleague = Arc::new(colleague);
+        match lock::recover_write(&self.colleagues).entry(AnyId::new(colleague.id().clone())) {
+            Entry::Occupied(occupied) => Err(Error::AlreadyRegistered { id: format!("{:?}", occupied.key()) }),
+            Entry::Vacant(vacant) => {
+                vacant.insert(colleague.clone());
+                Ok(colleague)
+            }
+        }
+    }
+    
+    pub fn deregister(&mut self, id: &AnyId) -> Result<bool, Error> {
+        let removed = lock::recover_write(&self.colleagues)
+            .remove(id);
+        Ok(removed.is_some())
+    }
+    
+    pub fn is_registered(&self, id: &AnyId) -> Result<bool, Error> {
+        let found = lock::recover_read(&self.colleagues)
+            .contains_key(id);
+        Ok(found)
+    }
+    
+    /// Delivers `msg` to `to`, failing with [`Error::MessageTypeMismatch`] if the recipient
+    /// doesn't take messages of type `M`.
+    pub fn consultation<M: Send + 'static>(&self, to: &AnyId, msg: M) -> Result<MessageId, Error> {
+        let recipient = lock::recover_read(&self.colleagues)
+            .get(to)
+            .cloned()
+            .ok_or_else(|| Error::NotRegistered { id: format!("{:?}", to) })?;
+        
+        recipient.read_any(Box::new(msg))
+            .map_err(|_| Error::MessageTypeMismatch {
+                id: format!("{:?}", to),
+                expected: recipient.message_type(),
+                got: type_name::<M>(),
+            })?
+            .map_err(|e| Error::DeliveryRejected { id: format!("{:?}", to), reason: e.reason })?;
+        Ok(MessageId::next(&self.sequence))
+    }
+}
+
+impl Clone for AnyMediator {
+    fn clone(&self) -> Self {
+        Self {
+            colleagues: Arc::clone(&self.colleagues),
+            sequence: Arc::clone(&self.sequence),
+        }
+    }
+}
@@ -0,0 +1,414 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::future::Future;
+use std::ops::Deref;
+use std::panic::AssertUnwindSafe;
+use std::pin::pin;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::future::{join_all, FutureExt};
+use tokio::sync::{Notify, RwLock};
+use tokio_util::sync::CancellationToken;
+
+use crate::error::Error;
+use crate::user::{User, UserId};
+
+/// Implementors write plain `async fn`s; the futures are handed out unboxed and are required
+/// to be `Send`, so they can be spawned onto a multi-threaded runtime.
+pub trait AsyncColleague: Sized + Send + Sync + 'static {
+    type Identifier: Send + Sync;
+    type Mediator: AsyncMediator<Self>;
+    type Message: Send;
+    fn id(&self) -> &Self::Identifier;
+    fn belong_to(self, mediator: Self::Mediator) -> AsyncManaged<Self>;
+    fn read_msg(&self, msg: Self::Message) -> impl Future<Output = ()> + Send;
+}
+
+/// Implemented with plain `async fn`s like [`AsyncColleague`]; every future it hands out is
+/// unboxed and `Send`.
+pub trait AsyncMediator<T: AsyncColleague>: Send + Sync {
+    /// Fails with [`Error::AlreadyRegistered`] if `id` is taken, like the synchronous mediators.
+    fn register(&mut self, id: T::Identifier, registered: AsyncManaged<T>) -> impl Future<Output = Result<AsyncRegistered<T>, Error>> + Send;
+    fn deregister(&mut self, id: &T::Identifier) -> impl Future<Output = Result<Option<AsyncRegistered<T>>, Error>> + Send;
+    fn consultation(&self, user: &T, to: &T::Identifier, msg: T::Message) -> impl Future<Output = Result<(), Error>> + Send;
+    /// Like [`AsyncMediator::consultation`] but gives up with [`Error::DeliveryTimeout`] once
+    /// `timeout` has elapsed. The abandoned delivery releases its lock as it is dropped,
+    /// so the registry stays usable.
+    fn consultation_timeout(&self, user: &T, to: &T::Identifier, msg: T::Message, timeout: Duration) -> impl Future<Output = Result<(), Error>> + Send
+        where T::Identifier: Debug
+    {
+        async move {
+            tokio::time::timeout(timeout, self.consultation(user, to, msg)).await
+                .map_err(|_| Error::DeliveryTimeout { id: format!("{:?}", to), timeout })?
+        }
+    }
+    /// Like [`AsyncMediator::consultation`] but stops with [`Error::Cancelled`] once `token` is
+    /// cancelled, e.g. because the sender disconnected. A token cancelled up front means nothing
+    /// is delivered; a handler already running is dropped at its next `.await`, so it should
+    /// leave its state consistent across awaits.
+    fn consultation_cancellable(&self, user: &T, to: &T::Identifier, msg: T::Message, token: &CancellationToken) -> impl Future<Output = Result<(), Error>> + Send {
+        async move {
+            token.run_until_cancelled(self.consultation(user, to, msg)).await
+                .unwrap_or(Err(Error::Cancelled))
+        }
+    }
+    fn broadcast(&self, from: &T, msg: T::Message) -> impl Future<Output = Result<usize, Error>> + Send
+        where T::Message: Clone;
+    /// Like [`AsyncMediator::broadcast`], but awaits every recipient's `read_msg` at once
+    /// instead of one after another. A handler that panics doesn't keep the others from
+    /// finishing; it just isn't counted among the recipients reached.
+    fn broadcast_concurrent(&self, from: &T, msg: T::Message) -> impl Future<Output = Result<usize, Error>> + Send
+        where T::Message: Clone;
+}
+
+pub struct AsyncManaged<T: AsyncColleague> {
+    inner: T,
+    mediator: T::Mediator
+}
+
+impl<T: AsyncColleague> AsyncManaged<T> {
+    pub(crate) fn new(t: T, bus: T::Mediator) -> Self {
+        Self { inner: t, mediator: bus }
+    }
+}
+
+impl<T: AsyncColleague> Deref for AsyncManaged<T> {
+    type Target = T;
+    
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+pub struct AsyncRegistered<T: AsyncColleague>(Arc<AsyncManaged<T>>);
+
+impl<T: AsyncColleague> AsyncRegistered<T> {
+    pub(crate) fn new(managed: AsyncManaged<T>) -> Self {
+        Self(Arc::new(managed))
+    }
+    
+    pub fn as_mediator(&self) -> &T::Mediator {
+        &self.0.mediator
+    }
+}
+
+impl<T: AsyncColleague> Clone for AsyncRegistered<T> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<T: AsyncColleague> Deref for AsyncRegistered<T> {
+    type Target = T;
+    
+    fn deref(&self) -> &Self::Target {
+        &self.0.inner
+    }
+}
+
+impl AsyncRegistered<User> {
+    pub async fn send_msg(&self, id: &UserId, msg: impl Into<String>) -> Result<(), Error> {
+        self.as_mediator().consultation(self, id, msg.into()).await?;
+        Ok(())
+    }
+}
+
+impl AsyncColleague for User {
+    type Identifier = UserId;
+    type Mediator = AsyncUserMediator;
+    type Message = String;
+    
+    fn id(&self) -> &Self::Identifier {
+        &self.id
+    }
+    
+    fn belong_to(self, bus: Self::Mediator) -> AsyncManaged<Self> {
+        AsyncManaged::new(self, bus)
+    }
+    
+    async fn read_msg(&self, msg: Self::Message) {
+        self.received.fetch_add(1, Ordering::Relaxed);
+        println!("[{}] {}", self.id.0, msg);
+    }
+}
+
+#[derive(Default)]
+pub struct AsyncUserMediator {
+    users: Arc<RwLock<HashMap<UserId, AsyncRegistered<User>>>>,
+    deadletter: bool,
+    registered: Arc<Notify>,
+}
+
+impl AsyncUserMediator {
+    /// Creates a mediator that reports undeliverable messages as drifted to deadletter
+    /// instead of failing with [`Error::NotRegistered`].
+    pub fn with_deadletter() -> Self {
+        Self { deadletter: true, ..Default::default() }
+    }
+    
+    /// Waits for `id` to register, e.g. for a sender that may start up before its recipient,
+    /// and hands back its handle. Gives up with [`Error::RegistrationTimeout`] after `timeout`.
+    ///
+    /// Cancel safe, e.g. as a branch of `tokio::select!`: dropping the future before it's done
+    /// takes it off the waiters the next registration wakes, and nothing else is held across an
+    /// `.await` but the read lock on the registry, released along with it.
+    pub async fn await_registration(&self, id: &UserId, timeout: Duration) -> Result<AsyncRegistered<User>, Error> {
+        let registered = async {
+            loop {
+                // Listening before looking, so a registration in between isn't missed.
+                let mut notified = pin!(self.registered.notified());
+                notified.as_mut().enable();
+                if let Some(user) = self.users.read().await.get(id) {
+                    return user.clone();
+                }
+                notified.await;
+            }
+        };
+        tokio::time::timeout(timeout, registered).await
+            .map_err(|_| Error::RegistrationTimeout { id: format!("{:?}", id), timeout })
+    }
+}
+
+impl Clone for AsyncUserMediator {
+    fn clone(&self) -> Self {
+        Self { users: Arc::clone(&self.users), deadletter: self.deadletter, registered: Arc::clone(&self.registered) }
+    }
+}
+
+impl AsyncMediator<User> for AsyncUserMediator {
+    async fn register(&mut self, id: UserId, registered: AsyncManaged<User>) -> Result<AsyncRegistered<User>, Error> {
+        let mut users = self.users.write().await;
+        if users.contains_key(&id) {
+            return Err(Error::AlreadyRegistered { id: format!("{:?}", id) });
+        }
+        let reg = AsyncRegistered::new(registered);
+        users.insert(id, reg.clone());
+        drop(users);
+        self.registered.notify_waiters();
+        Ok(reg)
+    }
+    
+    async fn deregister(&mut self, id: &UserId) -> Result<Option<AsyncRegistered<User>>, Error> {
+        let removed = self.users.write().await
+            .remove(id);
+        Ok(removed)
+    }
+    
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    async fn consultation(&self, from: &User, to: &UserId, msg: String) -> Result<(), Error> {
+        // Cloned out so the handler is free to call back into the mediator, even to register.
+        let user = self.users.read().await
+            .get(to)
+            .cloned();
+        match user {
+            Some(user) => {
+                #[cfg(feature = "tracing")]
+                tracing::info!(from = ?from.id, to = ?to, "delivered");
+                user.read_msg(msg).await;
+            }
+            None if self.deadletter => {
+                #[cfg(feature = "tracing")]
+                tracing::info!(from = ?from.id, to = ?to, "deadlettered");
+            }
+            None => return Err(Error::NotRegistered { id: format!("{:?}", to) }),
+        }
+        
+        Ok(())
+    }
+    
+    async fn broadcast(&self, from: &User, msg: String) -> Result<usize, Error> {
+        let users: Vec<_> = self.users.read().await
+            .iter()
+            .filter(|(id, _)| id.ne(&&from.id))
+            .map(|(_, user)| user.clone())
+            .collect();
+        
+        let mut reached = 0;
+        for user in users {
+            #[cfg(feature = "tracing")]
+            tracing::info!(from = ?from.id, to = ?user.id, "delivered");
+            user.read_msg(msg.clone()).await;
+            reached += 1;
+        }
+        
+        Ok(reached)
+    }
+    
+    async fn broadcast_concurrent(&self, from: &User, msg: String) -> Result<usize, Error> {
+        // Cloned out so handlers can use the mediator while the others are still reading.
+        let recipients: Vec<_> = self.users.read().await
+            .iter()
+            .filter(|(id, _)| id.ne(&&from.id))
+            .map(|(_, user)| user.clone())
+            .collect();
+        
+        #[cfg(feature = "tracing")]
+        for user in &recipients {
+            tracing::info!(from = ?from.id, to = ?user.id, "delivered");
+        }
+        Ok(read_concurrently(&recipients, msg).await)
+    }
+}
+
+/// Awaits every recipient's `read_msg` at once, each with its own clone of `msg`, and counts
+/// the ones that finished without panicking.
+async fn read_concurrently<T: AsyncColleague>(recipients: &[AsyncRegistered<T>], msg: T::Message) -> usize
+    where T::Message: Clone
+{
+    let reads = recipients.iter()
+        .map(|recipient| AssertUnwindSafe(recipient.read_msg(msg.clone())).catch_unwind());
+    join_all(reads).await
+        .into_iter()
+        .filter(Result::is_ok)
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use std::time::Duration;
+    
+    use tokio::sync::RwLock;
+    use tokio::time::Instant;
+    use tokio_util::sync::CancellationToken;
+    
+    use super::{read_concurrently, AsyncColleague, AsyncManaged, AsyncMediator, AsyncRegistered, AsyncUserMediator};
+    use crate::error::Error;
+    use crate::user::UserId;
+    
+    /// Takes its time over every message, for deliveries that are still running.
+    struct Sleeper {
+        id: u8,
+        nap: Duration,
+    }
+    
+    impl AsyncColleague for Sleeper {
+        type Identifier = u8;
+        type Mediator = Bedroom;
+        type Message = ();
+        
+        fn id(&self) -> &Self::Identifier {
+            &self.id
+        }
+        
+        fn belong_to(self, bus: Self::Mediator) -> AsyncManaged<Self> {
+            AsyncManaged::new(self, bus)
+        }
+        
+        async fn read_msg(&self, _: Self::Message) {
+            tokio::time::sleep(self.nap).await;
+        }
+    }
+    
+    #[derive(Clone, Default)]
+    struct Bedroom(Arc<RwLock<HashMap<u8, AsyncRegistered<Sleeper>>>>);
+    
+    impl AsyncMediator<Sleeper> for Bedroom {
+        async fn register(&mut self, id: u8, registered: AsyncManaged<Sleeper>) -> Result<AsyncRegistered<Sleeper>, Error> {
+            let reg = AsyncRegistered::new(registered);
+            self.0.write().await.insert(id, reg.clone());
+            Ok(reg)
+        }
+        
+        async fn deregister(&mut self, id: &u8) -> Result<Option<AsyncRegistered<Sleeper>>, Error> {
+            Ok(self.0.write().await.remove(id))
+        }
+        
+        async fn consultation(&self, _: &Sleeper, to: &u8, msg: ()) -> Result<(), Error> {
+            let sleeper = self.0.read().await
+                .get(to)
+                .cloned()
+                .ok_or_else(|| Error::NotRegistered { id: format!("{:?}", to) })?;
+            sleeper.read_msg(msg).await;
+            Ok(())
+        }
+        
+        async fn broadcast(&self, from: &Sleeper, msg: ()) -> Result<usize, Error> {
+            let ids: Vec<_> = self.0.read().await.keys().copied().filter(|id| *id != from.id).collect();
+            for id in &ids {
+                self.consultation(from, id, msg).await?;
+            }
+            Ok(ids.len())
+        }
+        
+        async fn broadcast_concurrent(&self, from: &Sleeper, msg: ()) -> Result<usize, Error> {
+            let sleepers: Vec<_> = self.0.read().await.values().filter(|sleeper| sleeper.id != from.id).cloned().collect();
+            Ok(read_concurrently(&sleepers, msg).await)
+        }
+    }
+    
+    async fn bedroom(naps: &[Duration]) -> Result<(Bedroom, Vec<AsyncRegistered<Sleeper>>), Error> {
+        let mut bedroom = Bedroom::default();
+        let mut sleepers = Vec::new();
+        for (id, &nap) in (0..).zip(naps) {
+            let managed = Sleeper { id, nap }.belong_to(bedroom.clone());
+            sleepers.push(bedroom.register(id, managed).await?);
+        }
+        Ok((bedroom, sleepers))
+    }
+    
+    #[tokio::test(start_paused = true)]
+    async fn slow_deliveries_time_out() -> Result<(), Error> {
+        let (bedroom, sleepers) = bedroom(&[Duration::ZERO, Duration::from_secs(5)]).await?;
+        
+        let timeout = Duration::from_secs(1);
+        let slow = bedroom.consultation_timeout(&sleepers[0], &1, (), timeout).await;
+        assert_eq!(slow.unwrap_err(), Error::DeliveryTimeout { id: "1".to_string(), timeout });
+        bedroom.consultation_timeout(&sleepers[1], &0, (), timeout).await?;
+        Ok(())
+    }
+    
+    #[tokio::test(start_paused = true)]
+    async fn concurrent_broadcasts_read_all_at_once() -> Result<(), Error> {
+        let nap = Duration::from_secs(1);
+        let (bedroom, sleepers) = bedroom(&[Duration::ZERO, nap, nap, nap]).await?;
+        
+        let start = Instant::now();
+        assert_eq!(bedroom.broadcast_concurrent(&sleepers[0], ()).await?, 3);
+        assert_eq!(start.elapsed(), nap);
+        assert_eq!(bedroom.broadcast(&sleepers[0], ()).await?, 3);
+        assert_eq!(start.elapsed(), nap * 4);
+        Ok(())
+    }
+    
+    #[tokio::test(start_paused = true)]
+    async fn cancelling_drops_the_running_handler() -> Result<(), Error> {
+        let (bedroom, sleepers) = bedroom(&[Duration::ZERO, Duration::from_secs(5)]).await?;
+        let token = CancellationToken::new();
+        let canceller = token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            canceller.cancel();
+        });
+        
+        let start = Instant::now();
+        let cancelled = bedroom.consultation_cancellable(&sleepers[0], &1, (), &token).await;
+        assert_eq!(cancelled.unwrap_err(), Error::Cancelled);
+        assert_eq!(start.elapsed(), Duration::from_secs(1));
+        // Once cancelled, even a handler that wouldn't wait isn't started.
+        let cancelled = bedroom.consultation_cancellable(&sleepers[1], &0, (), &token).await;
+        assert_eq!(cancelled.unwrap_err(), Error::Cancelled);
+        Ok(())
+    }
+    
+    #[tokio::test]
+    async fn cancelled_waits_leave_no_waiter_behind() {
+        let mediator = AsyncUserMediator::default();
+        let id = UserId::new("user-1");
+        let token = CancellationToken::new();
+        token.cancel();
+        
+        tokio::select! {
+            // Polled first, so it's waiting on the registrations by the time it's dropped.
+            biased;
+            _ = mediator.await_registration(&id, Duration::from_secs(60)) => panic!("nobody registered"),
+            _ = token.cancelled() => {}
+        }
+        // With no waiter left to take it, the permit is kept for the next one.
+        mediator.registered.notify_one();
+        assert!(tokio::time::timeout(Duration::ZERO, mediator.registered.notified()).await.is_ok());
+    }
+}
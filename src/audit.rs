@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+
+use crate::message::{DeliveryOutcome, MessageId};
+
+/// What an [`AckStore`] is told about a message.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum AckOutcome {
+    /// How the consultation was resolved; messages sent with
+    /// [`GenericMediator::consult_reliably`](crate::GenericMediator::consult_reliably) are
+    /// resolved as delivered on every delivery.
+    Resolved(DeliveryOutcome),
+    /// The recipient acked a message sent with `consult_reliably`.
+    Acked,
+    /// The recipient nacked a message sent with `consult_reliably`.
+    Nacked,
+}
+
+/// One entry of a message's audit trail, see [`AckStore`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AckRecord<I> {
+    pub message_id: MessageId,
+    pub recipient: I,
+    pub outcome: AckOutcome,
+}
+
+/// Keeps a record of what became of every message, e.g. in a database when deliveries need
+/// to be accounted for, see [`GenericMediatorBuilder::ack_store`](crate::GenericMediatorBuilder::ack_store).
+///
+/// A consultation is recorded once it's resolved here; one forwarded to a parent mediator or
+/// a bridge is for the mediator that resolves it to record. Acks and nacks are taken into account
+/// lazily, so they reach the store the next time the mediator looks at the messages in flight,
+/// e.g. on [`GenericMediator::redeliver`](crate::GenericMediator::redeliver) or
+/// [`GenericMediator::ack_history`](crate::GenericMediator::ack_history).
+pub trait AckStore<I>: Send + Sync {
+    fn record(&self, message_id: MessageId, recipient: &I, outcome: AckOutcome);
+    /// Everything recorded for `message_id`, oldest first.
+    fn history(&self, message_id: MessageId) -> Vec<AckRecord<I>>;
+}
+
+/// [`AckStore`] keeping every record in memory for as long as the mediator lives.
+pub struct MemoryAckStore<I> {
+    records: Mutex<HashMap<MessageId, Vec<AckRecord<I>>>>,
+}
+
+impl<I> MemoryAckStore<I> {
+    fn records(&self) -> MutexGuard<'_, HashMap<MessageId, Vec<AckRecord<I>>>> {
+        self.records.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl<I> Default for MemoryAckStore<I> {
+    fn default() -> Self {
+        Self { records: Mutex::new(HashMap::new()) }
+    }
+}
+
+impl<I: Clone + Send> AckStore<I> for MemoryAckStore<I> {
+    fn record(&self, message_id: MessageId, recipient: &I, outcome: AckOutcome) {
+        self.records()
+            .entry(message_id)
+            .or_default()
+            .push(AckRecord { message_id, recipient: recipient.clone(), outcome });
+    }
+    
+    fn history(&self, message_id: MessageId) -> Vec<AckRecord<I>> {
+        self.records()
+            .get(&message_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,348 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fmt::{Debug, Display, Formatter};
+use std::sync::atomic::AtomicU64;
+use std::sync::Arc;
+
+use crate::colleague::{Colleague, Managed, Registered};
+use crate::deadletter::{DeadLetter, DeadletterReason};
+use crate::error::{ColleagueError, Error, Operation};
+use crate::message::{DeliveryOutcome, MessageId};
+use crate::lock::{self, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use crate::mediator::{sealed, Mediator};
+use crate::observer::Observer;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BotId(u32);
+
+impl BotId {
+    pub fn new(id: u32) -> BotId {
+        Self(id)
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BotCommand {
+    Ping,
+    Say(String),
+    Shutdown { reason: String },
+}
+
+impl Display for BotCommand {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BotCommand::Ping => write!(f, "ping"),
+            BotCommand::Say(text) => write!(f, "say {}", text),
+            BotCommand::Shutdown { reason } => write!(f, "shutdown ({})", reason),
+        }
+    }
+}
+
+pub struct Bot {
+    id: BotId,
+}
+
+impl Bot {
+    pub fn new(id: BotId) -> Bot {
+        Self { id }
+    }
+}
+
+impl Debug for Bot {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Bot id:{}", self.id.0)
+    }
+}
+
+impl Registered<Bot> {
+    pub fn command(&self, id: &BotId, cmd: impl Into<BotCommand>) -> Result<DeliveryOutcome, Error> {
+        self.as_mediator().consultation(self, id, cmd.into())
+    }
+}
+
+impl Colleague for Bot {
+    type Identifier = BotId;
+    type Mediator = BotMediator;
+    type Message = BotCommand;
+    type Request = ();
+    type Response = BotId;
+    
+    fn id(&self) -> &Self::Identifier {
+        &self.id
+    }
+    
+    fn belong_to(self, bus: Self::Mediator) -> Managed<Self> {
+        Managed::new(self, bus)
+    }
+    
+    fn read_msg(&self, msg: Self::Message) -> Result<(), ColleagueError> {
+        match msg {
+            BotCommand::Ping => println!("[bot-{}] pong", self.id.0),
+            BotCommand::Say(text) => println!("[bot-{}] {}", self.id.0, text),
+            BotCommand::Shutdown { reason } => println!("[bot-{}] shutting down: {}", self.id.0, reason),
+        }
+        Ok(())
+    }
+    
+    fn handle_request(&self, _: Self::Request) -> Self::Response {
+        self.id
+    }
+}
+
+#[derive(Default)]
+pub struct BotMediator {
+    bots: Arc<RwLock<HashMap<BotId, Registered<Bot>>>>,
+    deadletters: Arc<RwLock<Vec<DeadLetter<BotId, BotCommand>>>>,
+    sequence: Arc<AtomicU64>,
+    deadletter: bool,
+    allow_self_send: bool,
+    observer: Option<Arc<dyn Observer<Bot>>>,
+}
+
+impl BotMediator {
+    /// Creates a mediator that keeps undeliverable commands in its deadletter queue
+    /// instead of failing with [`Error::NotRegistered`].
+    pub fn with_deadletter() -> Self {
+        Self { deadletter: true, ..Default::default() }
+    }
+    
+    /// Lets colleagues consult themselves; by default that is rejected with [`Error::SelfSend`].
+    pub fn allowing_self_send(self) -> Self {
+        Self { allow_self_send: true, ..self }
+    }
+    
+    pub fn observed_by(self, observer: impl Observer<Bot> + 'static) -> Self {
+        Self { observer: Some(Arc::new(observer)), ..self }
+    }
+    
+    pub fn deadletters(&self) -> Result<Vec<DeadLetter<BotId, BotCommand>>, Error> {
+        let letters = lock::read(&self.deadletters, Operation::Deadletters)?
+            .clone();
+        Ok(letters)
+    }
+    
+    pub fn drain_deadletters(&self) -> Result<Vec<DeadLetter<BotId, BotCommand>>, Error> {
+        let drained = lock::write(&self.deadletters, Operation::Deadletters)?
+            .drain(..)
+            .collect();
+        Ok(drained)
+    }
+    
+    fn hand_over(&self, from: &BotId, to: &BotId, recipient: &Registered<Bot>, msg: BotCommand) -> Result<bool, ColleagueError> {
+        recipient.deliver(from, msg, |msg| {
+            if let Some(observer) = &self.observer {
+                observer.on_delivered(from, to, msg);
+            }
+        })
+    }
+    
+    fn joined(&self, id: &BotId) {
+        if let Some(observer) = &self.observer {
+            observer.on_register(id);
+        }
+    }
+    
+    fn left(&self, id: &BotId) {
+        if let Some(observer) = &self.observer {
+            observer.on_deregister(id);
+        }
+    }
+    
+    fn read_bots(&self) -> RwLockReadGuard<'_, HashMap<BotId, Registered<Bot>>> {
+        lock::recover_read(&self.bots)
+    }
+    
+    fn write_bots(&self) -> RwLockWriteGuard<'_, HashMap<BotId, Registered<Bot>>> {
+        lock::recover_write(&self.bots)
+    }
+}
+
+impl Clone for BotMediator {
+    fn clone(&self) -> Self {
+        Self {
+            bots: Arc::clone(&self.bots),
+            deadletters: Arc::clone(&self.deadletters),
+            sequence: Arc::clone(&self.sequence),
+            deadletter: self.deadletter,
+            allow_self_send: self.allow_self_send,
+            observer: self.observer.clone(),
+        }
+    }
+}
+
+impl sealed::Sealed for BotMediator {}
+
+impl sealed::ConsultAs<Bot> for BotMediator {
+    fn consultation_as(&self, _: sealed::Token, from: &BotId, to: &BotId, msg: BotCommand) -> Result<DeliveryOutcome, Error> {
+        if !self.allow_self_send && from.eq(to) {
+            return Err(Error::SelfSend { id: format!("{:?}", to) });
+        }
+        
+        let id = MessageId::next(&self.sequence);
+        
+        // Cloned out so the handler is free to call back into the mediator.
+        let bot = self.read_bots()
+            .get(to)
+            .cloned();
+        let outcome = match bot {
+            Some(bot) => match self.hand_over(from, to, &bot, msg) {
+                Ok(true) => DeliveryOutcome::Delivered(id),
+                Ok(false) => return Err(Error::MailboxFull { id: format!("{:?}", to) }),
+                Err(e) => DeliveryOutcome::Rejected { id, colleague: format!("{:?}", to), reason: e.reason },
+            },
+            None if self.deadletter => {
+                if let Some(observer) = &self.observer {
+                    observer.on_deadletter(from, to, &msg);
+                }
+                lock::write(&self.deadletters, Operation::Consultation)?
+                    .push(DeadLetter { id, from: *from, to: *to, msg, reason: DeadletterReason::NotRegistered });
+                DeliveryOutcome::Deadlettered(id)
+            }
+            None => return Err(Error::NotRegistered { id: format!("{:?}", to) }),
+        };
+        
+        Ok(outcome)
+    }
+}
+
+impl Mediator<Bot> for BotMediator {
+    fn register(&self, id: BotId, registered: Managed<Bot>) -> Result<Registered<Bot>, Error> {
+        let reg = match self.write_bots().entry(id) {
+            Entry::Occupied(occupied) => return Err(Error::AlreadyRegistered { id: format!("{:?}", occupied.key()) }),
+            Entry::Vacant(vacant) => vacant.insert(Registered::new(registered)).clone(),
+        };
+        self.joined(&id);
+        Ok(reg)
+    }
+    
+    fn register_if_absent(&self, id: BotId, registered: Managed<Bot>) -> Result<Registered<Bot>, Error> {
+        let reg = match self.write_bots().entry(id) {
+            Entry::Occupied(occupied) => return Ok(occupied.get().clone()),
+            Entry::Vacant(vacant) => vacant.insert(Registered::new(registered)).clone(),
+        };
+        self.joined(&id);
+        Ok(reg)
+    }
+    
+    fn re_register(&self, id: BotId, registered: Managed<Bot>) -> Result<(Registered<Bot>, Option<Registered<Bot>>), Error> {
+        let reg = Registered::new(registered);
+        let previous = self.write_bots()
+            .insert(id, reg.clone());
+        self.joined(&id);
+        Ok((reg, previous))
+    }
+    
+    fn deregister(&self, id: &BotId) -> Result<Option<Registered<Bot>>, Error> {
+        let removed = self.write_bots()
+            .remove(id);
+        if removed.is_some() {
+            self.left(id);
+        }
+        Ok(removed)
+    }
+    
+    fn rename(&self, old: &BotId, new: BotId) -> Result<(), Error> {
+        let mut bots = self.write_bots();
+        if bots.contains_key(&new) {
+            return Err(Error::AlreadyRegistered { id: format!("{:?}", new) });
+        }
+        let bot = bots.remove(old)
+            .ok_or_else(|| Error::NotRegistered { id: format!("{:?}", old) })?;
+        bots.insert(new, bot);
+        drop(bots);
+        
+        if let Some(observer) = &self.observer {
+            observer.on_rename(old, &new);
+        }
+        Ok(())
+    }
+    
+    fn clear(&self) -> Result<usize, Error> {
+        let removed: Vec<_> = self.write_bots()
+            .drain()
+            .map(|(id, _)| id)
+            .collect();
+        removed.iter().for_each(|id| self.left(id));
+        Ok(removed.len())
+    }
+    
+    fn is_registered(&self, id: &BotId) -> Result<bool, Error> {
+        let found = self.read_bots()
+            .contains_key(id);
+        Ok(found)
+    }
+    
+    fn get(&self, id: &BotId) -> Result<Option<Registered<Bot>>, Error> {
+        let found = self.read_bots()
+            .get(id)
+            .cloned();
+        Ok(found)
+    }
+    
+    fn registered_count(&self) -> Result<usize, Error> {
+        let count = self.read_bots()
+            .len();
+        Ok(count)
+    }
+    
+    fn registered_ids(&self) -> Result<Vec<BotId>, Error> {
+        let ids = self.read_bots()
+            .keys()
+            .cloned()
+            .collect();
+        Ok(ids)
+    }
+    
+    fn entries(&self) -> Result<Vec<(BotId, Registered<Bot>)>, Error> {
+        let entries = self.read_bots()
+            .iter()
+            .map(|(id, bot)| (*id, bot.clone()))
+            .collect();
+        Ok(entries)
+    }
+    
+    fn try_consultation(&self, from: &Registered<Bot>, to: &BotId, msg: BotCommand) -> Result<bool, Error> {
+        if !self.allow_self_send && from.id.eq(to) {
+            return Ok(false);
+        }
+        
+        let recipient = self.read_bots()
+            .get(to)
+            .cloned();
+        match recipient {
+            Some(recipient) => Ok(matches!(recipient.deliver(&from.id, msg, |_| {}), Ok(true))),
+            None => Ok(false),
+        }
+    }
+    
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    fn request(&self, from: &Registered<Bot>, to: &BotId, req: ()) -> Result<BotId, Error> {
+        let bot = self.read_bots()
+            .get(to)
+            .cloned()
+            .ok_or_else(|| Error::NotRegistered { id: format!("{:?}", to) })?;
+        
+        #[cfg(feature = "tracing")]
+        tracing::debug!(from = ?from.id, to = ?to, request = ?req, "request");
+        Ok(bot.handle_request(req))
+    }
+    
+    fn broadcast(&self, from: &Registered<Bot>, msg: BotCommand) -> Result<usize, Error> {
+        let bots: Vec<_> = self.read_bots()
+            .iter()
+            .filter(|(id, _)| id.ne(&from.id()))
+            .map(|(id, bot)| (*id, bot.clone()))
+            .collect();
+        
+        let mut reached = 0;
+        for (id, bot) in &bots {
+            if let Ok(true) = self.hand_over(&from.id, id, bot, msg.clone()) {
+                reached += 1;
+            }
+        }
+        
+        Ok(reached)
+    }
+}
@@ -0,0 +1,69 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// Per-recipient circuit breakers: after `threshold` rejections in a row a recipient's circuit
+/// opens and consultations to it fail fast, until `reset_after` has passed and a trial
+/// consultation is let through. The trial succeeding closes the circuit, failing opens it again.
+pub(crate) struct Breakers<I> {
+    threshold: u32,
+    reset_after: Duration,
+    circuits: Mutex<HashMap<I, Circuit>>,
+}
+
+enum Circuit {
+    Closed { failures: u32 },
+    Open { since: Instant },
+    HalfOpen,
+}
+
+impl<I> Breakers<I> {
+    pub(crate) fn new(threshold: u32, reset_after: Duration) -> Self {
+        Self { threshold, reset_after, circuits: Mutex::new(HashMap::new()) }
+    }
+    
+    fn lock(&self) -> MutexGuard<'_, HashMap<I, Circuit>> {
+        self.circuits.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl<I: Eq + Hash + Clone> Breakers<I> {
+    /// Whether a consultation to `id` may go ahead at `now`.
+    pub(crate) fn admit(&self, id: &I, now: Instant) -> bool {
+        let mut circuits = self.lock();
+        let Some(circuit) = circuits.get_mut(id) else {
+            return true;
+        };
+        match circuit {
+            Circuit::Open { since } if now.duration_since(*since) < self.reset_after => false,
+            Circuit::Open { .. } => {
+                *circuit = Circuit::HalfOpen;
+                true
+            }
+            Circuit::Closed { .. } | Circuit::HalfOpen => true,
+        }
+    }
+    
+    /// Carries the circuit of `old` over to `new`, e.g. once a colleague is renamed.
+    pub(crate) fn rename(&self, old: &I, new: &I) {
+        let mut circuits = self.lock();
+        if let Some(circuit) = circuits.remove(old) {
+            circuits.insert(new.clone(), circuit);
+        }
+    }
+    
+    /// Records whether `id` read what it was consulted with.
+    pub(crate) fn record(&self, id: &I, read: bool, now: Instant) {
+        let mut circuits = self.lock();
+        if read {
+            circuits.remove(id);
+            return;
+        }
+        let circuit = circuits.entry(id.clone()).or_insert(Circuit::Closed { failures: 0 });
+        *circuit = match circuit {
+            Circuit::Closed { failures } if *failures + 1 < self.threshold => Circuit::Closed { failures: *failures + 1 },
+            _ => Circuit::Open { since: now },
+        };
+    }
+}
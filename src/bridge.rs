@@ -0,0 +1,91 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::sync::Arc;
+
+use crate::colleague::Colleague;
+use crate::error::{Error, Operation};
+use crate::generic::{GenericMediator, Link};
+use crate::lock::{self, RwLock};
+use crate::mediator::Mediator;
+
+/// Links two mediators, e.g. one per region, so that consulting an id registered on the other
+/// side gets the message there.
+///
+/// A side only turns to the bridge once its own lookup found nobody, before its
+/// [parent](crate::GenericMediatorBuilder::parent) or deadletter queue, and only for ids the
+/// routing table puts on the other side. Each crossing counts towards the
+/// [forward depth](crate::GenericMediatorBuilder::forward_depth), so a message can't bounce
+/// back and forth for good. Dropping the bridge disconnects the mediators again.
+pub struct Bridge<C: Colleague> {
+    left: GenericMediator<C>,
+    right: GenericMediator<C>,
+    routes: Arc<RwLock<Routes<C>>>,
+}
+
+type Routes<C> = HashMap<<C as Colleague>::Identifier, Side>;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Side {
+    Left,
+    Right,
+}
+
+impl<C> Bridge<C>
+    where C: Colleague + 'static,
+          C::Identifier: Eq + Hash + Clone + Debug + Send + Sync,
+          GenericMediator<C>: Mediator<C> + Send + Sync
+{
+    /// Links `left` and `right`, replacing any bridge either of them had, and builds the routing
+    /// table from whoever is registered on both sides right now.
+    pub fn connect(left: &GenericMediator<C>, right: &GenericMediator<C>) -> Result<Self, Error> {
+        let routes = Arc::default();
+        left.link(Some(link(&routes, Side::Right, right)));
+        right.link(Some(link(&routes, Side::Left, left)));
+        let bridge = Self { left: left.clone(), right: right.clone(), routes };
+        bridge.refresh()?;
+        Ok(bridge)
+    }
+    
+    /// Rebuilds the routing table, which isn't kept up to date as colleagues come and go on
+    /// either side. An id registered on both sides is never forwarded.
+    pub fn refresh(&self) -> Result<(), Error> {
+        let mut routes = HashMap::new();
+        for id in self.left.registered_ids()? {
+            routes.insert(id, Side::Left);
+        }
+        for id in self.right.registered_ids()? {
+            routes.entry(id).or_insert(Side::Right);
+        }
+        *lock::write(&self.routes, Operation::Bridge)? = routes;
+        Ok(())
+    }
+    
+    /// Whether consulting `id` from the other side would be forwarded across the bridge.
+    pub fn routes(&self, id: &C::Identifier) -> Result<bool, Error> {
+        let routed = lock::read(&self.routes, Operation::Bridge)?
+            .contains_key(id);
+        Ok(routed)
+    }
+}
+
+impl<C: Colleague> Drop for Bridge<C> {
+    fn drop(&mut self) {
+        self.left.link(None);
+        self.right.link(None);
+    }
+}
+
+/// Hands out `remote` for ids living on `side`.
+fn link<C>(routes: &Arc<RwLock<Routes<C>>>, side: Side, remote: &GenericMediator<C>) -> Link<C>
+    where C: Colleague + 'static,
+          C::Identifier: Eq + Hash + Send + Sync,
+          GenericMediator<C>: Mediator<C> + Send + Sync
+{
+    let routes = Arc::clone(routes);
+    let remote: Arc<dyn Mediator<C> + Send + Sync> = Arc::new(remote.clone());
+    Arc::new(move |id| {
+        let routed = lock::recover_read(&routes).get(id) == Some(&side);
+        routed.then(|| Arc::clone(&remote))
+    })
+}
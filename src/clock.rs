@@ -0,0 +1,97 @@
+use std::fmt::{Debug, Formatter};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Source of time for everything a mediator times, such as quota windows and retry backoff.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Instant;
+    
+    fn sleep(&self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
+}
+
+/// The real clock, used unless configured otherwise.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A clock that only moves when told to, for driving timing behaviour deterministically.
+/// Clones share the same time, so keep one to [`advance`](MockClock::advance) after handing
+/// another to a mediator. Sleeping advances the clock instead of blocking.
+#[derive(Debug, Clone)]
+pub struct MockClock {
+    start: Instant,
+    elapsed: Arc<Mutex<Duration>>,
+}
+
+impl MockClock {
+    pub fn new() -> MockClock {
+        Self { start: Instant::now(), elapsed: Arc::default() }
+    }
+    
+    pub fn advance(&self, by: Duration) {
+        *self.lock() += by;
+    }
+    
+    fn lock(&self) -> std::sync::MutexGuard<'_, Duration> {
+        self.elapsed.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        self.start + *self.lock()
+    }
+    
+    fn sleep(&self, duration: Duration) {
+        self.advance(duration);
+    }
+}
+
+/// When the sender of a message stops caring for an answer, as told by its mediator's clock,
+/// see [`GenericMediator::consult_with_deadline`](crate::GenericMediator::consult_with_deadline).
+#[derive(Clone)]
+pub struct Deadline {
+    at: Instant,
+    clock: Arc<dyn Clock>,
+}
+
+impl Deadline {
+    pub(crate) fn new(at: Instant, clock: Arc<dyn Clock>) -> Self {
+        Self { at, clock }
+    }
+    
+    /// The instant itself, e.g. to pass the deadline on to further consultations.
+    pub fn at(&self) -> Instant {
+        self.at
+    }
+    
+    /// Time left until the deadline, zero once it has passed.
+    pub fn remaining(&self) -> Duration {
+        self.at.saturating_duration_since(self.clock.now())
+    }
+    
+    pub fn is_expired(&self) -> bool {
+        self.clock.now() >= self.at
+    }
+}
+
+impl Debug for Deadline {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Deadline")
+            .field("at", &self.at)
+            .finish_non_exhaustive()
+    }
+}
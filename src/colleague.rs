@@ -0,0 +1,462 @@
+use std::fmt::{Debug, Formatter};
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::sync::{Arc, Weak};
+use std::time::Instant;
+
+use crate::ack::Acker;
+use crate::clock::{Clock, Deadline};
+use crate::error::{ColleagueError, Error};
+use crate::inbox::Outlet;
+use crate::mailbox::Mailbox;
+use crate::mediator::Mediator;
+use crate::message::{DeliveryOutcome, Message, MessageId, Priority, Readiness};
+use crate::reliable::AckHandle;
+use crate::reply::ReplyContext;
+use crate::scoped::Scoped;
+use crate::sender::Sender;
+use crate::turnstile::Turnstile;
+
+pub trait Colleague: Sized {
+    type Identifier;
+    type Mediator: Mediator<Self>;
+    type Message;
+    type Request;
+    type Response;
+    fn id(&self) -> &Self::Identifier;
+    fn belong_to(self, mediator: Self::Mediator) -> Managed<Self>;
+    fn read_msg(&self, msg: Self::Message) -> Result<(), ColleagueError>;
+    /// Reads a message lent out to several recipients at once, see
+    /// [`GenericMediator::broadcast_ref`](crate::GenericMediator::broadcast_ref). Colleagues that
+    /// don't need to own their messages override this to spare the copy `read_msg` takes.
+    fn read_msg_ref(&self, msg: &Self::Message) -> Result<(), ColleagueError>
+        where Self::Message: Clone
+    {
+        self.read_msg(msg.clone())
+    }
+    /// Reads a message sent with headers. Colleagues that care about headers override this;
+    /// by default the headers are dropped and the body goes to `read_msg`.
+    fn read_with_headers(&self, msg: Message<Self::Message>) -> Result<(), ColleagueError> {
+        self.read_msg(msg.body)
+    }
+    /// Reads a message knowing who sent it, e.g. to [reply](ReplyContext::reply) right away.
+    /// This is what every delivery goes through; by default it hands over to `read_with_headers`.
+    fn read_with_context(&self, msg: Message<Self::Message>, _ctx: ReplyContext<'_, Self>) -> Result<(), ColleagueError> {
+        self.read_with_headers(msg)
+    }
+    /// Reads a message sent with [`GenericMediator::consult_reliably`](crate::GenericMediator::consult_reliably),
+    /// which is delivered again until `ack` is acked. By default the message goes to `read_msg`
+    /// and is acked if that accepts it, nacked otherwise.
+    fn read_reliably(&self, msg: Self::Message, ack: AckHandle) -> Result<(), ColleagueError> {
+        let read = self.read_msg(msg);
+        match read {
+            Ok(()) => ack.ack(),
+            Err(_) => ack.nack(),
+        }
+        read
+    }
+    /// Reads the next piece of a payload sent with
+    /// [`GenericMediator::consult_stream`](crate::GenericMediator::consult_stream), in order;
+    /// [`Colleague::read_end`] follows the last one. Colleagues don't take payloads in chunks
+    /// unless they override this.
+    fn read_chunk(&self, _chunk: Vec<u8>) -> Result<(), ColleagueError> {
+        Err(ColleagueError::new("payloads in chunks aren't read here"))
+    }
+    /// Called once every chunk of a payload has been read.
+    fn read_end(&self) -> Result<(), ColleagueError> {
+        Ok(())
+    }
+    /// Whether the colleague is still up to its work, asked by [`Mediator::ping`]. Colleagues
+    /// are healthy unless they override this.
+    fn on_ping(&self) -> bool {
+        true
+    }
+    /// Whether the colleague would take `msg`, asked before
+    /// [`GenericMediator::consult_2pc`](crate::GenericMediator::consult_2pc) delivers anything.
+    /// Colleagues agree to everything unless they override this.
+    fn can_accept(&self, _msg: &Self::Message) -> bool {
+        true
+    }
+    /// Called as [`GenericMediator::shutdown`](crate::GenericMediator::shutdown) reaches the
+    /// colleague, right before it's deregistered.
+    fn on_shutdown(&self) {}
+    /// The schema version of the messages the colleague is written against, 0 unless overridden.
+    fn schema_version(&self) -> u16 {
+        0
+    }
+    /// Whether a message of schema version `version` may be delivered; consultations of other
+    /// versions fail with [`Error::VersionMismatch`]. By default only the colleague's own
+    /// [`Colleague::schema_version`] is accepted.
+    fn accepts_version(&self, version: u16) -> bool {
+        version == self.schema_version()
+    }
+    fn handle_request(&self, req: Self::Request) -> Self::Response;
+}
+
+/// A queued message together with its sender and whoever waits for it to be read.
+struct Envelope<T: Colleague> {
+    from: T::Identifier,
+    msg: Message<T::Message>,
+    acker: Option<Acker>,
+    expiry: Option<Expiry<T>>,
+    deadline: Option<Deadline>,
+}
+
+/// How a message is handed over, beyond the message itself.
+pub(crate) struct Delivery<T: Colleague> {
+    pub(crate) priority: Priority,
+    /// Told how `read_msg` went once the message is read.
+    pub(crate) acker: Option<Acker>,
+    /// Only mailboxes look at this; every other colleague reads its messages right away.
+    pub(crate) expiry: Option<Expiry<T>>,
+    pub(crate) deadline: Option<Deadline>,
+}
+
+impl<T: Colleague> Default for Delivery<T> {
+    fn default() -> Self {
+        Self { priority: Priority::Normal, acker: None, expiry: None, deadline: None }
+    }
+}
+
+pub(crate) type OnExpire<T> = Box<dyn FnOnce(MessageId, <T as Colleague>::Identifier, <T as Colleague>::Message) + Send>;
+
+/// When a queued message goes stale, and what becomes of it if it is still queued by then.
+pub(crate) struct Expiry<T: Colleague> {
+    pub(crate) id: MessageId,
+    pub(crate) at: Instant,
+    pub(crate) clock: Arc<dyn Clock>,
+    pub(crate) on_expire: OnExpire<T>,
+}
+
+/// A colleague bound to its mediator but not registered yet. It can't send anything: every
+/// send path asks for the [`Registered`] handle that registering it hands back.
+pub struct Managed<T: Colleague> {
+    inner: T,
+    mediator: T::Mediator,
+    mailbox: Option<Mailbox<Envelope<T>>>,
+    turnstile: Option<Turnstile>,
+    outlet: Option<Outlet<T::Message>>,
+}
+
+impl<T: Colleague> Managed<T> {
+    pub fn new(t: T, bus: T::Mediator) -> Self {
+        Self { inner: t, mediator: bus, mailbox: None, turnstile: None, outlet: None }
+    }
+    
+    /// Gives the colleague a bounded mailbox: once registered, consultations queue up to
+    /// `capacity` messages for it to [`Registered::drain`] instead of calling `read_msg` directly.
+    /// Draining goes by [`Priority`], then by arrival.
+    pub fn with_mailbox(self, capacity: usize) -> Self {
+        Self { mailbox: Some(Mailbox::new(capacity)), ..self }
+    }
+    
+    /// Serializes the colleague's `read_msg`: concurrent consultations wait for their turn and
+    /// are read one at a time in the order they reached the colleague, so messages from a single
+    /// sender are always read in send order.
+    ///
+    /// Consulting an ordered colleague from within its own `read_msg`, directly or through
+    /// another ordered colleague, waits for a turn that never comes.
+    pub fn ordered(self) -> Self {
+        Self { turnstile: Some(Turnstile::new()), ..self }
+    }
+    
+    /// Wraps the colleague in a [`Registered`] handle without registering it, e.g. to use it on
+    /// its own in a test. Consultations from other colleagues won't find it, but it can still
+    /// consult the mediator it belongs to.
+    pub fn into_registered(self) -> Registered<T> {
+        Registered::new(self)
+    }
+    
+    pub(crate) fn mediator(&self) -> T::Mediator
+        where T::Mediator: Clone
+    {
+        self.mediator.clone()
+    }
+    
+    /// Routes deliveries into `outlet` instead of `read_msg`, taking precedence over a mailbox.
+    /// Only message bodies go into the outlet.
+    pub(crate) fn with_outlet(self, outlet: Outlet<T::Message>) -> Self {
+        Self { outlet: Some(outlet), ..self }
+    }
+}
+
+impl<T: Colleague + Debug> Debug for Managed<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Managed").field(&self.inner).finish()
+    }
+}
+
+impl<T: Colleague> Deref for Managed<T> {
+    type Target = T;
+    
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+pub struct Registered<T: Colleague>(Arc<Managed<T>>);
+
+impl<T: Colleague> Registered<T> {
+    pub(crate) fn new(managed: Managed<T>) -> Self {
+        Self(Arc::new(managed))
+    }
+    
+    pub fn as_mediator(&self) -> &T::Mediator {
+        &self.0.mediator
+    }
+    
+    /// Owned clone of the mediator, e.g. for registering further colleagues from a helper.
+    pub fn mediator(&self) -> T::Mediator
+        where T::Mediator: Clone
+    {
+        self.0.mediator()
+    }
+    
+    /// Detaches a [`Sender`] that keeps sending as this colleague even after every
+    /// `Registered` handle is gone.
+    pub fn sender(&self) -> Sender<T>
+        where T::Identifier: Clone,
+              T::Mediator: Clone
+    {
+        Sender::new(self.id().clone(), self.as_mediator().clone())
+    }
+    
+    /// Consults `to` and hands the outcome to `then` right after, e.g. to chain on whether
+    /// the message got delivered. Failures that stop the consultation altogether are returned
+    /// instead and `then` isn't called.
+    pub fn send_msg_with<F>(&self, to: &T::Identifier, msg: impl Into<T::Message>, then: F) -> Result<(), Error>
+        where F: FnOnce(DeliveryOutcome)
+    {
+        let outcome = self.as_mediator().consultation(self, to, msg.into())?;
+        then(outcome);
+        Ok(())
+    }
+    
+    /// Ties the registration to the returned handle: once it and all its clones are dropped,
+    /// the colleague is deregistered. See [`Scoped`].
+    pub fn scoped(self) -> Scoped<T>
+        where T::Mediator: Clone
+    {
+        let mediator = self.mediator();
+        Scoped::new(self, mediator)
+    }
+    
+    /// Processes up to `max` queued messages with `read_msg`, returning how many were handled.
+    /// Colleagues without a mailbox never have anything to drain. The sender is long gone by
+    /// now, so messages `read_msg` rejects are dropped but still count as handled. Messages
+    /// past their time to live are deadlettered instead of read and don't count.
+    pub fn drain(&self, max: usize) -> usize {
+        let Some(mailbox) = &self.0.mailbox else {
+            return 0;
+        };
+        
+        let mut handled = 0;
+        while handled < max {
+            let _turn = self.0.turnstile.as_ref().map(Turnstile::enter);
+            let Some(Envelope { from, msg, acker, expiry, deadline }) = mailbox.pop() else {
+                break;
+            };
+            if let Some(expiry) = expiry.filter(|expiry| expiry.clock.now() >= expiry.at) {
+                (expiry.on_expire)(expiry.id, from, msg.body);
+                continue;
+            }
+            let read = self.read_with_context(msg, ReplyContext::new(&from, self.id(), self.as_mediator(), deadline.as_ref()));
+            if let Some(acker) = acker {
+                acker.ack(&read);
+            }
+            handled += 1;
+        }
+        handled
+    }
+    
+    /// Empties the mailbox without reading anything, handing each message and its sender
+    /// to `on_drop` instead. Returns how many messages were discarded.
+    pub(crate) fn discard(&self, mut on_drop: impl FnMut(T::Identifier, T::Message)) -> usize {
+        let Some(mailbox) = &self.0.mailbox else {
+            return 0;
+        };
+        
+        let mut discarded = 0;
+        while let Some(Envelope { from, msg, .. }) = mailbox.pop() {
+            on_drop(from, msg.body);
+            discarded += 1;
+        }
+        discarded
+    }
+    
+    /// Whether both handles are of the very same registration, as opposed to colleagues that
+    /// merely share an id.
+    pub(crate) fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+    
+    /// Takes the colleague back out of its registration, e.g. to persist its final state, if
+    /// this is the last handle; otherwise hands the handle back unchanged. A registered
+    /// colleague is held by its mediator too, so deregister it first. Whatever is still in
+    /// its mailbox is dropped unread.
+    pub fn try_into_inner(self) -> Result<T, Registered<T>> {
+        Arc::try_unwrap(self.0)
+            .map(|managed| managed.inner)
+            .map_err(Registered)
+    }
+    
+    /// A handle that doesn't keep the colleague alive, e.g. for colleagues holding on to each
+    /// other without forming a cycle.
+    pub fn downgrade(&self) -> WeakRegistered<T> {
+        WeakRegistered(Arc::downgrade(&self.0))
+    }
+    
+    pub(crate) fn readiness(&self) -> Readiness {
+        match (&self.0.outlet, &self.0.mailbox) {
+            (Some(outlet), _) if outlet.is_closed() => Readiness::Absent,
+            (Some(outlet), _) if outlet.is_full() => Readiness::Full,
+            (None, Some(mailbox)) if mailbox.is_full() => Readiness::Full,
+            _ => Readiness::Ready,
+        }
+    }
+    
+    /// How many messages wait in the colleague's mailbox, `None` if it reads them right away.
+    pub fn mailbox_depth(&self) -> Option<usize> {
+        self.0.mailbox.as_ref().map(Mailbox::len)
+    }
+    
+    pub(crate) fn has_mailbox(&self) -> bool {
+        self.0.mailbox.is_some()
+    }
+    
+    /// Hands `msg` over to the colleague, queueing it if the colleague has a mailbox.
+    /// Returns `Ok(false)` if the mailbox is full, in which case `on_accept` is not called,
+    /// and the colleague's error if it read the message right away and rejected it.
+    pub(crate) fn deliver(&self, from: &T::Identifier, msg: T::Message, on_accept: impl FnOnce(&T::Message)) -> Result<bool, ColleagueError>
+        where T::Identifier: Clone
+    {
+        self.deliver_with(from, Message::new(msg), Delivery::default(), on_accept)
+    }
+    
+    /// Like [`Registered::deliver`] but reads `msg` through `read_msg_ref`; only a mailbox, a
+    /// channel or an inbox, which need a message of their own, get a clone.
+    pub(crate) fn deliver_ref(&self, from: &T::Identifier, msg: &T::Message, on_accept: impl FnOnce(&T::Message)) -> Result<bool, ColleagueError>
+        where T::Identifier: Clone,
+              T::Message: Clone
+    {
+        if self.0.outlet.is_some() || self.0.mailbox.is_some() {
+            return self.deliver(from, msg.clone(), on_accept);
+        }
+        
+        let _turn = self.0.turnstile.as_ref().map(Turnstile::enter);
+        on_accept(msg);
+        self.read_msg_ref(msg)?;
+        Ok(true)
+    }
+    
+    /// Hands `msg` to [`Colleague::read_reliably`] right away, even if the colleague has a
+    /// mailbox or an outlet, as only the colleague itself can settle `ack`.
+    pub(crate) fn deliver_reliably(&self, msg: T::Message, ack: AckHandle) -> Result<(), ColleagueError> {
+        let _turn = self.0.turnstile.as_ref().map(Turnstile::enter);
+        self.read_reliably(msg, ack)
+    }
+    
+    /// Hands every chunk to [`Colleague::read_chunk`] right away, then calls
+    /// [`Colleague::read_end`], stopping at the first chunk turned down.
+    pub(crate) fn deliver_chunks(&self, chunks: impl Iterator<Item = Vec<u8>>) -> Result<(), ColleagueError> {
+        let _turn = self.0.turnstile.as_ref().map(Turnstile::enter);
+        for chunk in chunks {
+            self.read_chunk(chunk)?;
+        }
+        self.read_end()
+    }
+    
+    pub(crate) fn deliver_with(&self, from: &T::Identifier, msg: Message<T::Message>, delivery: Delivery<T>, on_accept: impl FnOnce(&T::Message)) -> Result<bool, ColleagueError>
+        where T::Identifier: Clone
+    {
+        let Delivery { priority, acker, expiry, deadline } = delivery;
+        if let Some(outlet) = &self.0.outlet {
+            let pushed = outlet.push(msg.body, on_accept);
+            match (acker, &pushed) {
+                (Some(acker), Ok(true)) => acker.ack(&Ok(())),
+                (Some(acker), Err(e)) => acker.ack(&Err(e.clone())),
+                _ => {}
+            }
+            return pushed;
+        }
+        
+        match &self.0.mailbox {
+            Some(mailbox) => {
+                let envelope = Envelope { from: from.clone(), msg, acker, expiry, deadline };
+                Ok(mailbox.push(envelope, priority, |envelope| on_accept(&envelope.msg.body)))
+            }
+            None => {
+                let _turn = self.0.turnstile.as_ref().map(Turnstile::enter);
+                on_accept(&msg.body);
+                let read = self.read_with_context(msg, ReplyContext::new(from, self.id(), self.as_mediator(), deadline.as_ref()));
+                if let Some(acker) = acker {
+                    acker.ack(&read);
+                }
+                read?;
+                Ok(true)
+            }
+        }
+    }
+}
+
+impl<T: Colleague> Clone for Registered<T> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+/// Non-owning counterpart of [`Registered`], see [`Registered::downgrade`].
+pub struct WeakRegistered<T: Colleague>(Weak<Managed<T>>);
+
+impl<T: Colleague> WeakRegistered<T> {
+    /// `None` once every [`Registered`] handle of the colleague, the mediator's included,
+    /// is gone.
+    pub fn upgrade(&self) -> Option<Registered<T>> {
+        self.0.upgrade().map(Registered)
+    }
+    
+    pub fn strong_count(&self) -> usize {
+        self.0.strong_count()
+    }
+}
+
+impl<T: Colleague> Clone for WeakRegistered<T> {
+    fn clone(&self) -> Self {
+        Self(Weak::clone(&self.0))
+    }
+}
+
+/// Handles are equal when their colleagues share an id, clones of one registration included.
+impl<T: Colleague> PartialEq for Registered<T>
+    where T::Identifier: PartialEq
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.id().eq(other.id())
+    }
+}
+
+impl<T: Colleague> Eq for Registered<T>
+    where T::Identifier: Eq
+{}
+
+impl<T: Colleague> Hash for Registered<T>
+    where T::Identifier: Hash
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id().hash(state);
+    }
+}
+
+impl<T: Colleague + Debug> Debug for Registered<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Registered").field(&self.0.inner).finish()
+    }
+}
+
+impl<T: Colleague> Deref for Registered<T> {
+    type Target = T;
+    
+    fn deref(&self) -> &Self::Target {
+        &self.0.inner
+    }
+}
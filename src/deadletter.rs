@@ -0,0 +1,224 @@
+use std::time::Duration;
+
+use crate::error::{Error, Operation};
+use crate::lock::{self, RwLock};
+use crate::message::MessageId;
+
+/// A message that could not be delivered, and why.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DeadLetter<I, M> {
+    pub id: MessageId,
+    pub from: I,
+    pub to: I,
+    pub msg: M,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub reason: DeadletterReason,
+}
+
+/// Why a [`DeadLetter`] wasn't delivered. Apart from a missing or paused recipient and an
+/// expired message, letters are only kept for these reasons while the deadletter queue is
+/// [enabled](crate::GenericMediatorBuilder::deadletter).
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum DeadletterReason {
+    /// Nobody was registered under the recipient's id.
+    #[default]
+    NotRegistered,
+    /// The recipient's [filter](crate::GenericMediator::set_filter) wasn't interested; the
+    /// consultation still comes out as [filtered](crate::DeliveryOutcome::Filtered).
+    Filtered,
+    /// The sender had used up its [quota](crate::GenericMediator::set_quota); the consultation
+    /// still fails with [`Error::QuotaExceeded`](crate::Error::QuotaExceeded).
+    QuotaExceeded,
+    /// The recipient was [paused](crate::GenericMediator::pause) and had no mailbox.
+    Paused,
+    /// The recipient doesn't accept the message's schema version; the consultation still fails
+    /// with [`Error::VersionMismatch`](crate::Error::VersionMismatch).
+    VersionMismatch,
+    /// The message was still in the recipient's mailbox when the mediator shut down.
+    ShuttingDown,
+    /// The message's [ttl](crate::GenericMediator::consult_with_ttl) ran out in a mailbox.
+    Expired,
+}
+
+/// What a deadletter queue at its
+/// [capacity](crate::GenericMediatorBuilder::deadletter_capacity) does with one letter too many.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub enum DeadletterPolicy {
+    /// Makes room by dropping the oldest letter.
+    #[default]
+    DropOldest,
+    /// Drops the new letter, keeping the queue as it is.
+    DropNewest,
+    /// Fails the consultation with [`Error::DeadletterFull`](crate::Error::DeadletterFull).
+    RejectSend,
+}
+
+/// How [`GenericMediator::retry_deadletters`](crate::GenericMediator::retry_deadletters)
+/// goes about redelivering deadletters, e.g. to recipients that registered late.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct RetryPolicy {
+    /// Rounds of delivery attempts per retry, at least one.
+    pub max_attempts: u32,
+    /// Pause between two rounds.
+    pub backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    /// A single round, no pause.
+    fn default() -> Self {
+        Self { max_attempts: 1, backoff: Duration::ZERO }
+    }
+}
+
+/// What became of the deadletters a retry went through.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct RetryReport {
+    pub delivered: usize,
+    /// Dropped because the recipient's filter wasn't interested.
+    pub filtered: usize,
+    /// Dropped because the recipient's `read_msg` refused them or its mailbox was full.
+    pub rejected: usize,
+    /// Still undeliverable after every attempt and back in the deadletter queue.
+    pub dead: usize,
+}
+
+/// A mediator's deadletter queue, along with the settings it was built with: whether it is
+/// [enabled](crate::GenericMediatorBuilder::deadletter) at all, how much it
+/// [holds](crate::GenericMediatorBuilder::deadletter_capacity) and how it is
+/// [retried](crate::GenericMediatorBuilder::retry_policy).
+pub(crate) struct DeadletterQueue<I, M> {
+    letters: RwLock<Vec<DeadLetter<I, M>>>,
+    enabled: bool,
+    capacity: Option<(usize, DeadletterPolicy)>,
+    retry: RetryPolicy,
+}
+
+/// What the queue did with a letter [offered](DeadletterQueue::offer) to it.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub(crate) enum Offer {
+    Queued,
+    /// Accepted, but dropped right away as the queue was full under [`DeadletterPolicy::DropNewest`].
+    Dropped,
+    /// Turned away as the queue was full under [`DeadletterPolicy::RejectSend`].
+    Rejected,
+}
+
+impl<I, M> DeadletterQueue<I, M> {
+    pub(crate) fn new(enabled: bool, capacity: Option<(usize, DeadletterPolicy)>, retry: RetryPolicy) -> Self {
+        Self { letters: RwLock::default(), enabled, capacity, retry }
+    }
+    
+    /// Whether undeliverable messages are deadlettered as a rule. Some, such as those for a paused
+    /// recipient or expired ones, are deadlettered either way.
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+    
+    pub(crate) fn retry_policy(&self) -> RetryPolicy {
+        self.retry
+    }
+    
+    pub(crate) fn len(&self, during: Operation) -> Result<usize, Error> {
+        Ok(lock::read(&self.letters, during)?.len())
+    }
+    
+    /// How many letters are queued, unless the queue is locked right now.
+    pub(crate) fn try_len(&self) -> Option<usize> {
+        lock::try_read(&self.letters)
+            .map(|letters| letters.len())
+    }
+    
+    pub(crate) fn letters(&self, during: Operation) -> Result<Vec<DeadLetter<I, M>>, Error>
+        where I: Clone,
+              M: Clone
+    {
+        Ok(lock::read(&self.letters, during)?.clone())
+    }
+    
+    pub(crate) fn drain(&self, during: Operation) -> Result<Vec<DeadLetter<I, M>>, Error> {
+        Ok(std::mem::take(&mut *lock::write(&self.letters, during)?))
+    }
+    
+    /// Takes out the letters addressed to `to`, oldest first, leaving the others queued.
+    pub(crate) fn drain_for(&self, to: &I, during: Operation) -> Result<Vec<DeadLetter<I, M>>, Error>
+        where I: Eq
+    {
+        let mut letters = lock::write(&self.letters, during)?;
+        let (addressed, others) = std::mem::take(&mut *letters).into_iter().partition(|letter| letter.to.eq(to));
+        *letters = others;
+        Ok(addressed)
+    }
+    
+    pub(crate) fn replace(&self, letters: Vec<DeadLetter<I, M>>, during: Operation) -> Result<(), Error> {
+        *lock::write(&self.letters, during)? = letters;
+        Ok(())
+    }
+    
+    /// Makes room for `additional` more letters, but never past the capacity, and only if the
+    /// queue is enabled, since nothing is ever queued otherwise.
+    pub(crate) fn reserve(&self, additional: usize) -> Result<(), Error> {
+        if self.enabled {
+            let mut letters = lock::write(&self.letters, Operation::Deadletters)?;
+            let room = self.capacity
+                .map_or(additional, |(capacity, _)| additional.min(capacity.saturating_sub(letters.len())));
+            letters.reserve(room);
+        }
+        Ok(())
+    }
+    
+    /// Queues `letter` as far as the capacity allows. `accepted` hears of it unless it is
+    /// rejected, and `queued` once it is in the queue, both while the queue is locked.
+    pub(crate) fn offer(&self, letter: DeadLetter<I, M>, accepted: impl FnOnce(&DeadLetter<I, M>), queued: impl FnOnce(&DeadLetter<I, M>)) -> Result<Offer, Error> {
+        let mut letters = lock::write(&self.letters, Operation::Consultation)?;
+        let full = self.capacity.filter(|&(capacity, _)| letters.len() >= capacity);
+        if let Some((_, DeadletterPolicy::RejectSend)) = full {
+            return Ok(Offer::Rejected);
+        }
+        accepted(&letter);
+        if let Some((_, DeadletterPolicy::DropNewest)) = full {
+            return Ok(Offer::Dropped);
+        }
+        letters.push(letter);
+        if let Some((capacity, _)) = full {
+            let excess = letters.len() - capacity;
+            letters.drain(..excess);
+        }
+        if let Some(letter) = letters.last() {
+            queued(letter);
+        }
+        Ok(Offer::Queued)
+    }
+    
+    /// Queues an expired message, which always goes to the queue, whatever its capacity.
+    pub(crate) fn expired(&self, letter: DeadLetter<I, M>) {
+        lock::recover_write(&self.letters)
+            .push(letter);
+    }
+    
+    /// Puts the letters a retry is left with back in front of the queue, trimming it back to
+    /// its capacity and counting the retried letters given up for that out of `report`.
+    pub(crate) fn requeue(&self, mut retried: Vec<DeadLetter<I, M>>, report: &mut RetryReport) -> Result<(), Error> {
+        let mut letters = lock::write(&self.letters, Operation::RetryDeadletters)?;
+        retried.append(&mut letters);
+        // Letters that came in meanwhile may have filled up the room the retried ones left.
+        if let Some((capacity, policy)) = self.capacity {
+            let excess = retried.len().saturating_sub(capacity);
+            match policy {
+                DeadletterPolicy::DropNewest => retried.truncate(capacity),
+                DeadletterPolicy::DropOldest => drop(retried.drain(..excess)),
+                // Senders of the letters queued meanwhile were told they made it in, so it's
+                // the retried letters in front, which no longer fit, that are given up.
+                DeadletterPolicy::RejectSend => {
+                    let given_up = excess.min(report.dead);
+                    retried.drain(..given_up);
+                    report.dead -= given_up;
+                }
+            }
+        }
+        *letters = retried;
+        Ok(())
+    }
+}
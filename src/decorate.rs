@@ -0,0 +1,96 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::colleague::{Colleague, Managed};
+use crate::error::ColleagueError;
+use crate::generic::GenericMediator;
+
+/// Wraps a colleague so every message it reads goes through `decorate` first, e.g. to trim or
+/// log it, without touching the colleague itself.
+///
+/// A decorated colleague is a colleague of its own type, registered with a [`GenericMediator`]
+/// of decorated colleagues rather than with the inner colleague's mediator. Everything besides
+/// [`Colleague::read_msg`] is handed to the inner colleague as is; messages reaching it through
+/// the default `read_msg_ref` and `read_with_headers` are decorated too, as those end up in
+/// `read_msg`.
+pub struct Decorate<C, F> {
+    inner: C,
+    decorate: F,
+}
+
+impl<C, F> Decorate<C, F> {
+    pub fn new(inner: C, decorate: F) -> Self {
+        Self { inner, decorate }
+    }
+    
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+    
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+}
+
+impl<C: Debug, F> Debug for Decorate<C, F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Decorate").field(&self.inner).finish()
+    }
+}
+
+impl<C, F> Colleague for Decorate<C, F>
+    where C: Colleague,
+          C::Identifier: Eq + Hash + Clone + Debug,
+          C::Request: Debug,
+          F: Fn(C::Message) -> C::Message
+{
+    type Identifier = C::Identifier;
+    type Mediator = GenericMediator<Self>;
+    type Message = C::Message;
+    type Request = C::Request;
+    type Response = C::Response;
+    
+    fn id(&self) -> &Self::Identifier {
+        self.inner.id()
+    }
+    
+    fn belong_to(self, mediator: Self::Mediator) -> Managed<Self> {
+        Managed::new(self, mediator)
+    }
+    
+    fn read_msg(&self, msg: Self::Message) -> Result<(), ColleagueError> {
+        self.inner.read_msg((self.decorate)(msg))
+    }
+    
+    fn read_chunk(&self, chunk: Vec<u8>) -> Result<(), ColleagueError> {
+        self.inner.read_chunk(chunk)
+    }
+    
+    fn read_end(&self) -> Result<(), ColleagueError> {
+        self.inner.read_end()
+    }
+    
+    fn can_accept(&self, msg: &Self::Message) -> bool {
+        self.inner.can_accept(msg)
+    }
+    
+    fn on_ping(&self) -> bool {
+        self.inner.on_ping()
+    }
+    
+    fn on_shutdown(&self) {
+        self.inner.on_shutdown()
+    }
+    
+    fn schema_version(&self) -> u16 {
+        self.inner.schema_version()
+    }
+    
+    fn accepts_version(&self, version: u16) -> bool {
+        self.inner.accepts_version(version)
+    }
+    
+    fn handle_request(&self, req: Self::Request) -> Self::Response {
+        self.inner.handle_request(req)
+    }
+}
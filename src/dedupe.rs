@@ -0,0 +1,63 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crate::message::MessageId;
+
+/// Explicit message ids seen within the last `window`, keeping at most `capacity` of them;
+/// the oldest ones are forgotten first.
+pub(crate) struct Dedupe {
+    window: Duration,
+    capacity: usize,
+    seen: Mutex<Seen>,
+}
+
+#[derive(Default)]
+struct Seen {
+    at: HashMap<MessageId, Instant>,
+    /// Ids in the order they were seen. Entries of ids that were forgotten early linger
+    /// until they reach the front.
+    order: VecDeque<(MessageId, Instant)>,
+}
+
+impl Seen {
+    fn pop_oldest(&mut self) {
+        if let Some((id, at)) = self.order.pop_front() {
+            if self.at.get(&id) == Some(&at) {
+                self.at.remove(&id);
+            }
+        }
+    }
+}
+
+impl Dedupe {
+    pub(crate) fn new(window: Duration, capacity: usize) -> Self {
+        Self { window, capacity, seen: Mutex::default() }
+    }
+    
+    /// Records `id` as seen at `now`, unless it was seen within the window already.
+    pub(crate) fn admit(&self, id: MessageId, now: Instant) -> bool {
+        let mut seen = self.lock();
+        while seen.order.front().is_some_and(|&(_, at)| now.duration_since(at) >= self.window) {
+            seen.pop_oldest();
+        }
+        if seen.at.contains_key(&id) {
+            return false;
+        }
+        while !seen.at.is_empty() && seen.at.len() >= self.capacity {
+            seen.pop_oldest();
+        }
+        seen.at.insert(id, now);
+        seen.order.push_back((id, now));
+        true
+    }
+    
+    /// Lets `id` through again, e.g. because its delivery failed before anyone read it.
+    pub(crate) fn forget(&self, id: MessageId) {
+        self.lock().at.remove(&id);
+    }
+    
+    fn lock(&self) -> MutexGuard<'_, Seen> {
+        self.seen.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
@@ -0,0 +1,299 @@
+//! The colleague/mediator core on nothing but `core` and `alloc`, for targets without `std`.
+//!
+//! Registries are `BTreeMap`s behind a [`spin::RwLock`], so identifiers need `Ord` rather
+//! than `Hash`, or fixed-size arrays for targets that know how many colleagues they'll
+//! have. There are no mailboxes, observers or deadletters here; messages are read right
+//! away on the sender's stack.
+//!
+//! These are traits of their own rather than a core the `std` ones build on. The `std`
+//! `Colleague` answers requests, refuses with a `ColleagueError` and has hooks for pings,
+//! chunks, shutdown and schema versions, and its `Mediator` is sealed and hands out the
+//! `Registered` handles mailboxes, weak registrations and redelivery hang off; none of that
+//! has a place on a target without threads or a clock, and sharing a core would have it
+//! either carry all of it or split every `std` trait in two. Colleagues written against one
+//! are therefore not colleagues of the other.
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Display, Formatter};
+use core::ops::Deref;
+
+use spin::RwLock;
+
+pub trait Colleague: Sized {
+    type Identifier: Ord + Clone + Debug;
+    type Mediator: Mediator<Self>;
+    type Message;
+    fn id(&self) -> &Self::Identifier;
+    fn belong_to(self, mediator: Self::Mediator) -> Managed<Self>;
+    /// Returns the reason for refusing `msg`, if it does.
+    fn read_msg(&self, msg: Self::Message) -> Result<(), String>;
+}
+
+pub trait Mediator<T: Colleague> {
+    /// Handed out on registering, to reach the colleague through.
+    type Handle;
+    /// Handed back on deregistering.
+    type Deregistered;
+    fn register(&mut self, id: T::Identifier, registered: Managed<T>) -> Result<Self::Handle, Error>;
+    fn deregister(&mut self, id: &T::Identifier) -> Result<Option<Self::Deregistered>, Error>;
+    fn is_registered(&self, id: &T::Identifier) -> bool;
+    fn consultation(&self, from: &T, to: &T::Identifier, msg: T::Message) -> Result<(), Error>;
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    NotRegistered { id: String },
+    AlreadyRegistered { id: String },
+    SelfSend { id: String },
+    DeliveryRejected { id: String, reason: String },
+    RegistryFull { capacity: usize },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NotRegistered { id } => write!(f, "{} is not registered", id),
+            Error::AlreadyRegistered { id } => write!(f, "{} is already registered", id),
+            Error::SelfSend { id } => write!(f, "{} tried to send a message to itself", id),
+            Error::DeliveryRejected { id, reason } => write!(f, "{} rejected the message: {}", id, reason),
+            Error::RegistryFull { capacity } => write!(f, "registry is full with {} colleagues", capacity),
+        }
+    }
+}
+
+pub struct Managed<T: Colleague> {
+    inner: T,
+    mediator: T::Mediator,
+}
+
+impl<T: Colleague> Managed<T> {
+    pub fn new(t: T, bus: T::Mediator) -> Self {
+        Self { inner: t, mediator: bus }
+    }
+}
+
+impl<T: Colleague> Deref for Managed<T> {
+    type Target = T;
+    
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+pub struct Registered<T: Colleague>(Arc<Managed<T>>);
+
+impl<T: Colleague> Registered<T> {
+    pub fn as_mediator(&self) -> &T::Mediator {
+        &self.0.mediator
+    }
+}
+
+impl<T: Colleague> Clone for Registered<T> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<T: Colleague> Deref for Registered<T> {
+    type Target = T;
+    
+    fn deref(&self) -> &Self::Target {
+        &self.0.inner
+    }
+}
+
+/// Mediator over any colleague whose identifier can key a `BTreeMap`.
+pub struct BTreeMediator<C: Colleague> {
+    colleagues: Arc<RwLock<BTreeMap<C::Identifier, Registered<C>>>>,
+}
+
+impl<C: Colleague> BTreeMediator<C> {
+    pub fn registered_ids(&self) -> Vec<C::Identifier> {
+        self.colleagues.read()
+            .keys()
+            .cloned()
+            .collect()
+    }
+}
+
+impl<C: Colleague> Default for BTreeMediator<C> {
+    fn default() -> Self {
+        Self { colleagues: Arc::new(RwLock::new(BTreeMap::new())) }
+    }
+}
+
+impl<C: Colleague> Clone for BTreeMediator<C> {
+    fn clone(&self) -> Self {
+        Self { colleagues: Arc::clone(&self.colleagues) }
+    }
+}
+
+impl<C: Colleague> Mediator<C> for BTreeMediator<C> {
+    type Handle = Registered<C>;
+    type Deregistered = Registered<C>;
+    
+    fn register(&mut self, id: C::Identifier, registered: Managed<C>) -> Result<Registered<C>, Error> {
+        let mut colleagues = self.colleagues.write();
+        if colleagues.contains_key(&id) {
+            return Err(Error::AlreadyRegistered { id: format!("{:?}", id) });
+        }
+        let reg = Registered(Arc::new(registered));
+        colleagues.insert(id, reg.clone());
+        Ok(reg)
+    }
+    
+    fn deregister(&mut self, id: &C::Identifier) -> Result<Option<Registered<C>>, Error> {
+        Ok(self.colleagues.write().remove(id))
+    }
+    
+    fn is_registered(&self, id: &C::Identifier) -> bool {
+        self.colleagues.read().contains_key(id)
+    }
+    
+    fn consultation(&self, from: &C, to: &C::Identifier, msg: C::Message) -> Result<(), Error> {
+        if from.id().eq(to) {
+            return Err(Error::SelfSend { id: format!("{:?}", to) });
+        }
+        
+        // Read outside the lock so the recipient can consult others from `read_msg`.
+        let recipient = self.colleagues.read()
+            .get(to)
+            .cloned()
+            .ok_or_else(|| Error::NotRegistered { id: format!("{:?}", to) })?;
+        recipient.read_msg(msg)
+            .map_err(|reason| Error::DeliveryRejected { id: format!("{:?}", to), reason })
+    }
+}
+
+type Slots<C, const N: usize> = [Option<(<C as Colleague>::Identifier, Managed<C>)>; N];
+
+/// Where a colleague sits in a [`FixedMediator`], handed out instead of a reference-counted
+/// handle. It names the colleague by its id too, so a slot that was freed and taken by
+/// someone else since doesn't lead there.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Slot<I> {
+    index: usize,
+    id: I,
+}
+
+impl<I> Slot<I> {
+    pub fn id(&self) -> &I {
+        &self.id
+    }
+}
+
+/// Mediator over at most `N` colleagues, kept inline in the mediator itself: registering and
+/// consulting never touch the heap, though the errors, which name the id, do. Registering the
+/// `N + 1`th colleague fails with [`Error::RegistryFull`]. Lookups scan the slots, which is
+/// what a handful of colleagues calls for anyway.
+///
+/// As the colleagues live inside it, they can't hold the mediator itself but a reference to
+/// it, i.e. `type Mediator = &'static FixedMediator<Self, N>` for one kept in a `static`, made
+/// with the `const` [`FixedMediator::new`]. Recipients read under the registry's read lock, so
+/// they may consult others from [`Colleague::read_msg`] but not register or deregister.
+pub struct FixedMediator<C: Colleague, const N: usize> {
+    slots: RwLock<Slots<C, N>>,
+}
+
+impl<C: Colleague, const N: usize> FixedMediator<C, N> {
+    pub const fn new() -> Self {
+        Self { slots: RwLock::new([const { None }; N]) }
+    }
+    
+    /// Takes the first free slot; fails with [`Error::RegistryFull`] if there is none.
+    pub fn register(&self, id: C::Identifier, registered: Managed<C>) -> Result<Slot<C::Identifier>, Error> {
+        let mut slots = self.slots.write();
+        if Self::find(&slots, &id).is_some() {
+            return Err(Error::AlreadyRegistered { id: format!("{:?}", id) });
+        }
+        let index = slots.iter()
+            .position(Option::is_none)
+            .ok_or(Error::RegistryFull { capacity: N })?;
+        slots[index] = Some((id.clone(), registered));
+        Ok(Slot { index, id })
+    }
+    
+    /// Frees the slot of `id`, handing its colleague back.
+    pub fn deregister(&self, id: &C::Identifier) -> Option<Managed<C>> {
+        let mut slots = self.slots.write();
+        Self::find(&slots, id)
+            .and_then(|index| slots[index].take())
+            .map(|(_, managed)| managed)
+    }
+    
+    pub fn is_registered(&self, id: &C::Identifier) -> bool {
+        Self::find(&self.slots.read(), id).is_some()
+    }
+    
+    /// Calls `f` with the colleague in `slot`, under the read lock, unless it was deregistered.
+    pub fn with<R>(&self, slot: &Slot<C::Identifier>, f: impl FnOnce(&C) -> R) -> Option<R> {
+        let slots = self.slots.read();
+        match &slots[slot.index] {
+            Some((id, managed)) if id.eq(&slot.id) => Some(f(managed)),
+            _ => None,
+        }
+    }
+    
+    pub fn consultation(&self, from: &C, to: &C::Identifier, msg: C::Message) -> Result<(), Error> {
+        if from.id().eq(to) {
+            return Err(Error::SelfSend { id: format!("{:?}", to) });
+        }
+        
+        let slots = self.slots.read();
+        let (_, recipient) = Self::find(&slots, to)
+            .and_then(|index| slots[index].as_ref())
+            .ok_or_else(|| Error::NotRegistered { id: format!("{:?}", to) })?;
+        recipient.read_msg(msg)
+            .map_err(|reason| Error::DeliveryRejected { id: format!("{:?}", to), reason })
+    }
+    
+    /// The ids registered right now, in slot order, copied out so the lock isn't held.
+    pub fn registered_ids(&self) -> impl Iterator<Item = C::Identifier> {
+        let slots = self.slots.read();
+        let ids: [Option<C::Identifier>; N] = core::array::from_fn(|index| slots[index].as_ref().map(|(id, _)| id.clone()));
+        ids.into_iter().flatten()
+    }
+    
+    pub fn registered_count(&self) -> usize {
+        self.slots.read()
+            .iter()
+            .flatten()
+            .count()
+    }
+    
+    fn find(slots: &Slots<C, N>, id: &C::Identifier) -> Option<usize> {
+        slots.iter().position(|slot| slot.as_ref().is_some_and(|(taken, _)| taken.eq(id)))
+    }
+}
+
+impl<C: Colleague, const N: usize> Default for FixedMediator<C, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: Colleague, const N: usize> Mediator<C> for &FixedMediator<C, N> {
+    type Handle = Slot<C::Identifier>;
+    type Deregistered = Managed<C>;
+    
+    fn register(&mut self, id: C::Identifier, registered: Managed<C>) -> Result<Slot<C::Identifier>, Error> {
+        FixedMediator::register(self, id, registered)
+    }
+    
+    fn deregister(&mut self, id: &C::Identifier) -> Result<Option<Managed<C>>, Error> {
+        Ok(FixedMediator::deregister(self, id))
+    }
+    
+    fn is_registered(&self, id: &C::Identifier) -> bool {
+        FixedMediator::is_registered(self, id)
+    }
+    
+    fn consultation(&self, from: &C, to: &C::Identifier, msg: C::Message) -> Result<(), Error> {
+        FixedMediator::consultation(self, from, to, msg)
+    }
+}
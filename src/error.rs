@@ -0,0 +1,155 @@
+use std::fmt::{Display, Formatter};
+use std::io;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Eq, PartialEq, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("cannot lock during {during}")]
+    LockPoison { during: Operation },
+    #[error("{id} is not registered")]
+    NotRegistered { id: String },
+    #[error("{id} is already registered")]
+    AlreadyRegistered { id: String },
+    #[error("{id} tried to send a message to itself")]
+    SelfSend { id: String },
+    #[error("{id} rejected the message: {reason}")]
+    DeliveryRejected { id: String, reason: String },
+    #[error("{id} did not handle the message within {timeout:?}")]
+    DeliveryTimeout { id: String, timeout: Duration },
+    #[error("{id} did not register within {timeout:?}")]
+    RegistrationTimeout { id: String, timeout: Duration },
+    #[error("{id} panicked while reading the message and was deregistered")]
+    HandlerPanicked { id: String },
+    #[error("publishing to {id} failed: {reason}")]
+    PublishFailed { id: String, reason: String },
+    #[error("circuit of {id} is open after repeated rejections")]
+    CircuitOpen { id: String },
+    #[error("mailbox of {id} is full")]
+    MailboxFull { id: String },
+    #[error("no room left in the deadletter queue for a message to {id}")]
+    DeadletterFull { id: String },
+    #[error("{id} expects messages of schema version {expected}, got version {got}")]
+    VersionMismatch { id: String, got: u16, expected: u16 },
+    #[error("message rejected by middleware: {reason}")]
+    Rejected { reason: String },
+    #[error("{id} expects messages of type {expected}, got {got}")]
+    MessageTypeMismatch { id: String, expected: &'static str, got: &'static str },
+    #[error("{id} has used up its send quota")]
+    QuotaExceeded { id: String },
+    #[error("group {group} does not exist")]
+    GroupNotFound { group: String },
+    #[error("no worker is registered for role {role}")]
+    NoWorkers { role: String },
+    #[error("gave up forwarding to parent mediators after {depth} hops")]
+    ForwardDepthExceeded { depth: usize },
+    #[error("{id} declined to take the message")]
+    PrepareDeclined { id: String },
+    #[error("no routing rule matched the message")]
+    NoRoute,
+    #[error("the delivery was cancelled")]
+    Cancelled,
+    #[error("mediator is shutting down")]
+    ShuttingDown,
+}
+
+/// For code speaking `std::io`, keeping the error as the source of an [`io::Error`] of the
+/// closest [`io::ErrorKind`].
+impl From<Error> for io::Error {
+    fn from(e: Error) -> Self {
+        let kind = match &e {
+            Error::NotRegistered { .. } | Error::GroupNotFound { .. } | Error::NoWorkers { .. } | Error::NoRoute => io::ErrorKind::NotFound,
+            Error::AlreadyRegistered { .. } => io::ErrorKind::AlreadyExists,
+            Error::SelfSend { .. } => io::ErrorKind::InvalidInput,
+            Error::MessageTypeMismatch { .. } | Error::VersionMismatch { .. } => io::ErrorKind::InvalidData,
+            Error::DeliveryTimeout { .. } | Error::RegistrationTimeout { .. } => io::ErrorKind::TimedOut,
+            Error::MailboxFull { .. } | Error::DeadletterFull { .. } | Error::QuotaExceeded { .. } => io::ErrorKind::WouldBlock,
+            Error::CircuitOpen { .. } => io::ErrorKind::ConnectionRefused,
+            Error::Rejected { .. } => io::ErrorKind::PermissionDenied,
+            Error::Cancelled => io::ErrorKind::Interrupted,
+            Error::LockPoison { .. }
+            | Error::DeliveryRejected { .. }
+            | Error::PrepareDeclined { .. }
+            | Error::HandlerPanicked { .. }
+            | Error::PublishFailed { .. }
+            | Error::ForwardDepthExceeded { .. }
+            | Error::ShuttingDown => io::ErrorKind::Other,
+        };
+        io::Error::new(kind, e)
+    }
+}
+
+/// What a mediator was doing when it hit a poisoned lock, see [`Error::LockPoison`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum Operation {
+    Register,
+    Consultation,
+    Multicast,
+    Snapshot,
+    Deadletters,
+    RetryDeadletters,
+    Filter,
+    Quota,
+    Group,
+    Tags,
+    Roles,
+    Fallback,
+    Transform,
+    Tap,
+    Stamper,
+    Bridge,
+    Watch,
+    Pause,
+    Router,
+}
+
+impl Display for Operation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let op = match self {
+            Operation::Register => "register",
+            Operation::Consultation => "consultation",
+            Operation::Multicast => "multicast",
+            Operation::Snapshot => "snapshot",
+            Operation::Deadletters => "deadletter access",
+            Operation::RetryDeadletters => "deadletter retry",
+            Operation::Filter => "filter update",
+            Operation::Quota => "quota update",
+            Operation::Group => "group update",
+            Operation::Tags => "tag lookup",
+            Operation::Roles => "role lookup",
+            Operation::Fallback => "fallback update",
+            Operation::Transform => "transform update",
+            Operation::Tap => "tap installation",
+            Operation::Stamper => "stamper update",
+            Operation::Bridge => "bridge routing",
+            Operation::Watch => "watch installation",
+            Operation::Pause => "pause update",
+            Operation::Router => "content routing",
+        };
+        f.write_str(op)
+    }
+}
+
+/// Why [`GenericMediator::try_register`](crate::GenericMediator::try_register) didn't register.
+#[derive(Debug, Clone, Eq, PartialEq, thiserror::Error)]
+pub enum TryRegisterError {
+    #[error("the registry is locked by someone else")]
+    WouldBlock,
+    #[error(transparent)]
+    Failed(#[from] Error),
+}
+
+/// Returned from [`Colleague::read_msg`](crate::Colleague::read_msg) when a colleague refuses or
+/// fails to process a message; the sender sees it as [`Error::DeliveryRejected`].
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("{reason}")]
+pub struct ColleagueError {
+    pub reason: String,
+}
+
+impl ColleagueError {
+    pub fn new(reason: impl Into<String>) -> ColleagueError {
+        Self { reason: reason.into() }
+    }
+}
@@ -0,0 +1,120 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Debug, Write};
+use std::time::SystemTime;
+
+use crate::deadletter::DeadletterReason;
+use crate::error::Error;
+use crate::lock::{self, RwLock};
+use crate::message::{DeliveryOutcome, Message};
+
+/// Something that happened on a mediator, as recorded by its event log.
+#[derive(Debug, Clone)]
+pub struct Event<I, M> {
+    pub at: SystemTime,
+    pub kind: EventKind<I, M>,
+}
+
+#[derive(Debug, Clone)]
+pub enum EventKind<I, M> {
+    /// `weak` for a [weak registration](crate::GenericMediator::register_weak), which is
+    /// recorded as deregistered once its colleague turns out to be gone.
+    Registered { id: I, weak: bool },
+    Deregistered { id: I },
+    /// A registration replaced an existing colleague with the same id.
+    Superseded { id: I },
+    /// A colleague was [renamed](crate::Mediator::rename) from `old` to `new`.
+    Renamed { old: I, new: I },
+    /// The message was handed over to `to`, as `to` got it, i.e. after the stamper,
+    /// middlewares and transforms; `to` may still have refused it, see
+    /// [`EventKind::Consulted`] for how a consultation came out.
+    Delivered { from: I, to: I, msg: M },
+    Deadletter { from: I, to: I, msg: M, reason: DeadletterReason },
+    /// A consultation as it was sent, before the stamper and middlewares got to it, and how it
+    /// came out in the end.
+    Consulted { from: I, to: I, msg: Message<M>, outcome: Result<DeliveryOutcome, Error> },
+    /// A broadcast as it was sent, and how many colleagues it reached.
+    Broadcast { from: I, msg: M, reached: usize },
+}
+
+/// Append-only event storage. Messages are copied into the log, so it can only be created
+/// for cloneable messages; the mediators themselves don't need that bound.
+pub(crate) struct EventLog<I, M> {
+    events: RwLock<Vec<Event<I, M>>>,
+    clone_msg: fn(&M) -> M,
+}
+
+impl<I, M> EventLog<I, M> {
+    pub(crate) fn new() -> Self
+        where M: Clone
+    {
+        Self { events: RwLock::default(), clone_msg: M::clone }
+    }
+    
+    pub(crate) fn record(&self, kind: EventKind<I, M>) {
+        lock::recover_write(&self.events)
+            .push(Event { at: SystemTime::now(), kind });
+    }
+    
+    pub(crate) fn copy(&self, msg: &M) -> M {
+        (self.clone_msg)(msg)
+    }
+    
+    pub(crate) fn copy_message(&self, msg: &Message<M>) -> Message<M> {
+        Message { headers: msg.headers.clone(), version: msg.version, body: self.copy(&msg.body) }
+    }
+    
+    pub(crate) fn events(&self) -> Vec<Event<I, M>>
+        where I: Clone,
+              M: Clone
+    {
+        lock::recover_read(&self.events)
+            .clone()
+    }
+    
+    /// The log as a Graphviz digraph: a node for every colleague it mentions and an edge for
+    /// every sender and recipient pair, labelled with how many messages went that way.
+    /// Deadlettered messages get dashed edges of their own. Nodes are named by their `Debug`
+    /// output and listed in its order, so the same log always renders the same way.
+    pub(crate) fn to_dot(&self) -> String
+        where I: Debug
+    {
+        let mut nodes = BTreeSet::new();
+        let mut edges = BTreeMap::new();
+        for event in lock::recover_read(&self.events).iter() {
+            match &event.kind {
+                EventKind::Registered { id, .. } | EventKind::Deregistered { id } | EventKind::Superseded { id } => {
+                    nodes.insert(quoted(id));
+                }
+                EventKind::Renamed { old, new } => {
+                    nodes.insert(quoted(old));
+                    nodes.insert(quoted(new));
+                }
+                EventKind::Delivered { from, to, .. } | EventKind::Deadletter { from, to, .. } => {
+                    let deadletter = matches!(event.kind, EventKind::Deadletter { .. });
+                    let (from, to) = (quoted(from), quoted(to));
+                    nodes.insert(from.clone());
+                    nodes.insert(to.clone());
+                    *edges.entry((from, to, deadletter)).or_insert(0usize) += 1;
+                }
+                // Their deliveries and deadletters are recorded on their own.
+                EventKind::Consulted { .. } | EventKind::Broadcast { .. } => {}
+            }
+        }
+        
+        let mut dot = String::from("digraph mediator {\n");
+        for node in &nodes {
+            let _ = writeln!(dot, "    {};", node);
+        }
+        for ((from, to, deadletter), count) in &edges {
+            let style = if *deadletter { ", style=dashed" } else { "" };
+            let _ = writeln!(dot, "    {} -> {} [label=\"{}\"{}];", from, to, count, style);
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// `id`'s `Debug` output as a DOT string literal.
+fn quoted<I: Debug>(id: &I) -> String {
+    format!("\"{}\"", format!("{:?}", id).replace('\\', "\\\\").replace('"', "\\\""))
+}
@@ -0,0 +1,153 @@
+//! C ABI over a mediator of string-identified colleagues exchanging UTF-8 strings.
+//!
+//! Build a library for C with e.g. `cargo rustc --release --features ffi --crate-type cdylib`.
+//! Every delivery is reported to the callback passed to [`mediator_new`], on the sending
+//! thread, with pointers that are only valid for the duration of the call.
+
+use std::ffi::{c_char, c_int, c_void, CStr, CString};
+
+use crate::colleague::{Colleague, Managed};
+use crate::error::{ColleagueError, Error};
+use crate::generic::GenericMediator;
+use crate::mediator::sealed::{self, ConsultAs};
+use crate::mediator::Mediator;
+use crate::message::Message;
+use crate::reply::ReplyContext;
+
+pub const MEDIATOR_OK: c_int = 0;
+/// A null pointer, or a string that isn't valid UTF-8.
+pub const MEDIATOR_INVALID_ARGUMENT: c_int = -1;
+pub const MEDIATOR_ALREADY_REGISTERED: c_int = -2;
+pub const MEDIATOR_NOT_REGISTERED: c_int = -3;
+/// Any other [`Error`], e.g. a self-send.
+pub const MEDIATOR_FAILED: c_int = -4;
+
+/// Called with `user_data`, the sender, the recipient and the message, all NUL-terminated.
+pub type MediatorCallback = extern "C" fn(user_data: *mut c_void, from: *const c_char, to: *const c_char, msg: *const c_char);
+
+/// Opaque handle returned by [`mediator_new`].
+pub struct FfiMediator {
+    inner: GenericMediator<FfiColleague>,
+    sink: Sink,
+}
+
+struct FfiColleague {
+    id: String,
+    sink: Sink,
+}
+
+#[derive(Clone, Copy)]
+struct Sink {
+    callback: MediatorCallback,
+    user_data: *mut c_void,
+}
+
+// `user_data` is opaque to us; the C side promises it may be used from any thread.
+unsafe impl Send for Sink {}
+unsafe impl Sync for Sink {}
+
+impl Sink {
+    fn call(&self, from: &str, to: &str, msg: &str) -> Result<(), ColleagueError> {
+        let nul = |_| ColleagueError::new("strings crossing the C API must not contain NUL");
+        let from = CString::new(from).map_err(nul)?;
+        let to = CString::new(to).map_err(nul)?;
+        let msg = CString::new(msg).map_err(nul)?;
+        (self.callback)(self.user_data, from.as_ptr(), to.as_ptr(), msg.as_ptr());
+        Ok(())
+    }
+}
+
+impl Colleague for FfiColleague {
+    type Identifier = String;
+    type Mediator = GenericMediator<FfiColleague>;
+    type Message = String;
+    type Request = ();
+    type Response = ();
+    
+    fn id(&self) -> &Self::Identifier {
+        &self.id
+    }
+    
+    fn belong_to(self, mediator: Self::Mediator) -> Managed<Self> {
+        Managed::new(self, mediator)
+    }
+    
+    fn read_msg(&self, msg: Self::Message) -> Result<(), ColleagueError> {
+        self.sink.call("", &self.id, &msg)
+    }
+    
+    fn read_with_context(&self, msg: Message<Self::Message>, ctx: ReplyContext<'_, Self>) -> Result<(), ColleagueError> {
+        self.sink.call(ctx.from(), &self.id, &msg.body)
+    }
+    
+    fn handle_request(&self, _: Self::Request) -> Self::Response {}
+}
+
+/// Creates a mediator reporting deliveries to `callback`. Free it with [`mediator_free`].
+#[no_mangle]
+pub extern "C" fn mediator_new(callback: MediatorCallback, user_data: *mut c_void) -> *mut FfiMediator {
+    let mediator = FfiMediator { inner: GenericMediator::default(), sink: Sink { callback, user_data } };
+    Box::into_raw(Box::new(mediator))
+}
+
+/// Registers a colleague under `id`.
+///
+/// # Safety
+///
+/// `mediator` must come from [`mediator_new`] and not be freed yet; `id` must be a
+/// NUL-terminated string or null.
+#[no_mangle]
+pub unsafe extern "C" fn mediator_register(mediator: *mut FfiMediator, id: *const c_char) -> c_int {
+    let (Some(mediator), Some(id)) = (mediator.as_mut(), to_str(id)) else {
+        return MEDIATOR_INVALID_ARGUMENT;
+    };
+    let colleague = FfiColleague { id: id.to_owned(), sink: mediator.sink };
+    let managed = colleague.belong_to(mediator.inner.clone());
+    status(mediator.inner.register(id.to_owned(), managed).map(drop))
+}
+
+/// Sends `msg` from `from` to `to`; the callback has run by the time this returns.
+///
+/// # Safety
+///
+/// `mediator` must come from [`mediator_new`] and not be freed yet; the strings must be
+/// NUL-terminated or null.
+#[no_mangle]
+pub unsafe extern "C" fn mediator_send(mediator: *const FfiMediator, from: *const c_char, to: *const c_char, msg: *const c_char) -> c_int {
+    let (Some(mediator), Some(from), Some(to), Some(msg)) = (mediator.as_ref(), to_str(from), to_str(to), to_str(msg)) else {
+        return MEDIATOR_INVALID_ARGUMENT;
+    };
+    let sent = mediator.inner.consultation_as(sealed::Token::new(), &from.to_owned(), &to.to_owned(), msg.to_owned())
+        .and_then(|outcome| outcome.ok_or_err());
+    status(sent.map(drop))
+}
+
+/// Frees a mediator along with its colleagues. Null is ignored.
+///
+/// # Safety
+///
+/// `mediator` must come from [`mediator_new`] and must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn mediator_free(mediator: *mut FfiMediator) {
+    if mediator.is_null() {
+        return;
+    }
+    let mediator = Box::from_raw(mediator);
+    let _ = mediator.inner.clear();
+}
+
+unsafe fn to_str<'a>(s: *const c_char) -> Option<&'a str> {
+    if s.is_null() {
+        return None;
+    }
+    CStr::from_ptr(s).to_str().ok()
+}
+
+fn status(result: Result<(), Error>) -> c_int {
+    match result {
+        Ok(()) => MEDIATOR_OK,
+        Err(Error::AlreadyRegistered { .. }) => MEDIATOR_ALREADY_REGISTERED,
+        Err(Error::NotRegistered { .. }) => MEDIATOR_NOT_REGISTERED,
+        Err(_) => MEDIATOR_FAILED,
+    }
+}
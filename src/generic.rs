@@ -0,0 +1,2480 @@
+use std::cell::Cell;
+use std::cmp::Reverse;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::{Debug, Formatter};
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::ops::ControlFlow;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "tokio")]
+use futures::stream::BoxStream;
+#[cfg(feature = "tokio")]
+use futures::{Stream, StreamExt};
+
+use crate::ack::{Ack, Acker};
+use crate::audit::{AckOutcome, AckRecord, AckStore};
+use crate::breaker::Breakers;
+use crate::clock::{Clock, Deadline, SystemClock};
+use crate::colleague::{Colleague, Delivery, Expiry, Managed, OnExpire, Registered, WeakRegistered};
+use crate::deadletter::{DeadLetter, DeadletterPolicy, DeadletterQueue, DeadletterReason, Offer, RetryPolicy, RetryReport};
+use crate::dedupe::Dedupe;
+use crate::error::{ColleagueError, Error, Operation, TryRegisterError};
+use crate::event::{Event, EventKind, EventLog};
+use crate::group::GroupId;
+use crate::inbox::{Inbox, Outlet};
+use crate::lock::{self, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use crate::mediator::{sealed, Mediator};
+use crate::message::{DeliveryOutcome, Message, MessageId, MessageSize, Priority, Readiness, RouteDecision, Tapped};
+use crate::metrics::{LatencyStats, Metrics, MetricsSnapshot};
+#[cfg(feature = "prometheus")]
+use crate::metrics::Collector;
+use crate::middleware::{Middleware, MiddlewareResult};
+use crate::observer::Observer;
+use crate::pattern::Pattern;
+use crate::pipeline::Pipeline;
+#[cfg(feature = "tokio")]
+use crate::pipeline::RemoteEnd;
+use crate::quota::Quotas;
+use crate::registry::Registry;
+use crate::reply::ReplyContext;
+use crate::reliable::InFlight;
+use crate::router::Router;
+use crate::schedule::Schedule;
+use crate::shutdown::ShutdownReport;
+use crate::snapshot::{MediatorState, RegistrySnapshot};
+#[cfg(feature = "tokio")]
+use crate::transport::{self, Decode, Encode, Remote, Transport};
+use crate::watch::{self, WatchHandle, Watchers};
+
+type WeakColleagues<C> = HashMap<<C as Colleague>::Identifier, WeakRegistered<C>>;
+type Filter<C> = Arc<dyn Fn(&<C as Colleague>::Message) -> bool + Send + Sync>;
+type Transform<C> = Arc<dyn Fn(<C as Colleague>::Message) -> <C as Colleague>::Message + Send + Sync>;
+type Stamper<C> = Arc<dyn Fn(&<C as Colleague>::Identifier, <C as Colleague>::Message) -> <C as Colleague>::Message + Send + Sync>;
+type Tap<C> = Arc<dyn Fn(&<C as Colleague>::Identifier, &<C as Colleague>::Identifier, &<C as Colleague>::Message, Tapped) + Send + Sync>;
+type Log<C> = EventLog<<C as Colleague>::Identifier, <C as Colleague>::Message>;
+type Events<C> = Vec<Event<<C as Colleague>::Identifier, <C as Colleague>::Message>>;
+type Sizer<C> = fn(&<C as Colleague>::Message) -> usize;
+type IdOrder<C> = fn(&<C as Colleague>::Identifier, &<C as Colleague>::Identifier) -> std::cmp::Ordering;
+type DeadLetters<C> = Vec<DeadLetter<<C as Colleague>::Identifier, <C as Colleague>::Message>>;
+type Parent<C> = Arc<dyn Mediator<C> + Send + Sync>;
+/// Finds the mediator on the far side of a [`Bridge`](crate::Bridge) that `to` lives on.
+pub(crate) type Link<C> = Arc<dyn Fn(&<C as Colleague>::Identifier) -> Option<Parent<C>> + Send + Sync>;
+type HashRegistry<C> = HashMap<<C as Colleague>::Identifier, Registered<C>>;
+/// Broadcast priority of every colleague and when it registered, to break ties.
+type Ranks<C> = HashMap<<C as Colleague>::Identifier, (i32, u64)>;
+
+thread_local! {
+    /// How many parent or bridged mediators the consultation running on this thread has been forwarded through.
+    static FORWARDED: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Per-call options of a consultation.
+struct Consult<C: Colleague> {
+    priority: Priority,
+    acker: Option<Acker>,
+    ttl: Option<(Duration, OnExpire<C>)>,
+    /// Given by the caller instead of taken from the sequence.
+    id: Option<MessageId>,
+    deadline: Option<Instant>,
+    /// Set for [`Mediator::try_consultation`]: nothing is deadlettered, forwarded, tapped or
+    /// told to the observer, but the consultation is otherwise checked and delivered as usual.
+    quiet: bool,
+}
+
+impl<C: Colleague> Default for Consult<C> {
+    fn default() -> Self {
+        Self { priority: Priority::Normal, acker: None, ttl: None, id: None, deadline: None, quiet: false }
+    }
+}
+
+/// Mediator over any colleague whose identifier can key a `HashMap`.
+///
+/// The registry itself is a `HashMap` unless `R` names another [`Registry`], e.g. the
+/// `BTreeMap` of an [`OrderedMediator`] for listing ids in order.
+///
+/// Clones share everything, and every change to the registry happens under its lock: once
+/// [`Mediator::register`] or [`Mediator::deregister`] returns, lookups through any clone, on any
+/// thread, see it. What hangs off a registration, such as its broadcast priority or last
+/// activity, is set right after the lock is released, so only the caller is sure to see those
+/// in place; other threads may briefly see the colleague without them.
+///
+/// A colleague whose handler panics while the mediator hands it something, be it a consultation,
+/// a broadcast, a deadletter or a request, is deregistered on the spot, as its state can't be
+/// trusted anymore; a consultation or request fails with [`Error::HandlerPanicked`], and the
+/// fan-outs pass it by.
+/// Handlers need not be `UnwindSafe` for that, but handles to the evicted colleague held
+/// elsewhere still see whatever state the panic left behind.
+pub struct GenericMediator<C: Colleague, R = HashRegistry<C>> {
+    colleagues: Arc<RwLock<R>>,
+    weak: Arc<RwLock<WeakColleagues<C>>>,
+    groups: Arc<RwLock<HashMap<GroupId, HashSet<C::Identifier>>>>,
+    tags: Arc<RwLock<HashMap<C::Identifier, HashSet<String>>>>,
+    roles: Arc<RwLock<HashMap<String, HashSet<C::Identifier>>>>,
+    fallback: Arc<RwLock<Option<C::Identifier>>>,
+    router: Arc<RwLock<Router<C>>>,
+    filters: Arc<RwLock<HashMap<C::Identifier, Filter<C>>>>,
+    transforms: Arc<RwLock<HashMap<C::Identifier, Transform<C>>>>,
+    taps: Arc<RwLock<Vec<Tap<C>>>>,
+    stamper: Arc<RwLock<Option<Stamper<C>>>>,
+    scheduled: Arc<Schedule<C::Identifier, C::Message>>,
+    activity: Arc<RwLock<HashMap<C::Identifier, Instant>>>,
+    ranks: Arc<RwLock<Ranks<C>>>,
+    shutdown_ranks: Arc<RwLock<HashMap<C::Identifier, i32>>>,
+    paused: Arc<RwLock<HashSet<C::Identifier>>>,
+    registrations: Arc<AtomicU64>,
+    watchers: Arc<RwLock<Watchers<C::Identifier, C::Message>>>,
+    pipeline: Arc<Pipeline<C, R>>,
+    patterns: Arc<RwLock<Vec<Pattern<C>>>>,
+    sequence: Arc<AtomicU64>,
+    shutting_down: Arc<AtomicBool>,
+    metrics: Arc<Metrics>,
+    events: Option<Arc<Log<C>>>,
+    sizer: Option<Sizer<C>>,
+    clock: Arc<dyn Clock>,
+    allow_self_send: bool,
+    id_order: Option<IdOrder<C>>,
+    observer: Option<Arc<dyn Observer<C>>>,
+    ack_store: Option<Arc<dyn AckStore<C::Identifier>>>,
+    middlewares: Vec<Arc<dyn Middleware<C>>>,
+    parent: Option<Parent<C>>,
+    bridge: Arc<RwLock<Option<Link<C>>>>,
+    forward_depth: usize,
+    dedupe: Arc<Dedupe>,
+}
+
+/// [`GenericMediator`] keeping its registry in a `BTreeMap`, so everything listing or walking
+/// the registered colleagues, from [`Mediator::registered_ids`] to broadcasts, goes in id order.
+pub type OrderedMediator<C> = GenericMediator<C, BTreeMap<<C as Colleague>::Identifier, Registered<C>>>;
+
+/// [`GenericMediator`] hashing ids with `S` rather than the std hasher, e.g. for a faster,
+/// non-cryptographic one when ids don't come from untrusted input.
+pub type HashedMediator<C, S> = GenericMediator<C, HashMap<<C as Colleague>::Identifier, Registered<C>, S>>;
+
+/// [`GenericMediator`] under the name of the map it keeps, for colleagues that need nothing more
+/// than a `type MyMediator = MapMediator<MyColleague>` instead of a mediator of their own.
+pub type MapMediator<C> = GenericMediator<C>;
+
+impl<C: Colleague, R: Registry<C::Identifier, Registered<C>>> GenericMediator<C, R>
+    where C::Identifier: Eq + Hash + Clone + Debug
+{
+    pub fn builder() -> GenericMediatorBuilder<C, R> {
+        GenericMediatorBuilder::default()
+    }
+    
+    /// Creates a mediator whose registry holds `capacity` colleagues before it reallocates,
+    /// short for the [builder](GenericMediatorBuilder::capacity) when nothing else needs setting.
+    pub fn with_capacity(capacity: usize) -> Self {
+        GenericMediatorBuilder::default().capacity(capacity).build()
+    }
+    
+    /// Creates a mediator that keeps undeliverable messages in its deadletter queue
+    /// instead of failing with [`Error::NotRegistered`].
+    pub fn with_deadletter() -> Self {
+        GenericMediatorBuilder::default().deadletter(true).build()
+    }
+    
+    /// Lets colleagues consult themselves; by default that is rejected with [`Error::SelfSend`].
+    pub fn allowing_self_send(self) -> Self {
+        Self { allow_self_send: true, ..self }
+    }
+    
+    pub fn observed_by(self, observer: impl Observer<C> + 'static) -> Self {
+        Self { observer: Some(Arc::new(observer)), ..self }
+    }
+    
+    /// Appends a middleware; middlewares run in the order they were added.
+    pub fn with_middleware(mut self, middleware: impl Middleware<C> + 'static) -> Self {
+        self.middlewares.push(Arc::new(middleware));
+        self
+    }
+    
+    pub fn deadletters(&self) -> Result<DeadLetters<C>, Error>
+        where C::Message: Clone
+    {
+        self.pipeline.deadletters.letters(Operation::Deadletters)
+    }
+    
+    pub fn drain_deadletters(&self) -> Result<DeadLetters<C>, Error> {
+        self.pipeline.deadletters.drain(Operation::Deadletters)
+    }
+    
+    /// Tries to deliver the queued deadletters again, following the
+    /// [retry policy](GenericMediatorBuilder::retry_policy): every round goes through the
+    /// letters still left, pausing for the backoff in between. Whatever is still undeliverable
+    /// afterwards goes back to the front of the queue.
+    pub fn retry_deadletters(&self) -> Result<RetryReport, Error> {
+        self.ensure_running()?;
+        
+        let retry = self.pipeline.deadletters.retry_policy();
+        let mut letters = self.pipeline.deadletters.drain(Operation::RetryDeadletters)?;
+        let mut report = RetryReport::default();
+        for attempt in 0..retry.max_attempts.max(1) {
+            if letters.is_empty() {
+                break;
+            }
+            if attempt > 0 {
+                self.clock.sleep(retry.backoff);
+            }
+            
+            let mut dead = Vec::new();
+            for letter in letters {
+                let Some(colleague) = self.lookup(&letter.from, &letter.to).filter(|_| !self.is_paused(&letter.to)) else {
+                    dead.push(letter);
+                    continue;
+                };
+                if !self.interested(&letter.to, &letter.msg) {
+                    report.filtered += 1;
+                    continue;
+                }
+                match self.hand_over(&letter.from, &letter.to, &colleague, Message::new(letter.msg), Delivery::default(), false) {
+                    Ok(Ok(true)) => report.delivered += 1,
+                    // A full mailbox drops the message just like a refusing or panicking `read_msg`.
+                    Ok(Ok(false) | Err(_)) | Err(_) => report.rejected += 1,
+                }
+            }
+            letters = dead;
+        }
+        
+        report.dead = letters.len();
+        self.pipeline.deadletters.requeue(letters, &mut report)?;
+        Ok(report)
+    }
+    
+    /// Stops delivering to `id` for now without deregistering it, e.g. while a user is away.
+    /// Consultations to a paused colleague still queue up in its mailbox, if it has one;
+    /// otherwise they go to the deadletter queue, even if the mediator doesn't deadletter
+    /// messages otherwise, and broadcasts pass it by. Fails with [`Error::NotRegistered`] if
+    /// `id` isn't registered.
+    pub fn pause(&self, id: &C::Identifier) -> Result<(), Error> {
+        if self.lookup_exact(id).is_none() {
+            return Err(Error::NotRegistered { id: format!("{:?}", id) });
+        }
+        lock::write(&self.paused, Operation::Pause)?
+            .insert(id.clone());
+        Ok(())
+    }
+    
+    /// Delivers to `id` again after [`GenericMediator::pause`]. With `flush`, the deadletters
+    /// addressed to `id` are handed to it right away, oldest first, and how many of them it
+    /// took is returned; the ones it doesn't take are dropped.
+    pub fn resume(&self, id: &C::Identifier, flush: bool) -> Result<usize, Error> {
+        lock::write(&self.paused, Operation::Pause)?
+            .remove(id);
+        if !flush {
+            return Ok(0);
+        }
+        let Some(colleague) = self.lookup_exact(id) else {
+            return Ok(0);
+        };
+        
+        let flushed = self.pipeline.deadletters.drain_for(id, Operation::Deadletters)?
+            .into_iter()
+            .map(|DeadLetter { from, msg, .. }| self.hand_over(&from, id, &colleague, Message::new(msg), Delivery::default(), false))
+            .filter(|handed| matches!(handed, Ok(Ok(true))))
+            .count();
+        Ok(flushed)
+    }
+    
+    /// Clones out the handles of every registered colleague matching `pred`.
+    pub fn find<F>(&self, pred: F) -> Result<Vec<Registered<C>>, Error>
+        where F: Fn(&C) -> bool
+    {
+        let found = self.read_colleagues()
+            .values()
+            .filter(|colleague| pred(colleague))
+            .cloned()
+            .collect();
+        Ok(found)
+    }
+    
+    /// Runs `f` on the registry while holding its read lock, for compound reads the rest of the
+    /// API doesn't cover. `f` must not go through this mediator itself, as that may deadlock.
+    pub fn with_registry<T>(&self, f: impl FnOnce(&R) -> T) -> Result<T, Error> {
+        Ok(f(&self.read_colleagues()))
+    }
+    
+    /// Calls `f` with every id and colleague in turn, without collecting them like
+    /// [`Mediator::entries`] does, until it breaks, and tells whether it did. `f` runs while the
+    /// read lock is held, so it must not go through this mediator itself.
+    pub fn for_each_entry(&self, mut f: impl FnMut(&C::Identifier, &Registered<C>) -> ControlFlow<()>) -> Result<ControlFlow<()>, Error> {
+        Ok(self.read_colleagues().iter().try_for_each(|(id, colleague)| f(id, colleague)))
+    }
+    
+    /// Like [`GenericMediator::with_registry`] but under the write lock, so `f` may change the
+    /// registry in place. That bypasses the observer, the event log and the bookkeeping of tags,
+    /// roles and groups.
+    pub fn with_registry_mut<T>(&self, f: impl FnOnce(&mut R) -> T) -> Result<T, Error> {
+        Ok(f(&mut self.write_colleagues()))
+    }
+    
+    /// How many colleagues the registry can hold without reallocating.
+    pub fn capacity(&self) -> Result<usize, Error> {
+        Ok(self.read_colleagues().capacity())
+    }
+    
+    /// Makes room for `additional` more colleagues in one go, and for as many deadletters if
+    /// the queue is enabled, e.g. ahead of a bulk registration, so neither grows piecemeal.
+    ///
+    /// The deadletter queue is only reserved when the mediator was built with
+    /// [`deadletter`](GenericMediatorBuilder::deadletter) enabled, since nothing is ever queued
+    /// otherwise, and never past its [`deadletter_capacity`](GenericMediatorBuilder::deadletter_capacity).
+    pub fn reserve(&self, additional: usize) -> Result<(), Error> {
+        self.write_colleagues()
+            .reserve(additional);
+        self.pipeline.deadletters.reserve(additional)
+    }
+    
+    /// Gives back memory the registry grew into, e.g. after a burst of registrations.
+    pub fn shrink_to_fit(&self) -> Result<(), Error> {
+        self.write_colleagues()
+            .shrink_to_fit();
+        Ok(())
+    }
+    
+    pub fn export_ids(&self) -> Vec<C::Identifier> {
+        self.read_colleagues()
+            .keys()
+            .cloned()
+            .collect()
+    }
+    
+    /// Registers every item under a single write lock, returning the handles in order.
+    /// All or nothing: if any id is already registered or appears twice in `items`, nothing
+    /// is registered and the error names the first conflicting id.
+    pub fn register_batch(&self, items: Vec<(C::Identifier, Managed<C>)>) -> Result<Vec<Registered<C>>, Error> {
+        let mut colleagues = self.write_colleagues();
+        self.ensure_running()?;
+        
+        let mut seen = HashSet::with_capacity(items.len());
+        if let Some((id, _)) = items.iter().find(|(id, _)| colleagues.contains_key(id) || !seen.insert(id)) {
+            return Err(Error::AlreadyRegistered { id: format!("{:?}", id) });
+        }
+        
+        let mut ids = Vec::with_capacity(items.len());
+        let registered = items.into_iter()
+            .map(|(id, managed)| {
+                let reg = Registered::new(managed);
+                self.record(|_| EventKind::Registered { id: id.clone(), weak: false });
+                colleagues.insert(id.clone(), reg.clone());
+                ids.push(id);
+                reg
+            })
+            .collect();
+        drop(colleagues);
+        
+        ids.iter().for_each(|id| self.joined(id));
+        Ok(registered)
+    }
+    
+    /// Registers `id` even if it's taken, e.g. for a user reconnecting from a new session.
+    /// Like [`Mediator::re_register`], but a displaced colleague is reported to the observer
+    /// through [`Observer::on_superseded`] and to the event log.
+    pub fn takeover(&self, id: C::Identifier, registered: Managed<C>) -> Result<(Registered<C>, Option<Registered<C>>), Error> {
+        let mut colleagues = self.write_colleagues();
+        self.ensure_running()?;
+        
+        let reg = Registered::new(registered);
+        self.record(|_| EventKind::Registered { id: id.clone(), weak: false });
+        let previous = colleagues.insert(id.clone(), reg.clone());
+        drop(colleagues);
+        
+        self.joined(&id);
+        if let Some(old) = &previous {
+            self.record(|_| EventKind::Superseded { id: id.clone() });
+            if let Some(observer) = &self.observer {
+                observer.on_superseded(&id, old);
+            }
+        }
+        Ok((reg, previous))
+    }
+    
+    /// Registers `id` for pull-style consumption: consultations are sent down the returned
+    /// channel rather than handed to `read_msg`, so the colleague can run its own loop on
+    /// [`Receiver::recv`] or [`Receiver::recv_timeout`]. Once the receiver is dropped,
+    /// consultations fail with [`Error::DeliveryRejected`].
+    pub fn register_channel(&self, id: C::Identifier, registered: Managed<C>) -> Result<(Registered<C>, Receiver<C::Message>), Error>
+        where C::Request: Debug
+    {
+        let (tx, rx) = mpsc::channel();
+        let reg = self.register(id, registered.with_outlet(Outlet::Channel(tx)))?;
+        Ok((reg, rx))
+    }
+    
+    /// Registers `id` for async consumption: consultations are queued for the returned
+    /// stream rather than handed to `read_msg`, so the colleague can drive it with
+    /// `StreamExt` combinators. The stream ends once every handle of the registration is
+    /// gone; once it is dropped, consultations fail with [`Error::DeliveryRejected`].
+    #[cfg(feature = "tokio")]
+    pub fn register_stream(&self, id: C::Identifier, registered: Managed<C>) -> Result<(Registered<C>, impl Stream<Item = C::Message>), Error>
+        where C::Request: Debug
+    {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let reg = self.register(id, registered.with_outlet(Outlet::Stream(tx)))?;
+        Ok((reg, futures::stream::poll_fn(move |cx| rx.poll_recv(cx))))
+    }
+    
+    /// Like [`GenericMediator::register_stream`], but drives the stream itself: a task spawned
+    /// onto the current tokio runtime hands each message to [`Colleague::read_msg`], where
+    /// rejections go unnoticed, as nobody waits for them. The returned join handle completes
+    /// once the stream ends, i.e. once the colleague is deregistered and its handles are dropped;
+    /// whatever it hadn't read by then is dropped along with it. Reading happens on the task,
+    /// out of the mediator's hands, so a panicking handler ends the task, failing its join
+    /// handle, rather than getting the colleague evicted. Panics outside a tokio runtime,
+    /// like `tokio::spawn`.
+    #[cfg(feature = "tokio")]
+    pub fn spawn_colleague(&self, id: C::Identifier, registered: Managed<C>) -> Result<(Registered<C>, tokio::task::JoinHandle<()>), Error>
+        where C: 'static,
+              C::Message: Send + 'static,
+              C::Request: Debug,
+              R: 'static,
+              WeakRegistered<C>: Send
+    {
+        let (reg, mut stream) = self.register_stream(id, registered)?;
+        // The task mustn't keep the registration, and with it the stream, alive itself.
+        let colleague = reg.downgrade();
+        let task = tokio::spawn(async move {
+            while let Some(msg) = stream.next().await {
+                let Some(colleague) = colleague.upgrade() else {
+                    break;
+                };
+                let _ = colleague.read_msg(msg);
+            }
+        });
+        Ok((reg, task))
+    }
+    
+    /// Registers `id` to be fed through the returned [`Inbox`]: consultations from any thread
+    /// just push into it, and the colleague consumes it on a loop of its own, e.g. on a
+    /// dedicated thread. Once the inbox is dropped, consultations fail with
+    /// [`Error::DeliveryRejected`].
+    pub fn register_inbox(&self, id: C::Identifier, registered: Managed<C>) -> Result<(Registered<C>, Inbox<C::Message>), Error>
+        where C::Request: Debug
+    {
+        self.register_inbox_with(id, registered, None)
+    }
+    
+    /// Like [`GenericMediator::register_inbox`], but holds at most `capacity` unread messages:
+    /// consultations beyond that fail with [`Error::MailboxFull`] until the colleague catches up.
+    pub fn register_bounded_inbox(&self, id: C::Identifier, registered: Managed<C>, capacity: usize) -> Result<(Registered<C>, Inbox<C::Message>), Error>
+        where C::Request: Debug
+    {
+        self.register_inbox_with(id, registered, Some(capacity))
+    }
+    
+    /// Registers `id` with tags to be found through [`GenericMediator::by_tag`]. Unlike group
+    /// membership, tags describe the colleague itself: they are only given at registration,
+    /// follow the colleague through [`Mediator::rename`] and go away once it is deregistered.
+    pub fn register_tagged(&self, id: C::Identifier, registered: Managed<C>, tags: HashSet<String>) -> Result<Registered<C>, Error>
+        where C::Request: Debug
+    {
+        let reg = self.register(id.clone(), registered)?;
+        lock::write(&self.tags, Operation::Register)?
+            .insert(id, tags);
+        Ok(reg)
+    }
+    
+    /// Registers the colleague under its own id as one of the workers sharing `role`, see
+    /// [`GenericMediator::consult_by_role`]. Deregistering it takes it out of the role again.
+    pub fn register_worker(&self, role: impl Into<String>, registered: Managed<C>) -> Result<Registered<C>, Error>
+        where C::Request: Debug
+    {
+        let id = registered.id().clone();
+        let reg = self.register(id.clone(), registered)?;
+        lock::write(&self.roles, Operation::Register)?
+            .entry(role.into())
+            .or_default()
+            .insert(id);
+        Ok(reg)
+    }
+    
+    /// Consults one of the workers sharing `role`, the same one for the same `key` for as long
+    /// as the role's workers stay the same. Workers are picked by rendezvous hashing, so a
+    /// worker joining or leaving only moves the keys it takes over or gives up. Fails with
+    /// [`Error::NoWorkers`] if nobody is registered for `role`.
+    ///
+    /// The picks are stable within a process, not across builds of the standard library.
+    pub fn consult_by_role<K>(&self, from: &Registered<C>, role: &str, key: &K, msg: C::Message) -> Result<DeliveryOutcome, Error>
+        where K: Hash + ?Sized
+    {
+        let worker = lock::read(&self.roles, Operation::Roles)?
+            .get(role)
+            .and_then(|workers| workers.iter().max_by_key(|worker| rendezvous(key, worker)))
+            .cloned()
+            .ok_or_else(|| Error::NoWorkers { role: role.to_string() })?;
+        self.consult(from.id(), &worker, Message::new(msg), Consult::default())
+    }
+    
+    /// Asks every worker sharing `role` but `from` with its own clone of `req`, see
+    /// [`GenericMediator::register_worker`], and collects their responses in no particular
+    /// order. A role nobody is registered for gets no responses rather than an error.
+    pub fn scatter_gather(&self, from: &Registered<C>, role: &str, req: C::Request) -> Result<Vec<C::Response>, Error>
+        where C::Request: Clone
+    {
+        self.ensure_running()?;
+        let workers: Vec<_> = lock::read(&self.roles, Operation::Roles)?
+            .get(role)
+            .into_iter()
+            .flatten()
+            .filter(|worker| worker.ne(&from.id()))
+            .cloned()
+            .collect();
+        let responses = workers.iter()
+            .filter_map(|id| self.lookup_exact(id).map(|worker| (id, worker)))
+            .filter_map(|(id, worker)| self.guarded(id, &worker, || Ok(worker.handle_request(req.clone()))).ok()?.ok())
+            .collect();
+        Ok(responses)
+    }
+    
+    /// Tells where a consultation to `to` would go, resolving it exactly like a consultation
+    /// from anyone but the fallback does, but without delivering anything. Fails with [`Error::NotRegistered`] where a
+    /// consultation would. Filters aren't consulted, as they need a message to judge.
+    pub fn route_preview(&self, to: &C::Identifier) -> Result<RouteDecision, Error> {
+        match self.resolve(None, to) {
+            Some((_, route)) => Ok(route),
+            None if self.bridged(to).is_some() => Ok(RouteDecision::Bridge),
+            #[cfg(feature = "tokio")]
+            None if self.pipeline.remote.is_some() => Ok(RouteDecision::Remote),
+            None if self.parent.is_some() => Ok(RouteDecision::Parent),
+            None if self.pipeline.deadletters.is_enabled() => Ok(RouteDecision::Deadletter),
+            None => Err(Error::NotRegistered { id: format!("{:?}", to) }),
+        }
+    }
+    
+    /// Tells whether `to` could take another message right now, before going to the trouble of
+    /// building it. Resolves `to` like [`GenericMediator::route_preview`], but ids only a
+    /// [parent](GenericMediatorBuilder::parent) could resolve count as [`Readiness::Absent`].
+    /// The answer may be outdated by the time the message is sent, so sending can still fail.
+    pub fn poll_ready(&self, to: &C::Identifier) -> Result<Readiness, Error> {
+        self.ensure_running()?;
+        let readiness = self.resolve(None, to)
+            .map_or(Readiness::Absent, |(colleague, _)| colleague.readiness());
+        Ok(readiness)
+    }
+    
+    /// How many messages wait in `id`'s mailbox, see [`Registered::mailbox_depth`]; `None` if
+    /// `id` isn't registered or reads its messages right away.
+    pub fn mailbox_depth(&self, id: &C::Identifier) -> Result<Option<usize>, Error> {
+        Ok(self.lookup_exact(id).and_then(|colleague| colleague.mailbox_depth()))
+    }
+    
+    /// The [mailbox depth](GenericMediator::mailbox_depth) of every registered colleague that
+    /// has a mailbox.
+    pub fn mailbox_depths(&self) -> Result<HashMap<C::Identifier, usize>, Error> {
+        let depths = self.read_colleagues()
+            .iter()
+            .filter_map(|(id, colleague)| colleague.mailbox_depth().map(|depth| (id.clone(), depth)))
+            .collect();
+        Ok(depths)
+    }
+    
+    /// Clones out the handles of every registered colleague tagged with `tag`.
+    pub fn by_tag(&self, tag: &str) -> Result<Vec<Registered<C>>, Error> {
+        let tags = lock::read(&self.tags, Operation::Tags)?;
+        let colleagues = self.read_colleagues();
+        let found = tags.iter()
+            .filter(|(_, tags)| tags.contains(tag))
+            .filter_map(|(id, _)| colleagues.get(id).cloned())
+            .collect();
+        Ok(found)
+    }
+    
+    /// Registers like [`Mediator::register`], with `priority` deciding how early broadcasts reach
+    /// the colleague: higher priorities first, and the same priority in the order colleagues
+    /// registered. Colleagues registered otherwise have priority 0.
+    pub fn register_with_priority(&self, id: C::Identifier, registered: Managed<C>, priority: i32) -> Result<Registered<C>, Error>
+        where C::Request: Debug
+    {
+        let reg = self.register(id.clone(), registered)?;
+        if let Some((rank, _)) = lock::recover_write(&self.ranks).get_mut(&id) {
+            *rank = priority;
+        }
+        Ok(reg)
+    }
+    
+    /// Like [`Mediator::register`] but fails with [`TryRegisterError::WouldBlock`] instead of
+    /// waiting while someone else holds the registry lock.
+    pub fn try_register(&self, id: C::Identifier, registered: Managed<C>) -> Result<Registered<C>, TryRegisterError> {
+        let colleagues = lock::try_write(&self.colleagues).ok_or(TryRegisterError::WouldBlock)?;
+        Ok(self.insert_new(colleagues, id, registered)?)
+    }
+    
+    /// Registers like [`Mediator::register`], with `priority` deciding how early
+    /// [`GenericMediator::shutdown`] stops the colleague: higher priorities first, e.g. producers
+    /// before the consumers they feed. Colleagues registered otherwise have priority 0.
+    pub fn register_with_shutdown_priority(&self, id: C::Identifier, registered: Managed<C>, priority: i32) -> Result<Registered<C>, Error>
+        where C::Request: Debug
+    {
+        let reg = self.register(id.clone(), registered)?;
+        lock::recover_write(&self.shutdown_ranks)
+            .insert(id, priority);
+        Ok(reg)
+    }
+    
+    /// Registers `id` without the mediator keeping the colleague alive: once the returned handle
+    /// and its clones are dropped, consultations treat `id` as unregistered and the entry is
+    /// pruned. Weak registrations are found by consultations, requests and
+    /// [`is_registered`](Mediator::is_registered), but not counted, listed or broadcast to.
+    pub fn register_weak(&self, id: C::Identifier, registered: Managed<C>) -> Result<Registered<C>, Error> {
+        let colleagues = self.write_colleagues();
+        self.ensure_running()?;
+        
+        let mut weak = lock::recover_write(&self.weak);
+        if colleagues.contains_key(&id) || weak.get(&id).is_some_and(|entry| entry.strong_count() > 0) {
+            return Err(Error::AlreadyRegistered { id: format!("{:?}", id) });
+        }
+        let reg = Registered::new(registered);
+        self.record(|_| EventKind::Registered { id: id.clone(), weak: true });
+        weak.insert(id.clone(), reg.downgrade());
+        drop(weak);
+        drop(colleagues);
+        
+        self.joined(&id);
+        Ok(reg)
+    }
+    
+    /// Registers a colleague for every id matching the glob `pattern`, where `*` matches any
+    /// run of characters and `?` a single one, e.g. `"user-*"`. Consultations only fall back to
+    /// patterns when no colleague is registered under the exact id, trying them in the order
+    /// they were registered. Pattern registrations don't count as registered ids.
+    pub fn register_pattern(&self, pattern: String, registered: Managed<C>) -> Result<Registered<C>, Error>
+        where C::Identifier: AsRef<str>
+    {
+        let mut patterns = lock::write(&self.patterns, Operation::Register)?;
+        self.ensure_running()?;
+        
+        if patterns.iter().any(|existing| existing.glob() == pattern) {
+            return Err(Error::AlreadyRegistered { id: pattern });
+        }
+        let reg = Registered::new(registered);
+        patterns.push(Pattern::new(pattern, AsRef::as_ref, reg.clone()));
+        Ok(reg)
+    }
+    
+    /// Everything recorded so far, oldest first; empty unless enabled with
+    /// [`GenericMediatorBuilder::event_log`].
+    pub fn event_log(&self) -> Result<Events<C>, Error>
+        where C::Message: Clone
+    {
+        Ok(self.events.as_ref().map(|events| events.events()).unwrap_or_default())
+    }
+    
+    /// The event log as a Graphviz DOT graph of who messaged whom and how often, see
+    /// [`GenericMediatorBuilder::event_log`]; a graph without nodes unless that is enabled.
+    pub fn to_dot(&self) -> Result<String, Error> {
+        Ok(self.events.as_ref()
+            .map(|events| events.to_dot())
+            .unwrap_or_else(|| String::from("digraph mediator {\n}\n")))
+    }
+    
+    /// How long `id` took over the consultations delivered to it since it registered, as measured
+    /// by the mediator's [`Clock`]: its `read_msg` for colleagues reading right away, only the
+    /// queueing for those with a mailbox. `None` before the first one reached it.
+    pub fn latency_stats(&self, id: &C::Identifier) -> Option<LatencyStats> {
+        self.pipeline.latency_stats(id)
+    }
+    
+    pub fn metrics(&self) -> MetricsSnapshot {
+        self.metrics.snapshot()
+    }
+    
+    /// Registers `messages_sent_total`, `messages_delivered_total`, `deadletters_total` and
+    /// `registered_colleagues` with `registry`, read from the mediator on every scrape. The
+    /// registry doesn't keep the colleagues alive: once the mediator is gone, nobody is registered.
+    /// Fails like [`prometheus::Registry::register`], e.g. if a mediator is registered there already.
+    #[cfg(feature = "prometheus")]
+    pub fn register_metrics(&self, registry: &prometheus::Registry) -> prometheus::Result<()>
+        where R: Registry<C::Identifier, Registered<C>> + Send + Sync + 'static
+    {
+        let colleagues = Arc::downgrade(&self.colleagues);
+        let collector = Collector::new(Arc::clone(&self.metrics), move || {
+            colleagues.upgrade()
+                .map_or(0, |colleagues| lock::recover_read(&colleagues).len())
+        })?;
+        registry.register(Box::new(collector))
+    }
+    
+    /// Shorthand for [`MetricsSnapshot::bytes_delivered`].
+    pub fn bytes_delivered(&self) -> u64 {
+        self.metrics.snapshot().bytes_delivered
+    }
+    
+    pub fn snapshot(&self) -> Result<RegistrySnapshot<C::Identifier>, Error> {
+        let ids = self.read_colleagues()
+            .keys()
+            .cloned()
+            .collect();
+        let groups = lock::read(&self.groups, Operation::Snapshot)?
+            .iter()
+            .map(|(group, members)| (group.clone(), members.iter().cloned().collect()))
+            .collect();
+        let deadletters = self.pipeline.deadletters.len(Operation::Snapshot)?;
+        
+        Ok(RegistrySnapshot {
+            ids,
+            groups,
+            deadletters,
+            shutting_down: self.shutting_down.load(Ordering::SeqCst),
+        })
+    }
+    
+    /// Copies out everything [`MediatorState`] preserves, to bring a fresh mediator up to the
+    /// same point with [`GenericMediator::import_state`].
+    pub fn export_state(&self) -> Result<MediatorState<C::Identifier, C::Message>, Error>
+        where C::Message: Clone
+    {
+        let ids = self.read_colleagues()
+            .keys()
+            .cloned()
+            .collect();
+        let groups = lock::read(&self.groups, Operation::Snapshot)?
+            .iter()
+            .map(|(group, members)| (group.clone(), members.iter().cloned().collect()))
+            .collect();
+        let tags = lock::read(&self.tags, Operation::Snapshot)?
+            .iter()
+            .map(|(id, tags)| (id.clone(), tags.iter().cloned().collect()))
+            .collect();
+        let roles = lock::read(&self.roles, Operation::Snapshot)?
+            .iter()
+            .map(|(role, workers)| (role.clone(), workers.iter().cloned().collect()))
+            .collect();
+        let fallback = lock::read(&self.fallback, Operation::Snapshot)?
+            .clone();
+        let deadletters = self.pipeline.deadletters.letters(Operation::Snapshot)?;
+        
+        Ok(MediatorState {
+            ids,
+            groups,
+            tags,
+            roles,
+            fallback,
+            deadletters,
+            next_message_id: self.sequence.load(Ordering::Relaxed),
+        })
+    }
+    
+    /// Replaces this mediator's groups, tags, roles, fallback and deadletters with those of
+    /// `state`. The ids are left alone: register the colleagues under `state.ids` first, as
+    /// this doesn't register anyone. Message ids continue from the exported point unless this
+    /// mediator is past it already.
+    pub fn import_state(&self, state: MediatorState<C::Identifier, C::Message>) -> Result<(), Error> {
+        let MediatorState { ids: _, groups, tags, roles, fallback, deadletters, next_message_id } = state;
+        *lock::write(&self.groups, Operation::Group)? = groups.into_iter()
+            .map(|(group, members)| (group, members.into_iter().collect()))
+            .collect();
+        *lock::write(&self.tags, Operation::Tags)? = tags.into_iter()
+            .map(|(id, tags)| (id, tags.into_iter().collect()))
+            .collect();
+        *lock::write(&self.roles, Operation::Roles)? = roles.into_iter()
+            .map(|(role, workers)| (role, workers.into_iter().collect()))
+            .collect();
+        *lock::write(&self.fallback, Operation::Fallback)? = fallback;
+        self.pipeline.deadletters.replace(deadletters, Operation::Deadletters)?;
+        self.sequence.fetch_max(next_message_id, Ordering::Relaxed);
+        Ok(())
+    }
+    
+    /// Stops the mediator for good: further registrations and consultations fail with
+    /// [`Error::ShuttingDown`] and the registry is cleared. Messages still sitting in mailboxes
+    /// are not read anymore but handed to [`Observer::on_drop_message`], so they can be
+    /// persisted or rerouted rather than lost silently; with the deadletter queue enabled, they
+    /// are deadlettered under new ids instead.
+    ///
+    /// Colleagues are told through [`Colleague::on_shutdown`] and deregistered one by one, the
+    /// highest [shutdown priority](GenericMediator::register_with_shutdown_priority) first and
+    /// the same priority in the order they registered.
+    pub fn shutdown(&self) -> Result<ShutdownReport, Error> {
+        self.shutting_down.store(true, Ordering::SeqCst);
+        
+        lock::recover_write(&self.weak).clear();
+        lock::recover_write(&self.patterns).clear();
+        lock::recover_write(&self.tags).clear();
+        lock::recover_write(&self.roles).clear();
+        let mut colleagues: Vec<_> = std::mem::take(&mut *self.write_colleagues())
+            .into_iter()
+            .collect();
+        {
+            let ranks = lock::recover_read(&self.ranks);
+            let shutdown_ranks = lock::recover_read(&self.shutdown_ranks);
+            colleagues.sort_by_key(|(id, _)| {
+                let registered = ranks.get(id).map_or(u64::MAX, |&(_, registered)| registered);
+                (Reverse(shutdown_ranks.get(id).copied().unwrap_or(0)), registered)
+            });
+        }
+        let mut report = ShutdownReport::default();
+        for (id, colleague) in colleagues {
+            colleague.on_shutdown();
+            report.flushed += colleague.discard(|from, msg| {
+                if self.pipeline.deadletters.is_enabled() {
+                    let _ = self.deadlettered(&from, &id, MessageId::next(&self.sequence), msg, DeadletterReason::ShuttingDown);
+                } else if let Some(observer) = &self.observer {
+                    observer.on_drop_message(&from, &id, msg);
+                }
+            });
+            self.left(&id);
+            self.record(|_| EventKind::Deregistered { id });
+            report.deregistered += 1;
+        }
+        Ok(report)
+    }
+    
+    /// Only lets messages matching `filter` through to `id`; the rest are dropped as not
+    /// interesting, counted in [`MetricsSnapshot::filtered`], and only kept in the deadletter
+    /// queue, if enabled, for the record.
+    /// The filter belongs to the id, so it also applies to later registrations under it.
+    pub fn set_filter(&self, id: C::Identifier, filter: impl Fn(&C::Message) -> bool + Send + Sync + 'static) -> Result<(), Error> {
+        lock::write(&self.filters, Operation::Filter)?
+            .insert(id, Arc::new(filter));
+        Ok(())
+    }
+    
+    pub fn clear_filter(&self, id: &C::Identifier) -> Result<(), Error> {
+        lock::write(&self.filters, Operation::Filter)?
+            .remove(id);
+        Ok(())
+    }
+    
+    /// Rewrites every message for `id` with `transform` right before it's handed over, e.g. to
+    /// localize it for that colleague. Middlewares and filters still see the message as sent;
+    /// the recipient, its mailbox and the observer see it rewritten. Like a filter, the
+    /// transform belongs to the id.
+    pub fn set_transform(&self, id: C::Identifier, transform: impl Fn(C::Message) -> C::Message + Send + Sync + 'static) -> Result<(), Error> {
+        lock::write(&self.transforms, Operation::Transform)?
+            .insert(id, Arc::new(transform));
+        Ok(())
+    }
+    
+    pub fn clear_transform(&self, id: &C::Identifier) -> Result<(), Error> {
+        lock::write(&self.transforms, Operation::Transform)?
+            .remove(id);
+        Ok(())
+    }
+    
+    /// Shows `tap` every consultation on its way to a colleague or the deadletter queue, as
+    /// the middlewares left it, e.g. to record the traffic in an integration test. Unlike a
+    /// middleware, a tap can neither change nor stop what it sees. Taps stay installed for
+    /// good; consultations forwarded to a parent are for the parent's taps to see.
+    pub fn tap(&self, tap: impl Fn(&C::Identifier, &C::Identifier, &C::Message, Tapped) + Send + Sync + 'static) -> Result<(), Error> {
+        lock::write(&self.taps, Operation::Tap)?
+            .push(Arc::new(tap));
+        Ok(())
+    }
+    
+    /// Calls `f` with every message delivered to `target` from now on, for as long as the
+    /// returned handle is kept. Unlike an [`Observer`], which sees everyone's deliveries, a
+    /// watch is about a single colleague, and any number of them can be installed.
+    pub fn watch(&self, target: &C::Identifier, f: impl Fn(&C::Message) + Send + Sync + 'static) -> Result<WatchHandle, Error>
+        where C::Identifier: Send + Sync + 'static,
+              C::Message: 'static
+    {
+        watch::watch(&self.watchers, target.clone(), Arc::new(f))
+    }
+    
+    /// Lets `id` send at most `max_messages_per_window` consultations until the next
+    /// [`reset_quotas`](Self::reset_quotas) or the end of the
+    /// [quota window](GenericMediatorBuilder::quota_window); past that, its consultations fail
+    /// with [`Error::QuotaExceeded`]. Broadcasts and multicasts don't count against the quota.
+    pub fn set_quota(&self, id: &C::Identifier, max_messages_per_window: u32) -> Result<(), Error> {
+        self.pipeline.quotas.set(id.clone(), max_messages_per_window, self.clock.now())
+    }
+    
+    pub fn clear_quota(&self, id: &C::Identifier) -> Result<(), Error> {
+        self.pipeline.quotas.clear(id)
+    }
+    
+    /// Hands whatever would otherwise be deadlettered or fail with [`Error::NotRegistered`] to
+    /// the colleague registered under `id`, e.g. a catch-all support agent. While `id` isn't
+    /// registered either, nothing changes. Messages the fallback sends itself never come back to it.
+    pub fn set_fallback(&self, id: C::Identifier) -> Result<(), Error> {
+        *lock::write(&self.fallback, Operation::Fallback)? = Some(id);
+        Ok(())
+    }
+    
+    pub fn clear_fallback(&self) -> Result<(), Error> {
+        *lock::write(&self.fallback, Operation::Fallback)? = None;
+        Ok(())
+    }
+    
+    /// Replaces the rules [`GenericMediator::consult_routed`] goes by.
+    pub fn set_router(&self, router: Router<C>) -> Result<(), Error> {
+        *lock::write(&self.router, Operation::Router)? = router;
+        Ok(())
+    }
+    
+    /// Consults whoever the first rule of the [router](GenericMediator::set_router) matching
+    /// `msg` names, like [`Mediator::consultation`] would, so an unregistered target is
+    /// deadlettered as usual. A message no rule matches goes to the
+    /// [fallback](GenericMediator::set_fallback) if there is one, and fails with
+    /// [`Error::NoRoute`] otherwise.
+    pub fn consult_routed(&self, from: &Registered<C>, msg: C::Message) -> Result<DeliveryOutcome, Error> {
+        let routed = lock::read(&self.router, Operation::Router)?
+            .route(&msg)
+            .cloned();
+        let to = match routed {
+            Some(to) => to,
+            None => lock::read(&self.fallback, Operation::Router)?
+                .clone()
+                .ok_or(Error::NoRoute)?,
+        };
+        self.consult(from.id(), &to, Message::new(msg), Consult::default())
+    }
+    
+    /// Runs every message through `stamper` once per send, with the sender's id, before it's
+    /// routed anywhere, e.g. to stamp it with the sender and a timestamp. Unlike a
+    /// [transform](GenericMediator::set_transform) it doesn't depend on the recipient, so a
+    /// broadcast is stamped once for all of them.
+    pub fn set_stamper(&self, stamper: impl Fn(&C::Identifier, C::Message) -> C::Message + Send + Sync + 'static) -> Result<(), Error> {
+        *lock::write(&self.stamper, Operation::Stamper)? = Some(Arc::new(stamper));
+        Ok(())
+    }
+    
+    pub fn clear_stamper(&self) -> Result<(), Error> {
+        *lock::write(&self.stamper, Operation::Stamper)? = None;
+        Ok(())
+    }
+    
+    /// Starts a new window, giving every colleague with a quota its full allowance back.
+    pub fn reset_quotas(&self) -> Result<(), Error> {
+        self.pipeline.quotas.reset(self.clock.now())
+    }
+    
+    pub fn join_group(&self, id: &C::Identifier, group: GroupId) -> Result<(), Error> {
+        lock::write(&self.groups, Operation::Group)?
+            .entry(group)
+            .or_default()
+            .insert(id.clone());
+        Ok(())
+    }
+    
+    /// Removes `id` from `group`, dropping the group altogether once its last member leaves.
+    pub fn leave_group(&self, id: &C::Identifier, group: &GroupId) -> Result<(), Error> {
+        let mut groups = lock::write(&self.groups, Operation::Group)?;
+        let members = groups.get_mut(group)
+            .ok_or_else(|| Error::GroupNotFound { group: format!("{:?}", group) })?;
+        members.remove(id);
+        if members.is_empty() {
+            groups.remove(group);
+        }
+        Ok(())
+    }
+    
+    /// Consults like [`Mediator::consultation`], but lets `msg` overtake queued messages of
+    /// lower priority if `to` has a mailbox.
+    pub fn consult_with_priority(&self, from: &Registered<C>, to: &C::Identifier, msg: C::Message, priority: Priority) -> Result<DeliveryOutcome, Error> {
+        self.consult(from.id(), to, Message::new(msg), Consult { priority, ..Consult::default() })
+    }
+    
+    /// Consults like [`Mediator::consultation`], telling the recipient through the
+    /// [`ReplyContext`](crate::ReplyContext) of [`Colleague::read_with_context`] that the answer
+    /// is only of use before `deadline`, as told by the mediator's [clock](GenericMediatorBuilder::clock).
+    /// Nothing stops the message from being read late; it's for the handler to check.
+    pub fn consult_with_deadline(&self, from: &Registered<C>, to: &C::Identifier, msg: C::Message, deadline: Instant) -> Result<DeliveryOutcome, Error> {
+        self.consult(from.id(), to, Message::new(msg), Consult { deadline: Some(deadline), ..Consult::default() })
+    }
+    
+    /// Consults like [`Mediator::consultation`], delivering `msg` together with its headers
+    /// to [`Colleague::read_with_headers`].
+    pub fn consult_with_headers(&self, from: &Registered<C>, to: &C::Identifier, msg: Message<C::Message>) -> Result<DeliveryOutcome, Error> {
+        self.consult(from.id(), to, msg, Consult::default())
+    }
+    
+    /// Passes on the message `received` came with, e.g. to hand it to a colleague better placed
+    /// to answer: `to`, the observer and the event log see it as sent by the original sender,
+    /// and only a `forwarded-by` header for [`Colleague::read_with_headers`] tells of the
+    /// colleague passing it on. Only the mediator hands out a [`ReplyContext`], to the recipient
+    /// while it reads, so nobody can pass on a message they didn't get in the first place.
+    pub fn transfer_message(&self, received: &ReplyContext<'_, C>, to: &C::Identifier, msg: C::Message) -> Result<DeliveryOutcome, Error> {
+        let msg = Message::new(msg).with_header("forwarded-by", format!("{:?}", received.to()));
+        self.consult(received.from(), to, msg, Consult::default())
+    }
+    
+    /// Consults like [`Mediator::consultation`] under the caller's own `id`, dropping `msg` as a
+    /// [`DeliveryOutcome::Duplicate`] before anyone reads it if the same id went through within
+    /// the [deduplication window](GenericMediatorBuilder::dedupe). Retrying senders can resend
+    /// freely that way. A consultation failing with an error doesn't count as seen.
+    pub fn consult_idempotent(&self, from: &Registered<C>, to: &C::Identifier, msg: C::Message, id: MessageId) -> Result<DeliveryOutcome, Error> {
+        if !self.dedupe.admit(id, self.clock.now()) {
+            return Ok(DeliveryOutcome::Duplicate(id));
+        }
+        self.consult(from.id(), to, Message::new(msg), Consult { id: Some(id), ..Consult::default() })
+            .inspect_err(|_| self.dedupe.forget(id))
+    }
+    
+    /// Delivers `msg` to all of `to` or none of them. Everything that could keep a recipient
+    /// from reading is checked for all of them first, before anyone reads anything: the send
+    /// fails as a consultation would if a recipient isn't registered, is `from` itself, has its
+    /// circuit open, a full mailbox or another schema version, or if a middleware rejects the
+    /// message; with [`Error::PrepareDeclined`] if a recipient is paused without a mailbox, its
+    /// filter isn't interested or it declines through [`Colleague::can_accept`]. Only then is
+    /// one message of `from`'s [quota](GenericMediator::set_quota) taken for the whole send,
+    /// and every recipient consulted in order. Nothing can be taken back once delivering
+    /// started, so a recipient that still can't read after agreeing, as its `read_msg` refused,
+    /// its handler panicked or its mailbox filled up in the meantime, shows as
+    /// [`DeliveryOutcome::Rejected`] rather than failing the send.
+    pub fn consult_2pc(&self, from: &Registered<C>, to: &[C::Identifier], msg: C::Message) -> Result<Vec<DeliveryOutcome>, Error>
+        where C::Message: Clone
+    {
+        self.ensure_running()?;
+        let prepared = to.iter()
+            .map(|id| self.prepare(from.id(), id, &msg).map(|(recipient, prepared)| (id, recipient, prepared)))
+            .collect::<Result<Vec<_>, _>>()?;
+        if !self.pipeline.quotas.take(from.id(), self.clock.now())? {
+            return Err(Error::QuotaExceeded { id: format!("{:?}", from.id()) });
+        }
+        Ok(prepared.into_iter()
+            .map(|(id, recipient, prepared)| self.commit(from.id(), id, &recipient, &msg, prepared))
+            .collect())
+    }
+    
+    /// Holds `msg` back until [`GenericMediator::tick`] is called at or after `when`, and only
+    /// then consults `to` with it, e.g. for a reminder. Everything about the consultation,
+    /// whether `to` is registered included, is decided at that point.
+    pub fn consult_at(&self, from: &Registered<C>, to: &C::Identifier, msg: C::Message, when: Instant) -> Result<(), Error> {
+        self.ensure_running()?;
+        self.scheduled.push(when, from.id().clone(), to.clone(), msg);
+        Ok(())
+    }
+    
+    /// Like [`GenericMediator::consult_at`], `delay` from now by the mediator's [`Clock`].
+    pub fn consult_after(&self, from: &Registered<C>, to: &C::Identifier, msg: C::Message, delay: Duration) -> Result<(), Error> {
+        self.consult_at(from, to, msg, self.clock.now() + delay)
+    }
+    
+    /// Consults with every scheduled message due at `now`, soonest first, and returns how many
+    /// of them were sent. Messages whose consultation fails, e.g. because the recipient isn't
+    /// registered and there's no deadletter queue, are dropped.
+    pub fn tick(&self, now: Instant) -> Result<usize, Error> {
+        self.ensure_running()?;
+        let mut sent = 0;
+        for pending in self.scheduled.due(now) {
+            if self.consult(&pending.from, &pending.to, Message::new(pending.msg), Consult::default()).is_ok() {
+                sent += 1;
+            }
+        }
+        Ok(sent)
+    }
+    
+    /// How many scheduled messages are still waiting for their time.
+    pub fn scheduled_count(&self) -> usize {
+        self.scheduled.len()
+    }
+    
+    /// Delivers `msg` at least once: it stays in flight until `to` acks it from
+    /// [`Colleague::read_reliably`], and [`GenericMediator::redeliver`] sends it again whenever
+    /// `to` nacked it or didn't ack it within `ack_timeout`, as told by the mediator's
+    /// [`Clock`]. The stamper, middlewares and transforms see the message once, on its way in;
+    /// filters, mailboxes and outlets are skipped, since only the colleague itself can ack.
+    pub fn consult_reliably(&self, from: &Registered<C>, to: &C::Identifier, msg: C::Message, ack_timeout: Duration) -> Result<MessageId, Error>
+        where C::Message: Clone
+    {
+        self.ensure_running()?;
+        let from = from.id();
+        if !self.allow_self_send && from.eq(to) {
+            return Err(Error::SelfSend { id: format!("{:?}", to) });
+        }
+        
+        let msg = self.through_middlewares(from, to, self.stamped(from, msg))?;
+        let recipient = self.lookup(from, to)
+            .ok_or_else(|| Error::NotRegistered { id: format!("{:?}", to) })?;
+        let id = MessageId::next(&self.sequence);
+        self.metrics.sent();
+        let msg = self.transformed(to, msg);
+        let ack = self.pipeline.in_flight.push(id, from.clone(), to.clone(), msg.clone(), ack_timeout, self.clock.now());
+        self.delivered(from, to, &msg, false);
+        // A refusal is the colleague's to nack; only a panic is the sender's to hear about.
+        let _ = self.guarded(to, &recipient, || recipient.deliver_reliably(msg, ack))?;
+        Ok(id)
+    }
+    
+    /// Delivers once more every message sent with [`GenericMediator::consult_reliably`] that was
+    /// nacked or hasn't been acked by `now`, and returns how many of them were. A message whose
+    /// recipient is no longer registered stays in flight, waiting for it to come back.
+    pub fn redeliver(&self, now: Instant) -> Result<usize, Error>
+        where C::Message: Clone
+    {
+        self.ensure_running()?;
+        let mut redelivered = 0;
+        for due in self.pipeline.in_flight.due(now) {
+            if let Some(recipient) = self.lookup(&due.from, &due.to) {
+                self.delivered(&due.from, &due.to, &due.msg, false);
+                let _ = self.guarded(&due.to, &recipient, || recipient.deliver_reliably(due.msg, due.ack));
+                redelivered += 1;
+            }
+        }
+        Ok(redelivered)
+    }
+    
+    /// When `id` last sent or was delivered a message, or registered if it has done neither,
+    /// as told by the mediator's [`Clock`].
+    pub fn last_active(&self, id: &C::Identifier) -> Option<Instant> {
+        lock::recover_read(&self.activity)
+            .get(id)
+            .copied()
+    }
+    
+    /// Deregisters every colleague that hasn't sent or been delivered a message for longer than
+    /// `older_than`, see [`GenericMediator::last_active`], and returns their ids.
+    pub fn reap_idle(&self, older_than: Duration) -> Result<Vec<C::Identifier>, Error>
+        where C::Request: Debug
+    {
+        let now = self.clock.now();
+        let idle: Vec<_> = lock::recover_read(&self.activity)
+            .iter()
+            .filter(|(_, last)| now.saturating_duration_since(**last) > older_than)
+            .map(|(id, _)| id.clone())
+            .collect();
+        let mut reaped = Vec::with_capacity(idle.len());
+        for id in idle {
+            lock::recover_write(&self.activity)
+                .remove(&id);
+            if self.deregister(&id)?.is_some() {
+                reaped.push(id);
+            }
+        }
+        Ok(reaped)
+    }
+    
+    /// Deregisters every colleague `pred` holds for in a single pass under the registry lock,
+    /// and returns their ids. `pred` mustn't call back into the mediator, as the lock is held.
+    pub fn deregister_where(&self, pred: impl Fn(&C) -> bool) -> Result<Vec<C::Identifier>, Error> {
+        let mut removed = Vec::new();
+        self.write_colleagues()
+            .retain(|id, colleague| {
+                let keep = !pred(colleague);
+                if !keep {
+                    removed.push(id.clone());
+                }
+                keep
+            });
+        for id in &removed {
+            self.forget(id);
+            self.record(|_| EventKind::Deregistered { id: id.clone() });
+            self.left(id);
+        }
+        Ok(removed)
+    }
+    
+    /// Moves every colleague over to `other`, e.g. to rebalance or retire this mediator,
+    /// returning how many there were. `other` gets the very handles registered here, so
+    /// [`Sender`](crate::Sender)s and [`Registered`]s kept elsewhere stay valid, though colleagues still send
+    /// through the mediator they belong to. All or nothing: if `other` already has any of the
+    /// ids, nothing moves. Draining a mediator into itself or a clone of it moves nothing.
+    pub fn drain_into(&self, other: &Self) -> Result<usize, Error> {
+        if Arc::ptr_eq(&self.colleagues, &other.colleagues) {
+            return Ok(0);
+        }
+        // Locked in the same order either way round, so two mediators draining into each other
+        // can't deadlock.
+        let (mut source, mut target) = if Arc::as_ptr(&self.colleagues) < Arc::as_ptr(&other.colleagues) {
+            let source = self.write_colleagues();
+            (source, other.write_colleagues())
+        } else {
+            let target = other.write_colleagues();
+            (self.write_colleagues(), target)
+        };
+        other.ensure_running()?;
+        
+        if let Some(id) = source.keys().find(|id| target.contains_key(id)) {
+            return Err(Error::AlreadyRegistered { id: format!("{:?}", id) });
+        }
+        let mut moved = Vec::with_capacity(source.len());
+        source.retain(|id, registered| {
+            target.insert(id.clone(), registered.clone());
+            moved.push(id.clone());
+            false
+        });
+        drop((source, target));
+        
+        for id in &moved {
+            self.forget(id);
+            self.record(|_| EventKind::Deregistered { id: id.clone() });
+            self.left(id);
+            other.record(|_| EventKind::Registered { id: id.clone(), weak: false });
+            other.joined(id);
+        }
+        Ok(moved.len())
+    }
+    
+    /// Delivers a large payload piece by piece, so `to` can get to work before it has all of it:
+    /// each chunk goes to [`Colleague::read_chunk`] as soon as `chunks` yields it, and
+    /// [`Colleague::read_end`] follows the last. The first chunk turned down ends the stream as
+    /// [`DeliveryOutcome::Rejected`]. Chunks aren't messages, so middlewares, filters, transforms
+    /// and mailboxes don't apply, and if `to` isn't registered the deadletter queue can't keep
+    /// them: the outcome is [`DeliveryOutcome::Deadlettered`] and none of them is pulled.
+    pub fn consult_stream(&self, from: &Registered<C>, to: &C::Identifier, chunks: impl Iterator<Item = Vec<u8>>) -> Result<DeliveryOutcome, Error> {
+        self.ensure_running()?;
+        let from = from.id();
+        if !self.allow_self_send && from.eq(to) {
+            return Err(Error::SelfSend { id: format!("{:?}", to) });
+        }
+        
+        let id = MessageId::next(&self.sequence);
+        self.metrics.sent();
+        let outcome = match self.lookup(from, to) {
+            Some(recipient) => match self.guarded(to, &recipient, || recipient.deliver_chunks(chunks))? {
+                Ok(()) => {
+                    self.metrics.delivered();
+                    DeliveryOutcome::Delivered(id)
+                }
+                Err(e) => DeliveryOutcome::Rejected { id, colleague: format!("{:?}", to), reason: e.reason },
+            },
+            None if self.pipeline.deadletters.is_enabled() => {
+                self.metrics.deadlettered();
+                DeliveryOutcome::Deadlettered(id)
+            }
+            None => return Err(Error::NotRegistered { id: format!("{:?}", to) }),
+        };
+        Ok(outcome)
+    }
+    
+    /// How many messages sent with [`GenericMediator::consult_reliably`] haven't been acked yet.
+    pub fn in_flight_count(&self) -> usize {
+        self.pipeline.in_flight.len()
+    }
+    
+    /// Everything the [ack store](GenericMediatorBuilder::ack_store) recorded for `id`, oldest
+    /// first, once the acks and nacks so far have been taken into account. Empty without a store.
+    pub fn ack_history(&self, id: MessageId) -> Vec<AckRecord<C::Identifier>> {
+        self.pipeline.in_flight.settle();
+        self.ack_store.as_ref()
+            .map(|store| store.history(id))
+            .unwrap_or_default()
+    }
+    
+    /// Consults like [`Mediator::consultation`] and hands back an [`Ack`] that resolves once
+    /// the recipient has read `msg`, which for a colleague with a mailbox is when it drains it.
+    pub fn consult_with_ack(&self, from: &Registered<C>, to: &C::Identifier, msg: C::Message) -> Result<Ack, Error> {
+        let (acker, rx) = Acker::pair();
+        let outcome = self.consult(from.id(), to, Message::new(msg), Consult { acker: Some(acker), ..Consult::default() })?;
+        Ok(Ack::new(outcome.message_id(), rx))
+    }
+    
+    /// Consults like [`Mediator::consultation`], but if `to` has a mailbox and hasn't drained
+    /// `msg` within `ttl`, as told by the mediator's [clock](GenericMediatorBuilder::clock),
+    /// the message is deadlettered instead of read. Expired messages always go to the
+    /// deadletter queue, even if the mediator doesn't deadletter otherwise.
+    pub fn consult_with_ttl(&self, from: &Registered<C>, to: &C::Identifier, msg: C::Message, ttl: Duration) -> Result<DeliveryOutcome, Error>
+        where C: 'static,
+              C::Identifier: Send + Sync + 'static,
+              C::Message: Send + Sync + 'static
+    {
+        let deadletters = Arc::clone(&self.pipeline.deadletters);
+        let metrics = Arc::clone(&self.metrics);
+        let observer = self.observer.clone();
+        let recipient = to.clone();
+        let on_expire = move |id, from, msg| {
+            if let Some(observer) = &observer {
+                observer.on_deadletter(&from, &recipient, &msg);
+            }
+            deadletters.expired(DeadLetter { id, from, to: recipient, msg, reason: DeadletterReason::Expired });
+            metrics.deadlettered();
+        };
+        self.consult(from.id(), to, Message::new(msg), Consult { ttl: Some((ttl, Box::new(on_expire))), ..Consult::default() })
+    }
+    
+    /// Delivers `msg` to everyone but `from` like [`Mediator::broadcast`], lending each recipient
+    /// the same message through [`Colleague::read_msg_ref`] instead of cloning it for them.
+    /// Recipients with a mailbox, a channel or a transform still get a clone of their own, and so
+    /// does every recipient while middlewares are installed, as those take the message by value.
+    pub fn broadcast_ref(&self, from: &Registered<C>, msg: &C::Message) -> Result<usize, Error>
+        where C::Message: Clone
+    {
+        self.ensure_running()?;
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("broadcast", from = ?from.id()).entered();
+        
+        let sent = self.events.as_ref().map(|events| events.copy(msg));
+        let stamped;
+        let msg = if lock::recover_read(&self.stamper).is_some() {
+            stamped = self.stamped(from.id(), msg.clone());
+            &stamped
+        } else {
+            msg
+        };
+        let mut reached = 0;
+        for (id, colleague) in self.broadcast_order(from.id()) {
+            let id = &id;
+            let rewritten;
+            let msg = if self.middlewares.is_empty() {
+                msg
+            } else {
+                let Ok(msg) = self.through_middlewares(from.id(), id, msg.clone()) else {
+                    continue;
+                };
+                rewritten = msg;
+                &rewritten
+            };
+            if !self.interested(id, msg) {
+                continue;
+            }
+            let delivered = if lock::recover_read(&self.transforms).contains_key(id) {
+                self.hand_over(from.id(), id, &colleague, Message::new(msg.clone()), Delivery::default(), false)
+            } else {
+                self.guarded(id, &colleague, || colleague.deliver_ref(from.id(), msg, |msg| self.delivered(from.id(), id, msg, false)))
+            };
+            if let Ok(Ok(true)) = delivered {
+                reached += 1;
+            }
+        }
+        
+        self.metrics.fanned_out(reached);
+        if let Some(msg) = sent {
+            self.record(|_| EventKind::Broadcast { from: from.id().clone(), msg, reached });
+        }
+        Ok(reached)
+    }
+    
+    /// Delivers `msg` to every registered member of `group` except the sender,
+    /// returning how many members were reached.
+    pub fn multicast(&self, from: &Registered<C>, group: &GroupId, msg: C::Message) -> Result<usize, Error>
+        where C::Message: Clone
+    {
+        self.ensure_running()?;
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("multicast", from = ?from.id(), group = ?group).entered();
+        
+        let msg = self.stamped(from.id(), msg);
+        // Cloned out like the broadcast order, so members may change groups while reading.
+        let members: Vec<_> = {
+            let groups = lock::read(&self.groups, Operation::Multicast)?;
+            let members = groups.get(group)
+                .ok_or_else(|| Error::GroupNotFound { group: format!("{:?}", group) })?;
+            let colleagues = self.read_colleagues();
+            members.iter()
+                .filter(|id| id.ne(&from.id()))
+                .filter_map(|id| colleagues.get(id).map(|colleague| (id.clone(), colleague.clone())))
+                .collect()
+        };
+        
+        let mut reached = 0;
+        for (id, colleague) in &members {
+            let Ok(msg) = self.through_middlewares(from.id(), id, msg.clone()) else {
+                continue;
+            };
+            if !self.interested(id, &msg) {
+                continue;
+            }
+            if let Ok(Ok(true)) = self.hand_over(from.id(), id, colleague, Message::new(msg), Delivery::default(), false) {
+                reached += 1;
+            }
+        }
+        
+        self.metrics.fanned_out(reached);
+        Ok(reached)
+    }
+    
+    /// Consults `to` on behalf of `from`, recording the consultation as sent along with how it
+    /// came out, unless it is a quiet one.
+    fn consult(&self, from: &C::Identifier, to: &C::Identifier, msg: Message<C::Message>, consult: Consult<C>) -> Result<DeliveryOutcome, Error> {
+        let sent = self.events.as_ref()
+            .filter(|_| !consult.quiet)
+            .map(|events| events.copy_message(&msg));
+        let outcome = self.run_consultation(from, to, msg, consult);
+        if let Some(msg) = sent {
+            self.record(|_| EventKind::Consulted { from: from.clone(), to: to.clone(), msg, outcome: outcome.clone() });
+        }
+        outcome
+    }
+    
+    /// Consults `to` on behalf of `from` with a message as it was recorded in the event log,
+    /// for [`testing::replay`](crate::testing::replay).
+    #[cfg(feature = "std")]
+    pub(crate) fn consult_recorded(&self, from: &C::Identifier, to: &C::Identifier, msg: Message<C::Message>) -> Result<DeliveryOutcome, Error> {
+        self.consult(from, to, msg, Consult::default())
+    }
+    
+    fn run_consultation(&self, from: &C::Identifier, to: &C::Identifier, msg: Message<C::Message>, consult: Consult<C>) -> Result<DeliveryOutcome, Error> {
+        self.ensure_running()?;
+        
+        if !self.allow_self_send && from.eq(to) {
+            return Err(Error::SelfSend { id: format!("{:?}", to) });
+        }
+        
+        if !self.pipeline.quotas.take(from, self.clock.now())? {
+            if self.pipeline.deadletters.is_enabled() && !consult.quiet {
+                let _ = self.deadlettered(from, to, MessageId::next(&self.sequence), msg.body, DeadletterReason::QuotaExceeded);
+            }
+            return Err(Error::QuotaExceeded { id: format!("{:?}", from) });
+        }
+        
+        let Message { headers, version, body } = msg;
+        let body = self.stamped(from, body);
+        let msg = Message { headers, version, body: self.through_middlewares(from, to, body)? };
+        let Consult { priority, acker, ttl, id, deadline, quiet } = consult;
+        let id = id.unwrap_or_else(|| MessageId::next(&self.sequence));
+        self.metrics.sent();
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("consultation", from = ?from, to = ?to, message_id = id.as_u64()).entered();
+        
+        let expiry = ttl.map(|(ttl, on_expire)| Expiry { id, at: self.clock.now() + ttl, clock: Arc::clone(&self.clock), on_expire });
+        let found = self.lookup(from, to);
+        if found.is_none() && quiet {
+            return Err(Error::NotRegistered { id: format!("{:?}", to) });
+        }
+        if let Some(remote) = found.is_none().then(|| self.bridged(to)).flatten() {
+            return self.forward(&remote, from, to, msg.body);
+        }
+        #[cfg(feature = "tokio")]
+        if let (None, Some(remote)) = (&found, &self.pipeline.remote) {
+            return self.publish(remote, from, to, id, &msg.body);
+        }
+        if let (None, Some(parent)) = (&found, &self.parent) {
+            return self.forward(parent, from, to, msg.body);
+        }
+        if found.is_some() && !self.pipeline.admit(to, self.clock.now()) {
+            return Err(Error::CircuitOpen { id: format!("{:?}", to) });
+        }
+        match &found {
+            _ if quiet => {}
+            Some(_) => self.tapped(from, to, &msg.body, Tapped::Routed),
+            None if self.pipeline.deadletters.is_enabled() => self.tapped(from, to, &msg.body, Tapped::Deadlettered),
+            None => {}
+        }
+        let outcome = match found {
+            Some(colleague) if self.is_paused(to) && !colleague.has_mailbox() => {
+                if quiet {
+                    return Err(Error::NotRegistered { id: format!("{:?}", to) });
+                }
+                self.deadlettered(from, to, id, msg.body, DeadletterReason::Paused)?
+            }
+            Some(colleague) if !colleague.accepts_version(msg.version) => {
+                if self.pipeline.deadletters.is_enabled() && !quiet {
+                    let _ = self.deadlettered(from, to, id, msg.body, DeadletterReason::VersionMismatch);
+                }
+                return Err(Error::VersionMismatch { id: format!("{:?}", to), got: msg.version, expected: colleague.schema_version() });
+            }
+            Some(_) if !self.interested(to, &msg.body) => {
+                if self.pipeline.deadletters.is_enabled() && !quiet {
+                    let _ = self.deadlettered(from, to, id, msg.body, DeadletterReason::Filtered);
+                }
+                DeliveryOutcome::Filtered(id)
+            }
+            Some(colleague) => {
+                let deadline = deadline.map(|at| Deadline::new(at, Arc::clone(&self.clock)));
+                match self.hand_over(from, to, &colleague, msg, Delivery { priority, acker, expiry, deadline }, quiet)? {
+                    Ok(true) => DeliveryOutcome::Delivered(id),
+                    Ok(false) => return Err(Error::MailboxFull { id: format!("{:?}", to) }),
+                    Err(e) => DeliveryOutcome::Rejected { id, colleague: format!("{:?}", to), reason: e.reason },
+                }
+            }
+            None if self.pipeline.deadletters.is_enabled() => self.deadlettered(from, to, id, msg.body, DeadletterReason::NotRegistered)?,
+            None => return Err(Error::NotRegistered { id: format!("{:?}", to) }),
+        };
+        if let Some(store) = &self.ack_store {
+            store.record(id, to, AckOutcome::Resolved(outcome.clone()));
+        }
+        
+        Ok(outcome)
+    }
+    
+    /// Checks whatever [`GenericMediator::run_consultation`] could refuse `msg` to `to` for,
+    /// without delivering anything, and hands back the recipient along with `msg` the way it
+    /// would read it, for [`GenericMediator::consult_2pc`].
+    fn prepare(&self, from: &C::Identifier, to: &C::Identifier, msg: &C::Message) -> Result<(Registered<C>, Message<C::Message>), Error>
+        where C::Message: Clone
+    {
+        if !self.allow_self_send && from.eq(to) {
+            return Err(Error::SelfSend { id: format!("{:?}", to) });
+        }
+        let recipient = self.lookup(from, to)
+            .filter(|recipient| recipient.readiness() != Readiness::Absent)
+            .ok_or_else(|| Error::NotRegistered { id: format!("{:?}", to) })?;
+        let Message { headers, version, body } = Message::new(msg.clone());
+        let msg = Message { headers, version, body: self.through_middlewares(from, to, self.stamped(from, body))? };
+        if !self.pipeline.admit(to, self.clock.now()) {
+            return Err(Error::CircuitOpen { id: format!("{:?}", to) });
+        }
+        if recipient.readiness() == Readiness::Full {
+            return Err(Error::MailboxFull { id: format!("{:?}", to) });
+        }
+        if !recipient.accepts_version(msg.version) {
+            return Err(Error::VersionMismatch { id: format!("{:?}", to), got: msg.version, expected: recipient.schema_version() });
+        }
+        let paused = self.is_paused(to) && !recipient.has_mailbox();
+        if paused || !self.interested(to, &msg.body) || !recipient.can_accept(&msg.body) {
+            return Err(Error::PrepareDeclined { id: format!("{:?}", to) });
+        }
+        Ok((recipient, msg))
+    }
+    
+    /// Hands `prepared` over to `recipient` once [`GenericMediator::consult_2pc`] has prepared
+    /// everyone, recording `msg` as sent the way [`GenericMediator::consult`] would. Whatever
+    /// goes wrong now is `recipient`'s outcome alone.
+    fn commit(&self, from: &C::Identifier, to: &C::Identifier, recipient: &Registered<C>, msg: &C::Message, prepared: Message<C::Message>) -> DeliveryOutcome {
+        let id = MessageId::next(&self.sequence);
+        self.metrics.sent();
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("consultation", from = ?from, to = ?to, message_id = id.as_u64()).entered();
+        self.tapped(from, to, &prepared.body, Tapped::Routed);
+        let rejected = |reason: String| DeliveryOutcome::Rejected { id, colleague: format!("{:?}", to), reason };
+        let outcome = match self.hand_over(from, to, recipient, prepared, Delivery::default(), false) {
+            Ok(Ok(true)) => DeliveryOutcome::Delivered(id),
+            Ok(Ok(false)) => rejected(Error::MailboxFull { id: format!("{:?}", to) }.to_string()),
+            Ok(Err(e)) => rejected(e.reason),
+            Err(e) => rejected(e.to_string()),
+        };
+        if let Some(store) = &self.ack_store {
+            store.record(id, to, AckOutcome::Resolved(outcome.clone()));
+        }
+        self.record(|events| EventKind::Consulted { from: from.clone(), to: to.clone(), msg: Message::new(events.copy(msg)), outcome: Ok(outcome.clone()) });
+        outcome
+    }
+    
+    fn deadlettered(&self, from: &C::Identifier, to: &C::Identifier, id: MessageId, msg: C::Message, reason: DeadletterReason) -> Result<DeliveryOutcome, Error> {
+        let letter = DeadLetter { id, from: from.clone(), to: to.clone(), msg, reason };
+        // The event log hears of every letter the queue accepts, the observer only of those that
+        // actually made it in.
+        let offered = self.pipeline.deadletters.offer(
+            letter,
+            |letter| self.record(|events| EventKind::Deadletter { from: letter.from.clone(), to: letter.to.clone(), msg: events.copy(&letter.msg), reason }),
+            |letter| self.notify_deadletter(letter),
+        )?;
+        if offered == Offer::Rejected {
+            return Err(Error::DeadletterFull { id: format!("{:?}", to) });
+        }
+        self.metrics.deadlettered();
+        #[cfg(feature = "tracing")]
+        tracing::info!("deadlettered");
+        Ok(DeliveryOutcome::Deadlettered(id))
+    }
+    
+    fn notify_deadletter(&self, letter: &DeadLetter<C::Identifier, C::Message>) {
+        if let Some(observer) = &self.observer {
+            observer.on_deadletter(&letter.from, &letter.to, &letter.msg);
+        }
+    }
+    
+    fn is_paused(&self, id: &C::Identifier) -> bool {
+        lock::recover_read(&self.paused)
+            .contains(id)
+    }
+    
+    /// Everyone but `from` and paused colleagues without a mailbox, highest [priority](GenericMediator::register_with_priority) first.
+    /// The handles are cloned out, so handlers reached by a broadcast are free to register,
+    /// deregister or broadcast themselves.
+    fn broadcast_order(&self, from: &C::Identifier) -> Vec<(C::Identifier, Registered<C>)> {
+        let colleagues = self.read_colleagues();
+        let ranks = lock::recover_read(&self.ranks);
+        let mut order: Vec<_> = colleagues.iter()
+            .filter(|(id, _)| id.ne(&from))
+            .filter(|(id, colleague)| colleague.has_mailbox() || !self.is_paused(id))
+            .map(|(id, colleague)| (ranks.get(id).copied().unwrap_or((0, u64::MAX)), id, colleague))
+            .collect();
+        order.sort_by(|&((priority, registered), id, _), &((other_priority, other_registered), other, _)| {
+            let ties = match self.id_order {
+                Some(cmp) => cmp(id, other),
+                None => registered.cmp(&other_registered),
+            };
+            other_priority.cmp(&priority).then(ties)
+        });
+        order.into_iter()
+            .map(|(_, id, colleague)| (id.clone(), colleague.clone()))
+            .collect()
+    }
+    
+    /// Delivers a consultation that came in over the transport, unless `to` left in the meantime.
+    #[cfg(feature = "tokio")]
+    fn receive(&self, from: &C::Identifier, to: &C::Identifier, msg: C::Message) {
+        if self.lookup(from, to).is_some() {
+            let _ = self.consult(from, to, Message::new(msg), Consult::default());
+        }
+    }
+    
+    #[cfg(feature = "tokio")]
+    fn publish(&self, remote: &RemoteEnd<C, R>, from: &C::Identifier, to: &C::Identifier, id: MessageId, msg: &C::Message) -> Result<DeliveryOutcome, Error> {
+        remote.transport.publish(&transport::topic(to), (remote.encode)(from, msg))
+            .map_err(|e| Error::PublishFailed { id: format!("{:?}", to), reason: e.reason })?;
+        Ok(DeliveryOutcome::Published(id))
+    }
+    
+    fn bridged(&self, to: &C::Identifier) -> Option<Parent<C>> {
+        let link = lock::recover_read(&self.bridge)
+            .clone();
+        link.and_then(|link| link(to))
+    }
+    
+    /// Consults `to` through the parent or a bridged mediator on behalf of `from`, as a plain consultation: headers,
+    /// priority, acks and time-to-live stay behind.
+    fn forward(&self, parent: &Parent<C>, from: &C::Identifier, to: &C::Identifier, msg: C::Message) -> Result<DeliveryOutcome, Error> {
+        let depth = FORWARDED.get();
+        if depth >= self.forward_depth {
+            return Err(Error::ForwardDepthExceeded { depth });
+        }
+        FORWARDED.set(depth + 1);
+        let outcome = parent.consultation_as(sealed::Token::new(), from, to, msg);
+        FORWARDED.set(depth);
+        outcome
+    }
+    
+    fn ensure_running(&self) -> Result<(), Error> {
+        if self.shutting_down.load(Ordering::SeqCst) {
+            return Err(Error::ShuttingDown);
+        }
+        Ok(())
+    }
+    
+    fn interested(&self, to: &C::Identifier, msg: &C::Message) -> bool {
+        let interested = lock::recover_read(&self.filters)
+            .get(to)
+            .is_none_or(|filter| filter(msg));
+        if !interested {
+            self.metrics.filtered();
+        }
+        interested
+    }
+    
+    /// Hands `msg` over to `recipient` through [`GenericMediator::guarded`], only failing if
+    /// the handler panicked. A `quiet` delivery isn't told to the observer.
+    fn hand_over(&self, from: &C::Identifier, to: &C::Identifier, recipient: &Registered<C>, msg: Message<C::Message>, delivery: Delivery<C>, quiet: bool) -> Result<Result<bool, ColleagueError>, Error> {
+        self.guarded(to, recipient, || {
+            let Message { headers, version, body } = msg;
+            let msg = Message { headers, version, body: self.transformed(to, body) };
+            recipient.deliver_with(from, msg, delivery, |msg| self.delivered(from, to, msg, quiet))
+        })
+    }
+    
+    /// Runs `deliver`, handing something over to `colleague` under `to`, the way every delivery
+    /// runs: timed for `to`'s latency stats and counted by its circuit breaker. Should the handler
+    /// panic, the colleague is evicted, as its state can't be trusted anymore, and this fails
+    /// with [`Error::HandlerPanicked`].
+    fn guarded<T>(&self, to: &C::Identifier, colleague: &Registered<C>, deliver: impl FnOnce() -> Result<T, ColleagueError>) -> Result<Result<T, ColleagueError>, Error> {
+        let started = self.clock.now();
+        let handed = panic::catch_unwind(AssertUnwindSafe(deliver));
+        let now = self.clock.now();
+        self.pipeline.handed(to, now.saturating_duration_since(started), handed.as_ref().ok().map(Result::is_ok), now);
+        handed.map_err(|_| {
+            self.evict(colleague);
+            Error::HandlerPanicked { id: format!("{:?}", to) }
+        })
+    }
+    
+    fn stamped(&self, from: &C::Identifier, msg: C::Message) -> C::Message {
+        let stamper = lock::recover_read(&self.stamper)
+            .clone();
+        match stamper {
+            Some(stamper) => stamper(from, msg),
+            None => msg,
+        }
+    }
+    
+    fn tapped(&self, from: &C::Identifier, to: &C::Identifier, msg: &C::Message, tapped: Tapped) {
+        let taps = lock::recover_read(&self.taps)
+            .clone();
+        taps.iter().for_each(|tap| tap(from, to, msg, tapped));
+    }
+    
+    fn transformed(&self, to: &C::Identifier, msg: C::Message) -> C::Message {
+        let transform = lock::recover_read(&self.transforms)
+            .get(to)
+            .cloned();
+        match transform {
+            Some(transform) => transform(msg),
+            None => msg,
+        }
+    }
+    
+    /// Accounts for `msg` reaching `to`; a `quiet` delivery isn't told to the observer.
+    fn delivered(&self, from: &C::Identifier, to: &C::Identifier, msg: &C::Message, quiet: bool) {
+        self.metrics.delivered();
+        let now = self.clock.now();
+        let mut activity = lock::recover_write(&self.activity);
+        for id in [from, to] {
+            if let Some(last) = activity.get_mut(id) {
+                *last = now;
+            }
+        }
+        drop(activity);
+        if let Some(size) = self.sizer {
+            self.metrics.delivered_bytes(size(msg));
+        }
+        #[cfg(feature = "tracing")]
+        tracing::info!(to = ?to, "delivered");
+        self.record(|events| EventKind::Delivered { from: from.clone(), to: to.clone(), msg: events.copy(msg) });
+        if let Some(observer) = self.observer.as_ref().filter(|_| !quiet) {
+            observer.on_delivered(from, to, msg);
+        }
+        let watchers = lock::recover_read(&self.watchers)
+            .of(to);
+        for watcher in watchers {
+            watcher(msg);
+        }
+    }
+    
+    fn register_inbox_with(&self, id: C::Identifier, registered: Managed<C>, capacity: Option<usize>) -> Result<(Registered<C>, Inbox<C::Message>), Error>
+        where C::Request: Debug
+    {
+        let (inbox, tx) = Inbox::new(capacity);
+        let reg = self.register(id, registered.with_outlet(Outlet::Inbox(tx)))?;
+        Ok((reg, inbox))
+    }
+    
+    fn through_middlewares(&self, from: &C::Identifier, to: &C::Identifier, msg: C::Message) -> Result<C::Message, Error> {
+        self.middlewares.iter().try_fold(msg, |msg, middleware| {
+            match middleware.before_deliver(from, to, msg) {
+                MiddlewareResult::Pass(msg) | MiddlewareResult::Rewrite(msg) => Ok(msg),
+                MiddlewareResult::Reject { reason } => Err(Error::Rejected { reason }),
+            }
+        })
+    }
+    
+    /// Finds whoever messages from `from` to `id` go to and why: the colleague registered
+    /// under `id`, else the first matching pattern, else the fallback unless it is `from`.
+    fn resolve(&self, from: Option<&C::Identifier>, id: &C::Identifier) -> Option<(Registered<C>, RouteDecision)> {
+        if let Some(found) = self.lookup_exact(id) {
+            return Some((found, RouteDecision::Exact));
+        }
+        let matched = lock::recover_read(&self.patterns)
+            .iter()
+            .find(|pattern| pattern.matches(id))
+            .map(|pattern| (pattern.colleague().clone(), RouteDecision::Pattern(pattern.glob().to_string())));
+        if matched.is_some() {
+            return matched;
+        }
+        
+        let fallback = lock::recover_read(&self.fallback).clone()?;
+        if from == Some(&fallback) {
+            return None;
+        }
+        self.lookup_exact(&fallback).map(|colleague| (colleague, RouteDecision::Fallback))
+    }
+    
+    fn lookup(&self, from: &C::Identifier, id: &C::Identifier) -> Option<Registered<C>> {
+        self.resolve(Some(from), id).map(|(colleague, _)| colleague)
+    }
+    
+    /// Finds `id` among the strong registrations first, then the weak ones, pruning `id`
+    /// if its colleague is gone.
+    fn lookup_exact(&self, id: &C::Identifier) -> Option<Registered<C>> {
+        if let Some(found) = self.read_colleagues().get(id) {
+            return Some(found.clone());
+        }
+        
+        let mut weak = lock::recover_write(&self.weak);
+        let found = weak.get(id)?.upgrade();
+        if found.is_none() {
+            weak.remove(id);
+            self.record(|_| EventKind::Deregistered { id: id.clone() });
+        }
+        found
+    }
+    
+    /// Drops what the mediator keeps about `id` besides its registration.
+    fn forget(&self, id: &C::Identifier) {
+        lock::recover_write(&self.tags)
+            .remove(id);
+        lock::recover_write(&self.roles)
+            .retain(|_, workers| {
+                workers.remove(id);
+                !workers.is_empty()
+            });
+    }
+    
+    /// Deregisters `colleague` under whatever ids and patterns it is registered, unlike
+    /// [`Mediator::deregister`] telling it apart from other colleagues sharing its id.
+    fn evict(&self, colleague: &Registered<C>) {
+        let mut evicted = Vec::new();
+        self.write_colleagues().retain(|id, registered| {
+            let keep = !registered.ptr_eq(colleague);
+            if !keep {
+                evicted.push(id.clone());
+            }
+            keep
+        });
+        lock::recover_write(&self.weak).retain(|id, weak| {
+            let keep = !weak.upgrade().is_some_and(|registered| registered.ptr_eq(colleague));
+            if !keep {
+                evicted.push(id.clone());
+            }
+            keep
+        });
+        lock::recover_write(&self.patterns)
+            .retain(|pattern| !pattern.colleague().ptr_eq(colleague));
+        
+        for id in &evicted {
+            self.forget(id);
+            self.record(|_| EventKind::Deregistered { id: id.clone() });
+            self.left(id);
+        }
+    }
+    
+    /// Tells the observer `id` has been registered; never call with the registry locked.
+    fn joined(&self, id: &C::Identifier) {
+        #[cfg(feature = "tokio")]
+        if let Some(remote) = &self.pipeline.remote {
+            // A colleague the transport can't subscribe for is still reachable locally.
+            let _ = remote.subscribe(self.clone(), id.clone());
+        }
+        lock::recover_write(&self.activity)
+            .insert(id.clone(), self.clock.now());
+        lock::recover_write(&self.ranks)
+            .insert(id.clone(), (0, self.registrations.fetch_add(1, Ordering::Relaxed)));
+        if let Some(observer) = &self.observer {
+            observer.on_register(id);
+        }
+    }
+    
+    /// Tells the observer `id` has been deregistered; never call with the registry locked.
+    fn left(&self, id: &C::Identifier) {
+        #[cfg(feature = "tokio")]
+        if let Some(remote) = &self.pipeline.remote {
+            remote.unsubscribe(id);
+        }
+        lock::recover_write(&self.activity)
+            .remove(id);
+        lock::recover_write(&self.ranks)
+            .remove(id);
+        lock::recover_write(&self.shutdown_ranks)
+            .remove(id);
+        self.pipeline.left(id);
+        lock::recover_write(&self.paused)
+            .remove(id);
+        if let Some(observer) = &self.observer {
+            observer.on_deregister(id);
+        }
+    }
+    
+    /// Moves everything kept about `old` over to `new` once it is renamed; never call with the
+    /// registry locked.
+    fn moved(&self, old: &C::Identifier, new: &C::Identifier) {
+        #[cfg(feature = "tokio")]
+        if let Some(remote) = &self.pipeline.remote {
+            remote.unsubscribe(old);
+            let _ = remote.subscribe(self.clone(), new.clone());
+        }
+        for members in lock::recover_write(&self.groups).values_mut() {
+            if members.remove(old) {
+                members.insert(new.clone());
+            }
+        }
+        for workers in lock::recover_write(&self.roles).values_mut() {
+            if workers.remove(old) {
+                workers.insert(new.clone());
+            }
+        }
+        if lock::recover_write(&self.paused).remove(old) {
+            lock::recover_write(&self.paused).insert(new.clone());
+        }
+        rekey(&mut lock::recover_write(&self.tags), old, new);
+        rekey(&mut lock::recover_write(&self.filters), old, new);
+        rekey(&mut lock::recover_write(&self.transforms), old, new);
+        rekey(&mut lock::recover_write(&self.activity), old, new);
+        rekey(&mut lock::recover_write(&self.ranks), old, new);
+        rekey(&mut lock::recover_write(&self.shutdown_ranks), old, new);
+        lock::recover_write(&self.watchers)
+            .rename(old, new);
+        self.pipeline.rename(old, new);
+        if let Some(observer) = &self.observer {
+            observer.on_rename(old, new);
+        }
+    }
+    
+    fn record(&self, kind: impl FnOnce(&Log<C>) -> EventKind<C::Identifier, C::Message>) {
+        if let Some(events) = &self.events {
+            events.record(kind(events));
+        }
+    }
+    
+    fn read_colleagues(&self) -> RwLockReadGuard<'_, R> {
+        lock::recover_read(&self.colleagues)
+    }
+    
+    fn write_colleagues(&self) -> RwLockWriteGuard<'_, R> {
+        lock::recover_write(&self.colleagues)
+    }
+    
+    /// Registers `id` under the registry lock already taken as `colleagues`.
+    fn insert_new(&self, mut colleagues: RwLockWriteGuard<'_, R>, id: C::Identifier, registered: Managed<C>) -> Result<Registered<C>, Error> {
+        self.ensure_running()?;
+        
+        if colleagues.contains_key(&id) {
+            return Err(Error::AlreadyRegistered { id: format!("{:?}", id) });
+        }
+        let reg = Registered::new(registered);
+        self.record(|_| EventKind::Registered { id: id.clone(), weak: false });
+        colleagues.insert(id.clone(), reg.clone());
+        drop(colleagues);
+        
+        self.joined(&id);
+        Ok(reg)
+    }
+}
+
+impl<C: Colleague, R: Registry<C::Identifier, Registered<C>>> Default for GenericMediator<C, R> {
+    fn default() -> Self {
+        GenericMediatorBuilder::default().build()
+    }
+}
+
+pub struct GenericMediatorBuilder<C: Colleague, R = HashRegistry<C>> {
+    registry: PhantomData<fn() -> R>,
+    capacity: usize,
+    events: Option<Arc<Log<C>>>,
+    sizer: Option<Sizer<C>>,
+    clock: Arc<dyn Clock>,
+    quota_window: Option<Duration>,
+    deadletter: bool,
+    deadletter_capacity: Option<(usize, DeadletterPolicy)>,
+    retry: RetryPolicy,
+    allow_self_send: bool,
+    id_order: Option<IdOrder<C>>,
+    observer: Option<Arc<dyn Observer<C>>>,
+    ack_store: Option<Arc<dyn AckStore<C::Identifier>>>,
+    middlewares: Vec<Arc<dyn Middleware<C>>>,
+    parent: Option<Parent<C>>,
+    #[cfg(feature = "tokio")]
+    remote: Option<RemoteEnd<C, R>>,
+    forward_depth: usize,
+    dedupe: (Duration, usize),
+    breaker: Option<(u32, Duration)>,
+}
+
+impl<C: Colleague, R> Default for GenericMediatorBuilder<C, R> {
+    fn default() -> Self {
+        Self {
+            registry: PhantomData,
+            capacity: 0,
+            events: None,
+            sizer: None,
+            clock: Arc::new(SystemClock),
+            quota_window: None,
+            deadletter: false,
+            deadletter_capacity: None,
+            retry: RetryPolicy::default(),
+            allow_self_send: false,
+            id_order: None,
+            observer: None,
+            ack_store: None,
+            middlewares: Vec::new(),
+            parent: None,
+            #[cfg(feature = "tokio")]
+            remote: None,
+            forward_depth: 8,
+            dedupe: (Duration::from_secs(60), 1024),
+            breaker: None,
+        }
+    }
+}
+
+impl<C: Colleague, R: Registry<C::Identifier, Registered<C>>> GenericMediatorBuilder<C, R> {
+    /// Pre-sizes the registry for `capacity` colleagues.
+    pub fn capacity(self, capacity: usize) -> Self {
+        Self { capacity, ..self }
+    }
+    
+    /// Records an [`Event`] for every registration, deregistration, delivery and deadletter,
+    /// see [`GenericMediator::event_log`]. Messages are cloned into the log.
+    pub fn event_log(self, enabled: bool) -> Self
+        where C::Message: Clone
+    {
+        Self { events: enabled.then(|| Arc::new(EventLog::new())), ..self }
+    }
+    
+    /// Sums up the [`MessageSize`] of every delivered message, see
+    /// [`GenericMediator::bytes_delivered`].
+    pub fn byte_accounting(self, enabled: bool) -> Self
+        where C::Message: MessageSize
+    {
+        Self { sizer: enabled.then_some(C::Message::size as Sizer<C>), ..self }
+    }
+    
+    pub fn deadletter(self, deadletter: bool) -> Self {
+        Self { deadletter, ..self }
+    }
+    
+    /// Keeps at most `capacity` letters in the deadletter queue, going by `policy` once it's full.
+    /// The queue is unbounded otherwise.
+    pub fn deadletter_capacity(self, capacity: usize, policy: DeadletterPolicy) -> Self {
+        Self { deadletter_capacity: Some((capacity, policy)), ..self }
+    }
+    
+    /// Where quota windows and retry backoff take their time from; [`SystemClock`] by default.
+    pub fn clock(self, clock: impl Clock + 'static) -> Self {
+        Self { clock: Arc::new(clock), ..self }
+    }
+    
+    /// Starts a new quota window automatically once `window` has passed since a colleague's
+    /// current one started; without it quotas only reset through
+    /// [`GenericMediator::reset_quotas`].
+    pub fn quota_window(self, window: Duration) -> Self {
+        Self { quota_window: Some(window), ..self }
+    }
+    
+    /// How [`GenericMediator::retry_deadletters`] retries; a single round by default.
+    pub fn retry_policy(self, retry: RetryPolicy) -> Self {
+        Self { retry, ..self }
+    }
+    
+    pub fn allow_self_send(self, allow_self_send: bool) -> Self {
+        Self { allow_self_send, ..self }
+    }
+    
+    /// Has broadcasts reach colleagues of the same [priority](GenericMediator::register_with_priority)
+    /// in the order of their ids rather than the order they registered in, e.g. so tests and logs
+    /// come out the same whatever way the colleagues were set up. Off by default.
+    pub fn deterministic_broadcast(self, enabled: bool) -> Self
+        where C::Identifier: Ord
+    {
+        Self { id_order: enabled.then_some(Ord::cmp as IdOrder<C>), ..self }
+    }
+    
+    pub fn observer(self, observer: impl Observer<C> + 'static) -> Self {
+        Self { observer: Some(Arc::new(observer)), ..self }
+    }
+    
+    /// Records what became of every message into `store`, e.g. a [`MemoryAckStore`](crate::MemoryAckStore), to be
+    /// looked up with [`GenericMediator::ack_history`]. Nothing is recorded by default.
+    pub fn ack_store(self, store: impl AckStore<C::Identifier> + 'static) -> Self {
+        Self { ack_store: Some(Arc::new(store)), ..self }
+    }
+    
+    pub fn middleware(mut self, middleware: impl Middleware<C> + 'static) -> Self {
+        self.middlewares.push(Arc::new(middleware));
+        self
+    }
+    
+    /// Forwards consultations to ids nobody here answers to, not even the fallback, to
+    /// `parent` before deadlettering them, so local mediators can federate under a shared one.
+    /// Only consultations are forwarded; requests, broadcasts and quiet attempts stay local.
+    pub fn parent(self, parent: impl Mediator<C> + Send + Sync + 'static) -> Self {
+        Self { parent: Some(Arc::new(parent)), ..self }
+    }
+    
+    /// Publishes consultations to ids nobody here answers to, not even a [bridge](crate::Bridge),
+    /// on `transport` for a mediator in another process to deliver, before turning to the
+    /// parent. Messages travel with their sender, turned into bytes by `encode` and back by
+    /// `decode`.
+    ///
+    /// Every colleague that registers gets a task of its own on the current tokio runtime,
+    /// delivering what arrives on its topic as consultations from the sender it came with; it
+    /// holds on to the mediator until the colleague is deregistered. Messages for colleagues that
+    /// left in the meantime are dropped rather than published again, and so are the ones that
+    /// don't decode. Panics outside a tokio runtime, like `tokio::spawn`.
+    #[cfg(feature = "tokio")]
+    pub fn transport(self, transport: impl Transport + 'static, encode: Encode<C::Identifier, C::Message>, decode: Decode<C::Identifier, C::Message>) -> Self
+        where C: 'static,
+              C::Identifier: Eq + Hash + Clone + Debug + Send + 'static,
+              C::Request: Debug,
+              GenericMediator<C, R>: Send + 'static
+    {
+        let runtime = tokio::runtime::Handle::current();
+        let spawn = move |mediator: GenericMediator<C, R>, to: C::Identifier, mut payloads: BoxStream<'static, Vec<u8>>| {
+            runtime.spawn(async move {
+                while let Some(payload) = payloads.next().await {
+                    if let Some((from, msg)) = decode(&payload) {
+                        mediator.receive(&from, &to, msg);
+                    }
+                }
+            }).abort_handle()
+        };
+        Self { remote: Some(Remote::new(Arc::new(transport), encode, Box::new(spawn))), ..self }
+    }
+    
+    /// How many parents or [bridges](crate::Bridge) a consultation may be forwarded through before
+    /// it fails with [`Error::ForwardDepthExceeded`], which also stops parents that loop back.
+    /// 8 by default.
+    pub fn forward_depth(self, forward_depth: usize) -> Self {
+        Self { forward_depth, ..self }
+    }
+    
+    /// How long [`GenericMediator::consult_idempotent`] remembers an id, and how many ids at
+    /// most, forgetting the oldest first. A minute and 1024 ids by default.
+    pub fn dedupe(self, window: Duration, capacity: usize) -> Self {
+        Self { dedupe: (window, capacity), ..self }
+    }
+    
+    /// Fails consultations fast with [`Error::CircuitOpen`] once a recipient has rejected
+    /// `failure_threshold` of them in a row, until `reset_timeout` has passed by the [`Clock`].
+    /// Then the next consultation is let through on trial: if it's read the circuit closes,
+    /// if it's rejected again the circuit opens for another `reset_timeout`.
+    pub fn circuit_breaker(self, failure_threshold: u32, reset_timeout: Duration) -> Self {
+        Self { breaker: Some((failure_threshold, reset_timeout)), ..self }
+    }
+    
+    pub fn build(self) -> GenericMediator<C, R> {
+        let pipeline = Pipeline::new(
+            Quotas::new(self.quota_window),
+            self.breaker.map(|(threshold, reset_after)| Breakers::new(threshold, reset_after)),
+            DeadletterQueue::new(self.deadletter, self.deadletter_capacity, self.retry),
+            InFlight::new(self.ack_store.clone()),
+        );
+        #[cfg(feature = "tokio")]
+        let pipeline = pipeline.with_remote(self.remote);
+        GenericMediator {
+            colleagues: Arc::new(RwLock::new(R::with_capacity(self.capacity))),
+            weak: Arc::default(),
+            groups: Arc::default(),
+            tags: Arc::default(),
+            roles: Arc::default(),
+            fallback: Arc::default(),
+            router: Arc::default(),
+            filters: Arc::default(),
+            transforms: Arc::default(),
+            taps: Arc::default(),
+            stamper: Arc::default(),
+            scheduled: Arc::default(),
+            activity: Arc::default(),
+            ranks: Arc::default(),
+            shutdown_ranks: Arc::default(),
+            paused: Arc::default(),
+            registrations: Arc::default(),
+            watchers: Arc::default(),
+            pipeline: Arc::new(pipeline),
+            patterns: Arc::default(),
+            sequence: Arc::default(),
+            shutting_down: Arc::default(),
+            metrics: Arc::default(),
+            events: self.events,
+            sizer: self.sizer,
+            clock: self.clock,
+            allow_self_send: self.allow_self_send,
+            id_order: self.id_order,
+            observer: self.observer,
+            ack_store: self.ack_store,
+            middlewares: self.middlewares,
+            parent: self.parent,
+            bridge: Arc::default(),
+            forward_depth: self.forward_depth,
+            dedupe: Arc::new(Dedupe::new(self.dedupe.0, self.dedupe.1)),
+        }
+    }
+}
+
+impl<C: Colleague, R> GenericMediator<C, R> {
+    /// Installs or, with `None`, removes the mediator's [`Bridge`](crate::Bridge).
+    pub(crate) fn link(&self, link: Option<Link<C>>) {
+        *lock::recover_write(&self.bridge) = link;
+    }
+}
+
+impl<C: Colleague, R> Clone for GenericMediator<C, R> {
+    fn clone(&self) -> Self {
+        Self {
+            colleagues: Arc::clone(&self.colleagues),
+            weak: Arc::clone(&self.weak),
+            groups: Arc::clone(&self.groups),
+            tags: Arc::clone(&self.tags),
+            roles: Arc::clone(&self.roles),
+            fallback: Arc::clone(&self.fallback),
+            router: Arc::clone(&self.router),
+            filters: Arc::clone(&self.filters),
+            transforms: Arc::clone(&self.transforms),
+            taps: Arc::clone(&self.taps),
+            stamper: Arc::clone(&self.stamper),
+            scheduled: Arc::clone(&self.scheduled),
+            activity: Arc::clone(&self.activity),
+            ranks: Arc::clone(&self.ranks),
+            shutdown_ranks: Arc::clone(&self.shutdown_ranks),
+            paused: Arc::clone(&self.paused),
+            registrations: Arc::clone(&self.registrations),
+            watchers: Arc::clone(&self.watchers),
+            pipeline: Arc::clone(&self.pipeline),
+            patterns: Arc::clone(&self.patterns),
+            sequence: Arc::clone(&self.sequence),
+            shutting_down: Arc::clone(&self.shutting_down),
+            metrics: Arc::clone(&self.metrics),
+            events: self.events.clone(),
+            sizer: self.sizer,
+            clock: Arc::clone(&self.clock),
+            allow_self_send: self.allow_self_send,
+            id_order: self.id_order,
+            observer: self.observer.clone(),
+            ack_store: self.ack_store.clone(),
+            middlewares: self.middlewares.clone(),
+            parent: self.parent.clone(),
+            bridge: Arc::clone(&self.bridge),
+            forward_depth: self.forward_depth,
+            dedupe: Arc::clone(&self.dedupe),
+        }
+    }
+}
+
+/// Lists the registered ids and the number of queued deadletters rather than the colleagues
+/// themselves. Nothing waits for a lock: whatever is locked at the time shows as `<locked>`.
+impl<C: Colleague, R: Registry<C::Identifier, Registered<C>>> Debug for GenericMediator<C, R>
+    where C::Identifier: Debug
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut debug = f.debug_struct("GenericMediator");
+        match lock::try_read(&self.colleagues) {
+            Some(colleagues) => debug.field("ids", &colleagues.keys().collect::<Vec<_>>()),
+            None => debug.field("ids", &format_args!("<locked>")),
+        };
+        match self.pipeline.deadletters.try_len() {
+            Some(deadletters) => debug.field("deadletters", &deadletters),
+            None => debug.field("deadletters", &format_args!("<locked>")),
+        };
+        debug.finish_non_exhaustive()
+    }
+}
+
+/// Registers every pair, replacing whatever was registered under the same id; a replaced
+/// colleague is deregistered first, and recorded as such in the event log. Colleagues keep
+/// talking to the mediator they [belong to](Colleague::belong_to), so build them from a clone
+/// of the one being extended. Once the mediator is [shut down](GenericMediator::shutdown)
+/// nothing is registered, as [`Mediator::register`] would refuse with [`Error::ShuttingDown`].
+impl<C, R> Extend<(C::Identifier, Managed<C>)> for GenericMediator<C, R>
+    where C: Colleague,
+          R: Registry<C::Identifier, Registered<C>>,
+          C::Identifier: Eq + Hash + Clone + Debug
+{
+    fn extend<I: IntoIterator<Item = (C::Identifier, Managed<C>)>>(&mut self, iter: I) {
+        // Collected first so the iterator is free to use the mediator itself.
+        let items: Vec<_> = iter.into_iter().collect();
+        let mut colleagues = self.write_colleagues();
+        if self.ensure_running().is_err() {
+            return;
+        }
+        let mut ids = Vec::with_capacity(items.len());
+        for (id, managed) in items {
+            let replaced = colleagues.insert(id.clone(), Registered::new(managed)).is_some();
+            if replaced {
+                self.record(|_| EventKind::Deregistered { id: id.clone() });
+            }
+            self.record(|_| EventKind::Registered { id: id.clone(), weak: false });
+            ids.push((id, replaced));
+        }
+        drop(colleagues);
+        
+        for (id, replaced) in &ids {
+            if *replaced {
+                self.forget(id);
+                self.left(id);
+            }
+            self.joined(id);
+        }
+    }
+}
+
+/// Collects into a fresh mediator; see the [`Extend`] impl for which mediator the collected
+/// colleagues end up consulting.
+impl<C, R> FromIterator<(C::Identifier, Managed<C>)> for GenericMediator<C, R>
+    where C: Colleague,
+          R: Registry<C::Identifier, Registered<C>>,
+          C::Identifier: Eq + Hash + Clone + Debug
+{
+    fn from_iter<I: IntoIterator<Item = (C::Identifier, Managed<C>)>>(iter: I) -> Self {
+        let mut mediator = Self::default();
+        mediator.extend(iter);
+        mediator
+    }
+}
+
+impl<C: Colleague, R> sealed::Sealed for GenericMediator<C, R> {}
+
+impl<C, R> sealed::ConsultAs<C> for GenericMediator<C, R>
+    where C: Colleague,
+          R: Registry<C::Identifier, Registered<C>>,
+          C::Identifier: Eq + Hash + Clone + Debug,
+          C::Request: Debug
+{
+    fn consultation_as(&self, _: sealed::Token, from: &C::Identifier, to: &C::Identifier, msg: C::Message) -> Result<DeliveryOutcome, Error> {
+        self.consult(from, to, Message::new(msg), Consult::default())
+    }
+}
+
+impl<C, R> Mediator<C> for GenericMediator<C, R>
+    where C: Colleague,
+          R: Registry<C::Identifier, Registered<C>>,
+          C::Identifier: Eq + Hash + Clone + Debug,
+          C::Request: Debug
+{
+    fn register(&self, id: C::Identifier, registered: Managed<C>) -> Result<Registered<C>, Error> {
+        self.insert_new(self.write_colleagues(), id, registered)
+    }
+    
+    fn register_if_absent(&self, id: C::Identifier, registered: Managed<C>) -> Result<Registered<C>, Error> {
+        let mut colleagues = self.write_colleagues();
+        self.ensure_running()?;
+        
+        if let Some(existing) = colleagues.get(&id) {
+            return Ok(existing.clone());
+        }
+        let reg = Registered::new(registered);
+        self.record(|_| EventKind::Registered { id: id.clone(), weak: false });
+        colleagues.insert(id.clone(), reg.clone());
+        drop(colleagues);
+        
+        self.joined(&id);
+        Ok(reg)
+    }
+    
+    fn re_register(&self, id: C::Identifier, registered: Managed<C>) -> Result<(Registered<C>, Option<Registered<C>>), Error> {
+        let mut colleagues = self.write_colleagues();
+        self.ensure_running()?;
+        
+        let reg = Registered::new(registered);
+        self.record(|_| EventKind::Registered { id: id.clone(), weak: false });
+        let previous = colleagues.insert(id.clone(), reg.clone());
+        drop(colleagues);
+        
+        self.joined(&id);
+        Ok((reg, previous))
+    }
+    
+    fn deregister(&self, id: &C::Identifier) -> Result<Option<Registered<C>>, Error> {
+        let mut colleagues = self.write_colleagues();
+        let removed = colleagues.remove(id);
+        // A weak registration is deregistered the same way, as long as its colleague is alive.
+        let weak = lock::recover_write(&self.weak)
+            .remove(id)
+            .and_then(|entry| entry.upgrade());
+        drop(colleagues);
+        let removed = removed.or(weak);
+        self.forget(id);
+        if removed.is_some() {
+            self.record(|_| EventKind::Deregistered { id: id.clone() });
+            self.left(id);
+        }
+        Ok(removed)
+    }
+    
+    fn rename(&self, old: &C::Identifier, new: C::Identifier) -> Result<(), Error> {
+        let mut colleagues = self.write_colleagues();
+        self.ensure_running()?;
+        
+        let mut weak = lock::recover_write(&self.weak);
+        if colleagues.contains_key(&new) || weak.get(&new).is_some_and(|entry| entry.strong_count() > 0) {
+            return Err(Error::AlreadyRegistered { id: format!("{:?}", new) });
+        }
+        match colleagues.remove(old) {
+            Some(reg) => drop(colleagues.insert(new.clone(), reg)),
+            None => {
+                let entry = weak.remove(old)
+                    .filter(|entry| entry.strong_count() > 0)
+                    .ok_or_else(|| Error::NotRegistered { id: format!("{:?}", old) })?;
+                weak.insert(new.clone(), entry);
+            }
+        }
+        self.record(|_| EventKind::Renamed { old: old.clone(), new: new.clone() });
+        drop(weak);
+        drop(colleagues);
+        
+        self.moved(old, &new);
+        Ok(())
+    }
+    
+    fn clear(&self) -> Result<usize, Error> {
+        let mut colleagues = self.write_colleagues();
+        let weak = std::mem::take(&mut *lock::recover_write(&self.weak));
+        lock::recover_write(&self.patterns).clear();
+        lock::recover_write(&self.tags).clear();
+        lock::recover_write(&self.roles).clear();
+        
+        let (alive, gone): (Vec<_>, Vec<_>) = weak.into_iter().partition(|(_, entry)| entry.strong_count() > 0);
+        let removed: Vec<_> = std::mem::take(&mut *colleagues).into_iter().map(|(id, _)| id)
+            .chain(alive.into_iter().map(|(id, _)| id))
+            .collect();
+        drop(colleagues);
+        
+        // Weak registrations whose colleague is gone were never around to be told they left.
+        for (id, _) in gone {
+            self.record(|_| EventKind::Deregistered { id });
+        }
+        for id in &removed {
+            self.record(|_| EventKind::Deregistered { id: id.clone() });
+            self.left(id);
+        }
+        Ok(removed.len())
+    }
+    
+    fn is_registered(&self, id: &C::Identifier) -> Result<bool, Error> {
+        Ok(self.lookup_exact(id).is_some())
+    }
+    
+    fn get(&self, id: &C::Identifier) -> Result<Option<Registered<C>>, Error> {
+        Ok(self.lookup_exact(id))
+    }
+    
+    fn registered_count(&self) -> Result<usize, Error> {
+        let count = self.read_colleagues()
+            .len();
+        Ok(count)
+    }
+    
+    fn registered_ids(&self) -> Result<Vec<C::Identifier>, Error> {
+        let ids = self.read_colleagues()
+            .keys()
+            .cloned()
+            .collect();
+        Ok(ids)
+    }
+    
+    fn entries(&self) -> Result<Vec<(C::Identifier, Registered<C>)>, Error> {
+        let entries = self.read_colleagues()
+            .iter()
+            .map(|(id, colleague)| (id.clone(), colleague.clone()))
+            .collect();
+        Ok(entries)
+    }
+
+    fn try_consultation(&self, from: &Registered<C>, to: &C::Identifier, msg: C::Message) -> Result<bool, Error> {
+        match self.consult(from.id(), to, Message::new(msg), Consult { quiet: true, ..Consult::default() }) {
+            Ok(outcome) => Ok(matches!(outcome, DeliveryOutcome::Delivered(_))),
+            Err(e @ Error::LockPoison { .. }) => Err(e),
+            Err(_) => Ok(false),
+        }
+    }
+    
+    fn request(&self, from: &Registered<C>, to: &C::Identifier, req: C::Request) -> Result<C::Response, Error> {
+        self.ensure_running()?;
+        
+        let colleague = self.lookup(from.id(), to)
+            .ok_or_else(|| Error::NotRegistered { id: format!("{:?}", to) })?;
+        
+        #[cfg(feature = "tracing")]
+        tracing::debug!(from = ?from.id(), to = ?to, request = ?req, "request");
+        self.guarded(to, &colleague, || Ok(colleague.handle_request(req)))?
+            .map_err(|e| Error::DeliveryRejected { id: format!("{:?}", to), reason: e.reason })
+    }
+    
+    fn broadcast(&self, from: &Registered<C>, msg: C::Message) -> Result<usize, Error>
+        where C::Message: Clone
+    {
+        self.ensure_running()?;
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("broadcast", from = ?from.id()).entered();
+        
+        let sent = self.events.as_ref().map(|events| events.copy(&msg));
+        let msg = self.stamped(from.id(), msg);
+        
+        let mut reached = 0;
+        for (id, colleague) in self.broadcast_order(from.id()) {
+            let Ok(msg) = self.through_middlewares(from.id(), &id, msg.clone()) else {
+                continue;
+            };
+            if !self.interested(&id, &msg) {
+                continue;
+            }
+            if let Ok(Ok(true)) = self.hand_over(from.id(), &id, &colleague, Message::new(msg), Delivery::default(), false) {
+                reached += 1;
+            }
+        }
+        
+        self.metrics.fanned_out(reached);
+        if let Some(msg) = sent {
+            self.record(|_| EventKind::Broadcast { from: from.id().clone(), msg, reached });
+        }
+        Ok(reached)
+    }
+}
+
+/// Moves the entry under `old`, if any, to `new`.
+fn rekey<K: Eq + Hash + Clone, V>(map: &mut HashMap<K, V>, old: &K, new: &K) {
+    if let Some(moved) = map.remove(old) {
+        map.insert(new.clone(), moved);
+    }
+}
+
+/// Weight of `worker` for `key` in rendezvous hashing: the worker weighing the most gets the key.
+fn rendezvous<K: Hash + ?Sized, W: Hash>(key: &K, worker: &W) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    worker.hash(&mut hasher);
+    hasher.finish()
+}
@@ -0,0 +1,167 @@
+use std::collections::VecDeque;
+use std::sync::{mpsc, Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crate::error::ColleagueError;
+
+/// Fan-in queue of a colleague registered with
+/// [`GenericMediator::register_inbox`](crate::GenericMediator::register_inbox). Consultations
+/// from any number of threads only push into it; the colleague consumes them on a loop of its
+/// own, so its handling never runs on a sender's thread.
+pub struct Inbox<M> {
+    shared: Arc<Shared<M>>,
+}
+
+struct Shared<M> {
+    state: Mutex<State<M>>,
+    ready: Condvar,
+    capacity: Option<usize>,
+}
+
+struct State<M> {
+    queue: VecDeque<M>,
+    /// The colleague's registration is gone for good, so nothing is ever pushed again.
+    closed: bool,
+    /// The [`Inbox`] is gone, so nothing pushed would ever be read.
+    abandoned: bool,
+}
+
+impl<M> Inbox<M> {
+    pub(crate) fn new(capacity: Option<usize>) -> (Self, InboxSender<M>) {
+        let state = State { queue: VecDeque::new(), closed: false, abandoned: false };
+        let shared = Arc::new(Shared { state: Mutex::new(state), ready: Condvar::new(), capacity });
+        (Self { shared: Arc::clone(&shared) }, InboxSender { shared })
+    }
+    
+    /// Waits for the next message. `None` once the inbox is empty and every handle of the
+    /// colleague's registration is gone.
+    pub fn recv(&self) -> Option<M> {
+        let mut state = self.shared.lock();
+        loop {
+            if let Some(msg) = state.queue.pop_front() {
+                return Some(msg);
+            }
+            if state.closed {
+                return None;
+            }
+            state = self.shared.ready.wait(state).unwrap_or_else(|poisoned| poisoned.into_inner());
+        }
+    }
+    
+    /// Like [`Inbox::recv`] but gives up with `None` once `timeout` has elapsed.
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<M> {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.shared.lock();
+        loop {
+            if let Some(msg) = state.queue.pop_front() {
+                return Some(msg);
+            }
+            let left = deadline.saturating_duration_since(Instant::now());
+            if state.closed || left.is_zero() {
+                return None;
+            }
+            state = self.shared.ready.wait_timeout(state, left)
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .0;
+        }
+    }
+    
+    pub fn try_recv(&self) -> Option<M> {
+        self.shared.lock().queue.pop_front()
+    }
+    
+    /// Blocking iterator over the messages, ending like [`Inbox::recv`] does.
+    pub fn iter(&self) -> impl Iterator<Item = M> + '_ {
+        std::iter::from_fn(|| self.recv())
+    }
+    
+    pub fn len(&self) -> usize {
+        self.shared.lock().queue.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<M> Drop for Inbox<M> {
+    fn drop(&mut self) {
+        self.shared.lock().abandoned = true;
+    }
+}
+
+impl<M> Shared<M> {
+    fn lock(&self) -> MutexGuard<'_, State<M>> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// The colleague's end of an [`Inbox`], closing it once the registration is dropped.
+pub(crate) struct InboxSender<M> {
+    shared: Arc<Shared<M>>,
+}
+
+impl<M> Drop for InboxSender<M> {
+    fn drop(&mut self) {
+        self.shared.lock().closed = true;
+        self.shared.ready.notify_all();
+    }
+}
+
+/// Where a pull-style colleague's deliveries go instead of `read_msg`.
+pub(crate) enum Outlet<M> {
+    Channel(mpsc::Sender<M>),
+    Inbox(InboxSender<M>),
+    #[cfg(feature = "tokio")]
+    Stream(tokio::sync::mpsc::UnboundedSender<M>),
+}
+
+impl<M> Outlet<M> {
+    /// Whether the consumer is gone, so nothing pushed would ever be read.
+    pub(crate) fn is_closed(&self) -> bool {
+        match self {
+            Outlet::Channel(_) => false,
+            #[cfg(feature = "tokio")]
+            Outlet::Stream(stream) => stream.is_closed(),
+            Outlet::Inbox(inbox) => inbox.shared.lock().abandoned,
+        }
+    }
+    
+    pub(crate) fn is_full(&self) -> bool {
+        match self {
+            Outlet::Inbox(inbox) => inbox.shared.capacity.is_some_and(|capacity| inbox.shared.lock().queue.len() >= capacity),
+            _ => false,
+        }
+    }
+    
+    /// Hands `msg` over to the consumer, returning `Ok(false)` if a bounded inbox is full,
+    /// in which case `on_accept` is not called.
+    pub(crate) fn push(&self, msg: M, on_accept: impl FnOnce(&M)) -> Result<bool, ColleagueError> {
+        match self {
+            Outlet::Channel(channel) => {
+                on_accept(&msg);
+                channel.send(msg)
+                    .map_err(|_| ColleagueError::new("the receiving end of the channel is gone"))?;
+            }
+            #[cfg(feature = "tokio")]
+            Outlet::Stream(stream) => {
+                on_accept(&msg);
+                stream.send(msg)
+                    .map_err(|_| ColleagueError::new("the stream is no longer polled"))?;
+            }
+            Outlet::Inbox(inbox) => {
+                let mut state = inbox.shared.lock();
+                if state.abandoned {
+                    return Err(ColleagueError::new("the inbox is no longer read"));
+                }
+                if inbox.shared.capacity.is_some_and(|capacity| state.queue.len() >= capacity) {
+                    return Ok(false);
+                }
+                on_accept(&msg);
+                state.queue.push_back(msg);
+                inbox.shared.ready.notify_one();
+            }
+        }
+        Ok(true)
+    }
+}
@@ -0,0 +1,113 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "std")]
+extern crate self as mediator_play;
+
+#[cfg(feature = "no-std")]
+extern crate alloc;
+
+// Everything built on `std`; without the `std` feature only `embedded` is left.
+macro_rules! with_std {
+    ($($item:item)*) => {
+        $(#[cfg(feature = "std")] $item)*
+    };
+}
+
+with_std! {
+    mod ack;
+    mod any_colleague;
+    mod audit;
+    mod bot;
+    mod breaker;
+    mod bridge;
+    mod clock;
+    mod colleague;
+    mod deadletter;
+    mod decorate;
+    mod dedupe;
+    mod error;
+    mod event;
+    mod generic;
+    mod group;
+    mod inbox;
+    mod lock;
+    mod mailbox;
+    mod mapped;
+    mod mediator;
+    mod message;
+    mod metrics;
+    mod middleware;
+    mod observer;
+    mod pattern;
+    mod pipeline;
+    mod quota;
+    mod registry;
+    mod reliable;
+    mod reply;
+    mod router;
+    mod schedule;
+    mod scoped;
+    mod sender;
+    mod service;
+    mod shutdown;
+    mod snapshot;
+    mod turnstile;
+    mod user;
+    mod watch;
+}
+
+#[cfg(feature = "tokio")]
+mod asynchronous;
+#[cfg(feature = "tokio")]
+mod transport;
+#[cfg(feature = "no-std")]
+pub mod embedded;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "redis")]
+mod redis;
+#[cfg(feature = "std")]
+pub mod testing;
+
+with_std! {
+    pub use self::ack::Ack;
+    pub use self::any_colleague::*;
+    pub use self::audit::*;
+    pub use self::bot::*;
+    pub use self::bridge::Bridge;
+    pub use self::clock::*;
+    pub use self::colleague::*;
+    pub use self::deadletter::*;
+    pub use self::decorate::Decorate;
+    pub use self::error::*;
+    pub use self::event::{Event, EventKind};
+    pub use self::generic::*;
+    pub use self::group::*;
+    pub use self::inbox::Inbox;
+    pub use self::mapped::MappedMediator;
+    pub use self::mediator::*;
+    pub use self::message::*;
+    pub use self::metrics::{LatencyStats, MetricsSnapshot};
+    pub use self::middleware::*;
+    pub use self::observer::*;
+    pub use self::registry::Registry;
+    pub use self::reliable::AckHandle;
+    pub use self::reply::*;
+    pub use self::router::Router;
+    pub use self::scoped::*;
+    pub use self::sender::*;
+    pub use self::service::MediatorService;
+    pub use self::shutdown::*;
+    pub use self::snapshot::*;
+    pub use self::user::*;
+    pub use self::watch::WatchHandle;
+    
+    pub use mediator_derive::Colleague;
+}
+
+#[cfg(feature = "tokio")]
+pub use self::asynchronous::*;
+#[cfg(feature = "tokio")]
+pub use self::transport::Transport;
+#[cfg(feature = "redis")]
+pub use self::redis::RedisTransport;
\ No newline at end of file
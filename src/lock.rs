@@ -0,0 +1,95 @@
+//! Lock backend shared by the mediators: `std::sync::RwLock` by default,
+//! `parking_lot::RwLock` with the `parking_lot` feature.
+
+#[cfg(not(feature = "parking_lot"))]
+pub(crate) use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+#[cfg(not(feature = "parking_lot"))]
+use std::sync::TryLockError;
+
+#[cfg(feature = "parking_lot")]
+pub(crate) use parking_lot::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use crate::error::{Error, Operation};
+
+// A panic while a registry lock was held leaves the map itself intact,
+// so recover the guard instead of failing every later call with `LockPoison`.
+#[cfg(not(feature = "parking_lot"))]
+pub(crate) fn recover_read<T>(lock: &RwLock<T>) -> RwLockReadGuard<'_, T> {
+    lock.read().unwrap_or_else(|poisoned| {
+        #[cfg(feature = "tracing")]
+        tracing::warn!("recovered from a poisoned registry lock");
+        lock.clear_poison();
+        poisoned.into_inner()
+    })
+}
+
+#[cfg(not(feature = "parking_lot"))]
+pub(crate) fn recover_write<T>(lock: &RwLock<T>) -> RwLockWriteGuard<'_, T> {
+    lock.write().unwrap_or_else(|poisoned| {
+        #[cfg(feature = "tracing")]
+        tracing::warn!("recovered from a poisoned registry lock");
+        lock.clear_poison();
+        poisoned.into_inner()
+    })
+}
+
+/// `None` instead of waiting if the lock is taken, e.g. for formatting that must never block.
+#[cfg(not(feature = "parking_lot"))]
+pub(crate) fn try_read<T>(lock: &RwLock<T>) -> Option<RwLockReadGuard<'_, T>> {
+    match lock.try_read() {
+        Ok(guard) => Some(guard),
+        Err(TryLockError::Poisoned(poisoned)) => Some(poisoned.into_inner()),
+        Err(TryLockError::WouldBlock) => None,
+    }
+}
+
+/// `None` instead of waiting if the lock is taken.
+#[cfg(not(feature = "parking_lot"))]
+pub(crate) fn try_write<T>(lock: &RwLock<T>) -> Option<RwLockWriteGuard<'_, T>> {
+    match lock.try_write() {
+        Ok(guard) => Some(guard),
+        Err(TryLockError::Poisoned(poisoned)) => Some(poisoned.into_inner()),
+        Err(TryLockError::WouldBlock) => None,
+    }
+}
+
+#[cfg(not(feature = "parking_lot"))]
+pub(crate) fn read<T>(lock: &RwLock<T>, during: Operation) -> Result<RwLockReadGuard<'_, T>, Error> {
+    lock.read().map_err(|_| Error::LockPoison { during })
+}
+
+#[cfg(not(feature = "parking_lot"))]
+pub(crate) fn write<T>(lock: &RwLock<T>, during: Operation) -> Result<RwLockWriteGuard<'_, T>, Error> {
+    lock.write().map_err(|_| Error::LockPoison { during })
+}
+
+#[cfg(feature = "parking_lot")]
+pub(crate) fn recover_read<T>(lock: &RwLock<T>) -> RwLockReadGuard<'_, T> {
+    lock.read()
+}
+
+#[cfg(feature = "parking_lot")]
+pub(crate) fn recover_write<T>(lock: &RwLock<T>) -> RwLockWriteGuard<'_, T> {
+    lock.write()
+}
+
+#[cfg(feature = "parking_lot")]
+pub(crate) fn try_read<T>(lock: &RwLock<T>) -> Option<RwLockReadGuard<'_, T>> {
+    lock.try_read()
+}
+
+#[cfg(feature = "parking_lot")]
+pub(crate) fn try_write<T>(lock: &RwLock<T>) -> Option<RwLockWriteGuard<'_, T>> {
+    lock.try_write()
+}
+
+// parking_lot never poisons, so these never produce `Error::LockPoison`.
+#[cfg(feature = "parking_lot")]
+pub(crate) fn read<T>(lock: &RwLock<T>, _: Operation) -> Result<RwLockReadGuard<'_, T>, Error> {
+    Ok(lock.read())
+}
+
+#[cfg(feature = "parking_lot")]
+pub(crate) fn write<T>(lock: &RwLock<T>, _: Operation) -> Result<RwLockWriteGuard<'_, T>, Error> {
+    Ok(lock.write())
+}
@@ -0,0 +1,82 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::sync::{Mutex, MutexGuard};
+
+use crate::message::Priority;
+
+/// Bounded queue a colleague pulls its messages from instead of having `read_msg` invoked
+/// on the sender's call stack. Messages come out by [`Priority`], oldest first within a level.
+pub(crate) struct Mailbox<M> {
+    queue: Mutex<Queue<M>>,
+    capacity: usize,
+}
+
+struct Queue<M> {
+    heap: BinaryHeap<Queued<M>>,
+    pushed: u64,
+}
+
+struct Queued<M> {
+    priority: Priority,
+    seq: u64,
+    msg: M,
+}
+
+impl<M> Mailbox<M> {
+    pub(crate) fn new(capacity: usize) -> Self {
+        let queue = Queue { heap: BinaryHeap::with_capacity(capacity), pushed: 0 };
+        Self { queue: Mutex::new(queue), capacity }
+    }
+    
+    /// Enqueues `msg` unless the mailbox is full; `on_accept` runs before the push
+    /// while the queue is still locked.
+    pub(crate) fn push(&self, msg: M, priority: Priority, on_accept: impl FnOnce(&M)) -> bool {
+        let mut queue = self.lock();
+        if queue.heap.len() >= self.capacity {
+            return false;
+        }
+        on_accept(&msg);
+        let seq = queue.pushed;
+        queue.pushed += 1;
+        queue.heap.push(Queued { priority, seq, msg });
+        true
+    }
+    
+    pub(crate) fn is_full(&self) -> bool {
+        self.lock().heap.len() >= self.capacity
+    }
+    
+    pub(crate) fn len(&self) -> usize {
+        self.lock().heap.len()
+    }
+    
+    pub(crate) fn pop(&self) -> Option<M> {
+        self.lock().heap.pop().map(|queued| queued.msg)
+    }
+    
+    fn lock(&self) -> MutexGuard<'_, Queue<M>> {
+        self.queue.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl<M> PartialEq for Queued<M> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<M> Eq for Queued<M> {}
+
+impl<M> PartialOrd for Queued<M> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<M> Ord for Queued<M> {
+    // `BinaryHeap` pops the greatest entry: highest priority, then the lowest sequence number.
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority.cmp(&other.priority)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
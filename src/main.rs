@@ -1,173 +1,428 @@
-use std::collections::HashMap;
-use std::fmt::{Debug, Formatter};
-use std::ops::Deref;
-use std::rc::Rc;
-use std::sync::RwLock;
+use std::collections::HashSet;
+use std::ops::ControlFlow;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+use std::time::Duration;
 
+use mediator_play::{AnyId, AnyMediator, Bot, BotCommand, BotId, BotMediator, Bridge, Clock, Colleague, DeadletterPolicy, Decorate, Error, GenericMediator, GroupId, Mediator, MediatorService, Message, MessageId, Middleware, MiddlewareResult, MockClock, PrintlnObserver, Priority, RetryPolicy, Router, Tapped, User, UserId, UserMediator, UserRequest, UserResponse};
 
 fn main() -> Result<(), Error> {
     let user_id1 = UserId::new("user-1");
     let user_id2 = UserId::new("user-2");
     
-    let user1 = User { id: user_id1.clone() };
-    let user2 = User { id: user_id2.clone() };
+    let user1 = User::new(user_id1.clone());
+    let user2 = User::new(user_id2.clone());
     
-    let mut mediator = UserMediator::default();
+    let mediator = UserMediator::default()
+        .observed_by(PrintlnObserver)
+        .with_middleware(Redact);
     
     let managed1 = user1.belong_to(mediator.clone());
     let managed2 = user2.belong_to(mediator.clone());
     
-    let reg1= mediator.register(user_id1.clone(), managed1)?;
+    mediator.register(user_id1.clone(), managed1)?;
     let reg2 = mediator.register(user_id2.clone(), managed2)?;
     
-    reg1.send_msg(&user_id2, "hi".to_string())?;
-    reg2.send_msg(&user_id1, "hello".to_string())?;
+    if let Err(e) = mediator.register(user_id1.clone(), User::new(user_id1.clone()).belong_to(mediator.clone())) {
+        println!("[Main] {}", e);
+    }
+    let (reg1, old) = mediator.takeover(user_id1.clone(), User::new(user_id1.clone()).belong_to(mediator.clone()))?;
+    println!("[Main] user-1 reconnected, old session handed back: {}", old.is_some());
+    
+    mediator.register_if_absent(user_id1.clone(), User::new(user_id1.clone()).belong_to(mediator.clone()))?;
+    println!("[Main] still {} colleague(s) after register_if_absent", mediator.registered_count()?);
+    
+    let helper = reg1.mediator();
+    let user_id7 = UserId::new("user-7");
+    helper.register(user_id7.clone(), User::new(user_id7.clone()).belong_to(helper.clone()))?;
+    let (observer, id) = (mediator.clone(), user_id7.clone());
+    let visible = thread::spawn(move || observer.is_registered(&id))
+        .join()
+        .expect("observing thread panicked")?;
+    println!("[Main] user-7 registered through a helper, visible from another clone: {}", visible);
+    
+    let user_id9 = UserId::new("user-9");
+    let visitor = mediator.register(user_id9.clone(), User::new(user_id9.clone()).belong_to(mediator.clone()))?.scoped();
+    reg1.send_msg(&user_id9, "welcome, visitor")?;
+    let remembered = visitor.downgrade();
+    drop(visitor);
+    println!("[Main] user-9 still registered after its scoped handle dropped: {}, still alive: {}",
+        mediator.is_registered(&user_id9)?, remembered.upgrade().is_some());
+    
+    let first = reg1.send_msg(&user_id2, "hi")?;
+    let second = reg2.send_msg(&user_id1, "hello")?;
+    println!("[Main] sent {:?} then {:?}", first, second);
+    reg2.send_msg(&user_id1, "my password is secret")?;
+    mediator.set_transform(user_id2.clone(), |msg| msg.to_uppercase())?;
+    reg1.send_msg(&user_id2, "read this loud")?;
+    reg2.send_msg(&user_id1, "read this as sent")?;
+    mediator.clear_transform(&user_id2)?;
+    mediator.set_stamper(|from, msg| format!("{} (signed {})", msg, from.as_ref()))?;
+    reg1.send_msg(&user_id2, "stamped on the way out")?;
+    mediator.clear_stamper()?;
+    mediator.consult_with_headers(&reg2, &user_id1, Message::new("traced hello".to_string()).with_header("trace-id", "abc-123"))?;
+    if let Err(e) = mediator.consult_with_headers(&reg2, &user_id1, Message::new("hello from the future".to_string()).with_version(2)) {
+        println!("[Main] {}", e);
+    }
+    let mut service = MediatorService::new(&reg2, user_id1.clone());
+    if let Poll::Ready(Ok(())) = service.poll_ready(&mut Context::from_waker(Waker::noop())) {
+        println!("[Main] service call: {:?}", service.call("through the service".to_string()).into_inner()?);
+    }
+    
+    let moved = reg2.clone();
+    let to = user_id1.clone();
+    thread::spawn(move || moved.send_msg(&to, "from another thread").map(drop))
+        .join()
+        .expect("sender thread panicked")?;
+    
+    let UserResponse::MessageCount(count) = reg1.ask(&user_id2, UserRequest::MessageCount)?;
+    println!("[Main] user-2 has read {} message(s)", count);
+    if let Some(user) = mediator.get(&user_id1)? {
+        println!("[Main] user-1 has read {} message(s)", user.received());
+    }
+    
+    if let Err(e) = reg1.send_msg(&user_id1, "talking to myself") {
+        println!("[Main] {}", e);
+    }
+    
+    let detached = reg2.sender();
+    thread::spawn(move || detached.send_msg(&UserId::new("user-1"), "sent through a detached sender").map(drop))
+        .join()
+        .expect("sender thread panicked")?;
     
     let user_id3 = UserId::new("user-3");
-    reg1.send_msg(&user_id3, "hi".to_string())?;
+    println!("[Main] quiet send to user-3 delivered: {}",
+        mediator.try_consultation(&reg1, &user_id3, "psst".to_string())?);
+    println!("[Main] user-2 registered: {}, user-3 registered: {}",
+        mediator.is_registered(&user_id2)?, mediator.is_registered(&user_id3)?);
+    if let Err(e) = reg1.send_msg(&user_id3, "hi") {
+        println!("[Main] {}", e);
+    }
     
-    Ok(())
-}
-
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
-pub struct UserId(String);
-
-impl UserId {
-    pub fn new(id: impl Into<String>) -> UserId {
-        Self(id.into())
+    let roll_call = mediator.consult_many(&reg1, &[user_id2.clone(), user_id3.clone()], "roll call".to_string());
+    println!("[Main] roll call delivered to {:?}", roll_call.delivered);
+    for (id, e) in &roll_call.failed {
+        println!("[Main] roll call to {:?} failed: {} ({:?} in io terms)", id, e, std::io::Error::from(e.clone()).kind());
     }
-}
-
-#[derive(Clone)]
-pub struct User {
-    id: UserId,
-}
-
-impl Debug for User {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "User id:{}", self.id.0)
+    
+    let reached = reg1.as_mediator().broadcast(&reg1, "everyone, listen up".to_string())?;
+    println!("[Main] broadcast reached {} colleague(s)", reached);
+    let minutes = "minutes of the last meeting, all of them".to_string();
+    let reached = mediator.broadcast_ref(&reg1, &minutes)?;
+    println!("[Main] broadcast by reference reached {} colleague(s)", reached);
+    mediator.set_filter(user_id7.clone(), |msg: &String| !msg.contains("spam"))?;
+    let reached = reg1.as_mediator().broadcast(&reg1, "spam, spam and eggs".to_string())?;
+    println!("[Main] spam broadcast reached {} colleague(s)", reached);
+    println!("[Main] spam sent straight to user-7: {:?}", reg1.send_msg(&user_id7, "more spam")?);
+    
+    let lobby = GroupId::new("lobby");
+    mediator.join_group(&user_id1, lobby.clone())?;
+    mediator.join_group(&user_id2, lobby.clone())?;
+    mediator.join_group(&user_id1, GroupId::new("admins"))?;
+    reg1.as_mediator().multicast(&reg1, &lobby, "welcome to the lobby".to_string())?;
+    
+    mediator.set_quota(&user_id2, 1)?;
+    reg2.send_msg(&user_id1, "one for the road")?;
+    if let Err(e) = reg2.send_msg(&user_id1, "and another") {
+        println!("[Main] {}", e);
     }
-}
-
-impl User {
-    pub fn read_msg(&self, msg: String) {
-        println!("[{}] {}", self.id.0, msg);
+    mediator.reset_quotas()?;
+    
+    let sevens = mediator.find(|user| user.id().as_str().ends_with('7'))?;
+    println!("[Main] found {:?} by predicate", sevens.iter().map(|user| user.id()).collect::<Vec<_>>());
+    let user_id12 = UserId::new("user-12");
+    let tags = HashSet::from(["admin".to_string(), "chat".to_string()]);
+    let _admin = mediator.register_tagged(user_id12.clone(), User::new(user_id12).belong_to(mediator.clone()), tags)?;
+    let admins = mediator.by_tag("admin")?;
+    println!("[Main] found {:?} by tag", admins.iter().map(|user| user.id()).collect::<Vec<_>>());
+    let root = UserId::new("root");
+    mediator.rename(admins[0].id(), root.clone())?;
+    reg1.send_msg(&root, "welcome under your new name")?;
+    for worker in ["worker-1", "worker-2"] {
+        mediator.register_worker("billing", User::new(UserId::new(worker)).belong_to(mediator.clone()))?;
     }
-}
-
-impl Registered<User> {
-    pub fn send_msg(&self, id: &UserId, msg: String) -> Result<(), Error> {
-        self.as_mediator().consultation(self, id, msg)?;
-        Ok(())
+    for session in ["alice", "bob", "alice", "bob"] {
+        mediator.consult_by_role(&reg1, "billing", session, format!("invoice for {}", session))?;
     }
-}
-
-impl Colleague for User {
-    type Identifier = UserId;
-    type Mediator = UserMediator;
+    let mut counts = mediator.scatter_gather(&reg1, "billing", UserRequest::MessageCount)?;
+    counts.sort_by_key(|UserResponse::MessageCount(count)| *count);
+    println!("[Main] billing answered {:?}, an empty role {:?}", counts, mediator.scatter_gather(&reg1, "nobody", UserRequest::MessageCount)?);
+    println!("[Main] {:?}", mediator.metrics());
+    println!("[Main] {} colleague(s) registered: {:?}", mediator.registered_count()?, mediator.registered_ids()?);
+    println!("[Main] {:?} on {:?}", reg1, mediator);
+    for (id, user) in mediator.entries()? {
+        println!("[Main] {:?} has read {} message(s)", id, user.received());
+    }
+    let mut visited = 0;
+    let found = mediator.for_each_entry(|id, _| {
+        visited += 1;
+        if id == &user_id2 { ControlFlow::Break(()) } else { ControlFlow::Continue(()) }
+    })?;
+    println!("[Main] found {:?}: {} after visiting {} colleague(s)", user_id2, found.is_break(), visited);
+    mediator.deregister(&user_id2)?;
+    println!("[Main] {} colleague(s) registered", mediator.registered_count()?);
+    if let Err(e) = reg1.send_msg(&user_id2, "are you still there?") {
+        println!("[Main] {}", e);
+    }
+    match reg2.try_into_inner() {
+        Ok(user) => println!("[Main] reclaimed {:?} after {} message(s)", user, user.received()),
+        Err(reg2) => println!("[Main] {:?} is still shared", reg2.id()),
+    }
+    let loner = User::new(UserId::new("loner")).belong_to(mediator.clone()).into_registered();
+    println!("[Main] standalone {:?}, registered: {}", *loner, mediator.is_registered(loner.id())?);
+    loner.send_msg(&user_id1, "hello from outside the registry")?;
+    
+    let anonymous: Vec<_> = (0..4)
+        .map(|_| {
+            let mediator = mediator.clone();
+            thread::spawn(move || mediator.register_anonymous().map(|(id, _)| id))
+        })
+        .collect();
+    let anonymous = anonymous.into_iter()
+        .map(|handle| handle.join().expect("registering thread panicked"))
+        .collect::<Result<HashSet<_>, _>>()?;
+    println!("[Main] registered {} distinct anonymous user(s)", anonymous.len());
     
-    fn id(&self) -> &Self::Identifier {
-        &self.id
+    let mut bulk = UserMediator::with_capacity(64);
+    let presized = bulk.capacity()?;
+    bulk.import_ids((0..64).map(|i| UserId::new(format!("bulk-{}", i))).collect())?;
+    println!("[Main] bulk capacity {} before and {} after 64 registrations", presized, bulk.capacity()?);
+    let reserved = UserMediator::default();
+    reserved.reserve(32)?;
+    let room = reserved.capacity()?;
+    let mut grew = false;
+    for i in 0..32 {
+        let id = UserId::new(format!("reserved-{}", i));
+        reserved.register(id.clone(), User::new(id).belong_to(reserved.clone()))?;
+        grew |= reserved.capacity()? != room;
     }
+    println!("[Main] reserved room for {} and grew while registering 32: {}", room, grew);
+    let longest = bulk.with_registry(|registry| registry.keys().map(|id| id.as_ref().len()).max().unwrap_or(0))?;
+    println!("[Main] longest bulk id is {} characters", longest);
+    let late = UserId::new("bulk-late");
+    let contended = bulk.with_registry(|_| bulk.try_register(late.clone(), User::new(late.clone()).belong_to(bulk.clone())).err())?;
+    println!("[Main] registering while the registry is read: {:?}", contended);
+    let pruned = bulk.deregister_where(|user| user.id().as_ref().ends_with('7'))?;
+    println!("[Main] pruned {} bulk user(s) ending in 7, {} left", pruned.len(), bulk.registered_count()?);
+    let overflow = UserMediator::default();
+    let moved = bulk.drain_into(&overflow)?;
+    println!("[Main] drained {} bulk user(s) into another mediator, {} left behind", moved, bulk.registered_count()?);
+    println!("[Main] draining a mediator into a clone of itself moves {}", overflow.drain_into(&overflow.clone())?);
     
-    fn belong_to(self, bus: Self::Mediator) -> Managed<Self> {
-        Managed::new(self, bus)
+    let capped = UserMediator::builder()
+        .deadletter(true)
+        .deadletter_capacity(2, DeadletterPolicy::DropOldest)
+        .build();
+    let sender = UserId::new("sender");
+    let sender = capped.register(sender.clone(), User::new(sender).belong_to(capped.clone()))?;
+    for msg in ["first", "second", "third"] {
+        sender.send_msg(&UserId::new("nobody"), msg)?;
     }
-}
-
-pub struct Managed<T: Colleague> {
-    inner: T,
-    mediator: T::Mediator
-}
-
-impl<T: Colleague> Managed<T> {
-    fn new(t: T, bus: T::Mediator) -> Self {
-        Self { inner: t, mediator: bus }
+    let kept: Vec<_> = capped.deadletters()?.into_iter().map(|letter| letter.msg).collect();
+    println!("[Main] capped deadletters kept {:?}", kept);
+    let picky = UserId::new("picky");
+    capped.register(picky.clone(), User::new(picky.clone()).belong_to(capped.clone()))?;
+    capped.set_filter(picky.clone(), |msg: &String| !msg.contains("spam"))?;
+    sender.send_msg(&picky, "spam")?;
+    let reasons: Vec<_> = capped.deadletters()?.into_iter().map(|letter| (letter.msg, letter.reason)).collect();
+    println!("[Main] deadletters and why: {:?}", reasons);
+    
+    let shouting = GenericMediator::<Decorate<User, fn(String) -> String>>::default();
+    let shout: fn(String) -> String = |msg| msg.to_uppercase();
+    let (calm, loud) = (UserId::new("calm"), UserId::new("loud"));
+    let calm = shouting.register(calm.clone(), Decorate::new(User::new(calm), shout).belong_to(shouting.clone()))?;
+    shouting.register(loud.clone(), Decorate::new(User::new(loud.clone()), shout).belong_to(shouting.clone()))?;
+    shouting.consultation(&calm, &loud, "keep it down".to_string())?;
+    
+    let desk = UserMediator::default();
+    let (triage, oncall, inbox) = (UserId::new("triage"), UserId::new("oncall"), UserId::new("inbox"));
+    let triage = desk.register(triage.clone(), User::new(triage).belong_to(desk.clone()))?;
+    desk.register(oncall.clone(), User::new(oncall.clone()).belong_to(desk.clone()))?;
+    desk.register(inbox.clone(), User::new(inbox.clone()).belong_to(desk.clone()))?;
+    desk.set_router(Router::new()
+        .rule(|msg: &String| msg.contains("urgent"), oncall)
+        .rule(|_| true, inbox))?;
+    desk.consult_routed(&triage, "urgent: the build is on fire".to_string())?;
+    desk.consult_routed(&triage, "lunch on friday?".to_string())?;
+    
+    let hq = UserMediator::default();
+    let desk = UserId::new("hq-desk");
+    hq.register(desk.clone(), User::new(desk.clone()).belong_to(hq.clone()))?;
+    let branch = UserMediator::builder().parent(hq.clone()).build();
+    let clerk = UserId::new("branch-clerk");
+    let clerk = branch.register(clerk.clone(), User::new(clerk).belong_to(branch.clone()))?;
+    println!("[Main] branch routes hq-desk {:?}", branch.route_preview(&desk)?);
+    clerk.send_msg(&desk, "escalating to headquarters")?;
+    
+    let east = UserMediator::default();
+    let west = UserMediator::default();
+    let (east_id, west_id) = (UserId::new("east-1"), UserId::new("west-1"));
+    let east_user = east.register(east_id.clone(), User::new(east_id.clone()).belong_to(east.clone()))?;
+    let west_user = west.register(west_id.clone(), User::new(west_id.clone()).belong_to(west.clone()))?;
+    let bridge = Bridge::connect(&east, &west)?;
+    println!("[Main] east routes west-1 {:?}", east.route_preview(&west_id)?);
+    east_user.send_msg(&west_id, "hello from the east")?;
+    let onlooker = UserId::new("east-2");
+    let onlooker = east.register(onlooker.clone(), User::new(onlooker).belong_to(east.clone()))?;
+    let watched = Arc::new(AtomicUsize::new(0));
+    let count = Arc::clone(&watched);
+    let watch = onlooker.watch(&east_id, move |msg| {
+        count.fetch_add(1, Ordering::Relaxed);
+        println!("[Main] east-2 saw east-1 get {:?}", msg);
+    })?;
+    west_user.send_msg(&east_id, "hello from the west")?;
+    drop(watch);
+    west_user.send_msg(&east_id, "unwatched")?;
+    println!("[Main] watch fired {} time(s)", watched.load(Ordering::Relaxed));
+    let logger = UserId::new("east-logger");
+    east.register_with_priority(logger.clone(), User::new(logger).belong_to(east.clone()), 10)?;
+    println!("[Main] east broadcast reached {}, logger first", east.broadcast(&onlooker, "all hands".to_string())?);
+    drop(bridge);
+    if let Err(e) = east_user.send_msg(&west_id, "anyone still there?") {
+        println!("[Main] bridge gone: {}", e);
     }
-}
-
-impl<T: Colleague> Deref for Managed<T> {
-    type Target = T;
     
-    fn deref(&self) -> &Self::Target {
-        &self.inner
+    let clock = MockClock::new();
+    let lenient = UserMediator::builder()
+        .capacity(16)
+        .deadletter(true)
+        .observer(PrintlnObserver)
+        .event_log(true)
+        .byte_accounting(true)
+        .retry_policy(RetryPolicy { max_attempts: 2, backoff: Duration::from_millis(10) })
+        .clock(clock.clone())
+        .quota_window(Duration::from_secs(60))
+        .dedupe(Duration::from_secs(30), 64)
+        .build();
+    let tapped = Arc::new(Mutex::new(Vec::new()));
+    let tap = Arc::clone(&tapped);
+    lenient.tap(move |_, _, _, passage| tap.lock().unwrap().push(passage))?;
+    let reg4 = lenient.register(UserId::new("user-4"), User::new(UserId::new("user-4")).belong_to(lenient.clone()))?;
+    reg4.send_msg(&user_id1, "nobody here knows you")?;
+    let user_id10 = UserId::new("user-10");
+    let fleeting = lenient.register_weak(user_id10.clone(), User::new(user_id10.clone()).belong_to(lenient.clone()))?;
+    reg4.send_msg(&user_id10, "still around?")?;
+    drop(fleeting);
+    reg4.send_msg(&user_id10, "gone already")?;
+    reg4.send_msg_with(&user_id10, "anyone?", |outcome| println!("[Main] user-10 check: {:?}", outcome))?;
+    println!("[Main] {:?}", lenient.snapshot()?);
+    let _late = lenient.register(user_id1.clone(), User::new(user_id1.clone()).belong_to(lenient.clone()))?;
+    println!("[Main] {:?}", lenient.retry_deadletters()?);
+    for letter in lenient.drain_deadletters()? {
+        println!("[Main] deadletter from:{:?} -> to:{:?}: {}", letter.from, letter.to, letter.msg);
     }
-}
-
-pub struct Registered<T: Colleague>(Rc<Managed<T>>);
-
-impl<T: Colleague> Registered<T> {
-    pub fn as_mediator(&self) -> &T::Mediator {
-        &self.0.mediator
+    
+    for event in lenient.event_log()? {
+        println!("[Main] event: {:?}", event.kind);
     }
-}
-
-impl<T: Colleague> Clone for Registered<T> {
-    fn clone(&self) -> Self {
-        Self(Rc::clone(&self.0))
+    print!("[Main] message graph:\n{}", lenient.to_dot()?);
+    
+    let user_id5 = UserId::new("user-5");
+    let slow = lenient.register(user_id5.clone(), User::new(user_id5.clone()).belong_to(lenient.clone()).with_mailbox(2))?;
+    lenient.consult_with_priority(&reg4, &user_id5, "whenever".to_string(), Priority::Low)?;
+    lenient.consult_with_priority(&reg4, &user_id5, "urgent".to_string(), Priority::High)?;
+    if let Err(e) = reg4.send_msg(&user_id5, "one too many") {
+        println!("[Main] {}", e);
     }
-}
-
-impl<T: Colleague> Deref for Registered<T> {
-    type Target = T;
+    println!("[Main] user-5 is {:?} before draining", lenient.poll_ready(&user_id5)?);
+    println!("[Main] user-5 has {:?} message(s) waiting, mailboxes: {:?}", lenient.mailbox_depth(&user_id5)?, lenient.mailbox_depths()?);
+    println!("[Main] user-5 drained {} message(s)", slow.drain(usize::MAX));
+    println!("[Main] user-5 is {:?} after draining", lenient.poll_ready(&user_id5)?);
+    let receipt = lenient.consult_with_ack(&reg4, &user_id5, "read me when you can".to_string())?;
+    println!("[Main] user-5 acknowledged before draining: {:?}", receipt.try_wait());
+    slow.drain(1);
+    println!("[Main] user-5 acknowledged {:?}: {:?}", receipt.message_id(), receipt.wait());
+    lenient.consult_with_ttl(&reg4, &user_id5, "read me within 5s".to_string(), Duration::from_secs(5))?;
+    clock.advance(Duration::from_secs(10));
+    println!("[Main] user-5 drained {} message(s) in time", slow.drain(usize::MAX));
+    for letter in lenient.drain_deadletters()? {
+        println!("[Main] expired {:?} from:{:?} -> to:{:?}: {}", letter.id, letter.from, letter.to, letter.msg);
+    }
+    reg4.send_msg(&user_id5, "left in the mailbox")?;
+    
+    let user_id11 = UserId::new("user-11");
+    let (_puller, inbox) = lenient.register_channel(user_id11.clone(), User::new(user_id11.clone()).belong_to(lenient.clone()))?;
+    reg4.send_msg(&user_id11, "pick me up")?;
+    println!("[Main] user-11 pulled {:?} off its channel", inbox.recv_timeout(Duration::from_secs(1)));
     
-    fn deref(&self) -> &Self::Target {
-        &self.0.inner
+    lenient.set_quota(reg4.id(), 1)?;
+    reg4.send_msg(&user_id1, "once a minute")?;
+    if let Err(e) = reg4.send_msg(&user_id1, "twice a minute") {
+        println!("[Main] {}", e);
     }
-}
-
-pub trait Colleague: Sized {
-    type Identifier;
-    type Mediator: Mediator<Self>;
-    fn id(&self) -> &Self::Identifier;
-    fn belong_to(self, mediator: Self::Mediator) -> Managed<Self>;
-}
-
-pub trait Mediator<T: Colleague> {
-    fn register(&mut self, id: T::Identifier, registered: Managed<T>) -> Result<Registered<T>, Error>;
-    fn consultation(&self, user: &T, to: &T::Identifier, msg: String) -> Result<(), Error>;
-}
-
-#[derive(Default)]
-pub struct UserMediator {
-    users: Rc<RwLock<HashMap<UserId, Registered<User>>>>
-}
-
-impl Clone for UserMediator {
-    fn clone(&self) -> Self {
-        Self { users: Rc::clone(&self.users) }
+    clock.advance(Duration::from_secs(60));
+    reg4.send_msg(&user_id1, "a minute later")?;
+    lenient.clear_quota(reg4.id())?;
+    for _ in 0..2 {
+        println!("[Main] {:?}", lenient.consult_idempotent(&reg4, &user_id1, "charged once".to_string(), MessageId::new(500))?);
+    }
+    clock.advance(Duration::from_secs(30));
+    println!("[Main] {:?}", lenient.consult_idempotent(&reg4, &user_id1, "charged again later".to_string(), MessageId::new(500))?);
+    lenient.consult_after(&reg4, &user_id1, "your reminder".to_string(), Duration::from_secs(5))?;
+    println!("[Main] {} reminder(s) sent right away, {} pending", lenient.tick(clock.now())?, lenient.scheduled_count());
+    clock.advance(Duration::from_secs(5));
+    println!("[Main] {} reminder(s) sent 5s later", lenient.tick(clock.now())?);
+    lenient.pause(&user_id1)?;
+    println!("[Main] user-1 away: {:?}", reg4.send_msg(&user_id1, "are you there?")?);
+    println!("[Main] user-1 back, {} message(s) flushed", lenient.resume(&user_id1, true)?);
+    clock.advance(Duration::from_secs(20));
+    reg4.send_msg(&user_id1, "still here")?;
+    println!("[Main] reaped idle {:?}", lenient.reap_idle(Duration::from_secs(15))?);
+    println!("[Main] lenient delivered {} byte(s)", lenient.bytes_delivered());
+    lenient.register_pattern("guest-*".to_string(), User::new(UserId::new("guests")).belong_to(lenient.clone()))?;
+    println!("[Main] {:?}", reg4.send_msg(&UserId::new("guest-99"), "which guest is this?")?);
+    for id in [reg4.id().clone(), UserId::new("guest-42"), UserId::new("stranger")] {
+        println!("[Main] messages to {:?} would route {:?}", id, lenient.route_preview(&id)?);
+    }
+    let support = UserId::new("support");
+    lenient.register(support.clone(), User::new(support.clone()).belong_to(lenient.clone()))?;
+    lenient.set_fallback(support)?;
+    println!("[Main] {:?}", reg4.send_msg(&UserId::new("stranger"), "can anyone help?")?);
+    lenient.clear_fallback()?;
+    let (seen, deadlettered) = {
+        let tapped = tapped.lock().unwrap();
+        (tapped.len(), tapped.iter().filter(|&&passage| passage == Tapped::Deadlettered).count())
+    };
+    println!("[Main] tap saw {} routed and {} deadlettered consultation(s)", seen - deadlettered, deadlettered);
+    let (producer, consumer) = (UserId::new("producer"), UserId::new("consumer"));
+    lenient.register_with_shutdown_priority(consumer.clone(), User::new(consumer).belong_to(lenient.clone()), -10)?;
+    lenient.register_with_shutdown_priority(producer.clone(), User::new(producer).belong_to(lenient.clone()), 10)?;
+    let report = lenient.shutdown()?;
+    println!("[Main] lenient shut down: {:?}", report);
+    if let Err(e) = lenient.register(UserId::new("user-8"), User::new(UserId::new("user-8")).belong_to(lenient.clone())) {
+        println!("[Main] {}", e);
     }
+    
+    let bots = BotMediator::default().observed_by(PrintlnObserver);
+    let bot1 = bots.register(BotId::new(1), Bot::new(BotId::new(1)).belong_to(bots.clone()))?;
+    let _bot2 = bots.register(BotId::new(2), Bot::new(BotId::new(2)).belong_to(bots.clone()))?;
+    
+    bot1.command(&BotId::new(2), BotCommand::Ping)?;
+    bot1.command(&BotId::new(2), BotCommand::Say("beep boop".to_string()))?;
+    bots.broadcast(&bot1, BotCommand::Shutdown { reason: "maintenance".to_string() })?;
+    
+    let mut mixed = AnyMediator::default();
+    mixed.register(User::new(UserId::new("user-6")))?;
+    mixed.register(Bot::new(BotId::new(3)))?;
+    mixed.consultation(&AnyId::new(UserId::new("user-6")), "hello from a mixed bus".to_string())?;
+    mixed.consultation(&AnyId::new(BotId::new(3)), BotCommand::Ping)?;
+    if let Err(e) = mixed.consultation(&AnyId::new(BotId::new(3)), "bots only take commands".to_string()) {
+        println!("[Main] {}", e);
+    }
+    
+    Ok(())
 }
 
-impl Mediator<User> for UserMediator {
-    fn register(&mut self, id: UserId, registered: Managed<User>) -> Result<Registered<User>, Error> {
-        let reg = Registered(Rc::new(registered));
-        self.users.write().map_err(|_| Error::LockPoison)?
-            .insert(id, reg.clone());
-        Ok(reg)
-    }
+struct Redact;
 
-    fn consultation(&self, from: &User, to: &UserId, msg: String) -> Result<(), Error> {
-        match self.users.read()
-            .map_err(|_| Error::LockPoison)?
-            .iter()
-            .find(|(id, _)| id.eq(&to)) 
-        {
-            Some((_, user)) => {
-                println!("[Mediator] from:{:?} -> to:{:?}: {}", from.id, to, msg);
-                user.read_msg(msg);
-            }
-            None => {
-                println!("[Mediator] msg:{} from {:?} has drifted over to deadletter.", msg, from.id);
-            }
+impl Middleware<User> for Redact {
+    fn before_deliver(&self, _: &UserId, _: &UserId, msg: String) -> MiddlewareResult<String> {
+        if msg.contains("secret") {
+            MiddlewareResult::Rewrite(msg.replace("secret", "***"))
+        } else {
+            MiddlewareResult::Pass(msg)
         }
-        
-        Ok(())
     }
 }
-
-#[derive(Debug, thiserror::Error)]
-pub enum Error {
-    #[error("cannot lock")]
-    LockPoison
-}
@@ -0,0 +1,103 @@
+use crate::colleague::{Colleague, Managed, Registered};
+use crate::error::Error;
+use crate::mediator::Mediator;
+use crate::message::DeliveryOutcome;
+
+/// A façade over a mediator keyed by another id type, built with [`Mediator::map_identifier`].
+///
+/// Every id handed in is translated with the conversion before being passed on, so e.g. a
+/// `String`-keyed subsystem can reach colleagues registered under numeric ids. The mediator's
+/// colleague type is unchanged, so this can't implement [`Mediator`] itself: the colleagues
+/// still report their own ids, and nothing that hands ids back, like
+/// [`Mediator::registered_ids`], is available here since the conversion only goes one way.
+#[derive(Clone)]
+pub struct MappedMediator<M, F> {
+    inner: M,
+    map: F,
+}
+
+impl<M, F> MappedMediator<M, F> {
+    pub(crate) fn new(inner: M, map: F) -> Self {
+        Self { inner, map }
+    }
+    
+    pub fn inner(&self) -> &M {
+        &self.inner
+    }
+    
+    pub fn into_inner(self) -> M {
+        self.inner
+    }
+    
+    pub fn register<T, O>(&self, id: O, registered: Managed<T>) -> Result<Registered<T>, Error>
+        where T: Colleague,
+              M: Mediator<T>,
+              F: Fn(O) -> T::Identifier
+    {
+        let id = (self.map)(id);
+        self.inner.register(id, registered)
+    }
+    
+    pub fn deregister<T, O>(&self, id: &O) -> Result<Option<Registered<T>>, Error>
+        where T: Colleague,
+              M: Mediator<T>,
+              F: Fn(O) -> T::Identifier,
+              O: Clone
+    {
+        let id = (self.map)(id.clone());
+        self.inner.deregister(&id)
+    }
+    
+    pub fn is_registered<T, O>(&self, id: &O) -> Result<bool, Error>
+        where T: Colleague,
+              M: Mediator<T>,
+              F: Fn(O) -> T::Identifier,
+              O: Clone
+    {
+        self.inner.is_registered(&(self.map)(id.clone()))
+    }
+    
+    pub fn get<T, O>(&self, id: &O) -> Result<Option<Registered<T>>, Error>
+        where T: Colleague,
+              M: Mediator<T>,
+              F: Fn(O) -> T::Identifier,
+              O: Clone
+    {
+        self.inner.get(&(self.map)(id.clone()))
+    }
+    
+    pub fn consultation<T, O>(&self, from: &Registered<T>, to: &O, msg: T::Message) -> Result<DeliveryOutcome, Error>
+        where T: Colleague,
+              M: Mediator<T>,
+              F: Fn(O) -> T::Identifier,
+              O: Clone
+    {
+        self.inner.consultation(from, &(self.map)(to.clone()), msg)
+    }
+    
+    pub fn try_consultation<T, O>(&self, from: &Registered<T>, to: &O, msg: T::Message) -> Result<bool, Error>
+        where T: Colleague,
+              M: Mediator<T>,
+              F: Fn(O) -> T::Identifier,
+              O: Clone
+    {
+        self.inner.try_consultation(from, &(self.map)(to.clone()), msg)
+    }
+    
+    pub fn request<T, O>(&self, from: &Registered<T>, to: &O, req: T::Request) -> Result<T::Response, Error>
+        where T: Colleague,
+              M: Mediator<T>,
+              F: Fn(O) -> T::Identifier,
+              O: Clone
+    {
+        self.inner.request(from, &(self.map)(to.clone()), req)
+    }
+    
+    pub fn broadcast<T>(&self, from: &Registered<T>, msg: T::Message) -> Result<usize, Error>
+        where T: Colleague,
+              M: Mediator<T>,
+              T::Message: Clone
+    {
+        self.inner.broadcast(from, msg)
+    }
+}
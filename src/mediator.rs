@@ -0,0 +1,134 @@
+use std::fmt::Debug;
+
+use crate::colleague::{Colleague, Managed, Registered};
+use crate::error::Error;
+use crate::mapped::MappedMediator;
+use crate::message::{BatchResult, DeliveryOutcome};
+
+pub(crate) mod sealed {
+    use crate::colleague::Colleague;
+    use crate::error::Error;
+    use crate::message::DeliveryOutcome;
+    
+    pub trait Sealed {}
+    
+    /// Only the crate can make one, so only the crate can call [`ConsultAs::consultation_as`],
+    /// even through a generic `Mediator` bound.
+    pub struct Token(());
+    
+    impl Token {
+        pub(crate) fn new() -> Self {
+            Self(())
+        }
+    }
+    
+    pub trait ConsultAs<T: Colleague> {
+        /// Consults on behalf of `from` without needing the colleague itself. It takes no
+        /// [`Registered`](crate::Registered) handle, so it is only for the send paths that got
+        /// `from` from one in the first place: a [`Sender`](crate::Sender), a
+        /// [`MediatorService`](crate::MediatorService), a reply or a forward.
+        /// Every consultation that gets past validation is assigned a [`MessageId`](crate::MessageId)
+        /// and reported as a [`DeliveryOutcome`], including the ones the recipient never reads.
+        fn consultation_as(&self, token: Token, from: &T::Identifier, to: &T::Identifier, msg: T::Message) -> Result<DeliveryOutcome, Error>;
+    }
+}
+
+type Entries<T> = Vec<(<T as Colleague>::Identifier, Registered<T>)>;
+
+/// Implemented by the crate's own mediators only. The crate's send paths, from a
+/// [`Sender`](crate::Sender) to a reply, consult on behalf of a colleague they don't hold, and
+/// count on a mediator that only lets them do so for an id taken from a [`Registered`] handle;
+/// one written elsewhere could let anyone speak for anyone. Pick one of them, such as
+/// [`GenericMediator`](crate::GenericMediator) or [`MapMediator`](crate::MapMediator), rather
+/// than rolling your own; colleagues are yours to write. What a mediator keeps track of on top
+/// of that, such as metrics or a deadletter queue, is up to each one; see its own docs.
+pub trait Mediator<T: Colleague>: sealed::Sealed + sealed::ConsultAs<T> {
+    /// Fails with [`Error::AlreadyRegistered`] if `id` is taken; see [`Mediator::re_register`].
+    fn register(&self, id: T::Identifier, registered: Managed<T>) -> Result<Registered<T>, Error>;
+    /// Like [`Mediator::register`] but hands back the colleague already under `id` instead of
+    /// failing; `registered` is dropped in that case. The check and the insert happen under one lock.
+    fn register_if_absent(&self, id: T::Identifier, registered: Managed<T>) -> Result<Registered<T>, Error>;
+    /// Replaces whatever is registered under `id`, returning the new handle and the displaced one.
+    ///
+    /// Handles are shared, so clones of the displaced [`Registered`] held elsewhere keep
+    /// dereferencing to the old colleague; they simply stop being reachable through the mediator.
+    fn re_register(&self, id: T::Identifier, registered: Managed<T>) -> Result<(Registered<T>, Option<Registered<T>>), Error>;
+    fn deregister(&self, id: &T::Identifier) -> Result<Option<Registered<T>>, Error>;
+    /// Moves the registration under `old` to `new`, keeping the very same [`Registered`] so
+    /// outstanding handles stay valid. Fails with [`Error::NotRegistered`] if `old` is absent
+    /// and with [`Error::AlreadyRegistered`] if `new` is taken. Whatever else the mediator keeps
+    /// about `old`, from its groups and filters to its quota, moves along with it.
+    ///
+    /// Only the mediator's key changes: the colleague's own [`Colleague::id`] still reports
+    /// `old`, and so do the messages it sends.
+    fn rename(&self, old: &T::Identifier, new: T::Identifier) -> Result<(), Error>;
+    /// Deregisters everyone, returning how many colleagues were removed. Outstanding
+    /// [`Registered`] handles keep working as values but are no longer reachable through the mediator.
+    fn clear(&self) -> Result<usize, Error>;
+    fn is_registered(&self, id: &T::Identifier) -> Result<bool, Error>;
+    /// Hands back the colleague's handle for direct interaction, bypassing consultation.
+    fn get(&self, id: &T::Identifier) -> Result<Option<Registered<T>>, Error>;
+    fn registered_count(&self) -> Result<usize, Error>;
+    /// Health check through [`Colleague::on_ping`], failing with [`Error::NotRegistered`] for
+    /// ids nobody is registered under.
+    fn ping(&self, id: &T::Identifier) -> Result<bool, Error>
+        where T::Identifier: Debug
+    {
+        match self.get(id)? {
+            Some(colleague) => Ok(colleague.on_ping()),
+            None => Err(Error::NotRegistered { id: format!("{:?}", id) }),
+        }
+    }
+    fn is_empty(&self) -> Result<bool, Error> {
+        Ok(self.registered_count()? == 0)
+    }
+    fn registered_ids(&self) -> Result<Vec<T::Identifier>, Error>
+        where T::Identifier: Clone;
+    /// Every colleague with its id, copied out of the registry so no lock is held
+    /// while the caller walks them.
+    fn entries(&self) -> Result<Entries<T>, Error>
+        where T::Identifier: Clone;
+    /// Sending takes the sender's [`Registered`] handle, so a colleague that was never
+    /// registered, or only got as far as [`Managed`], has no way to consult anyone.
+    /// Every consultation that gets past validation is assigned a [`MessageId`](crate::MessageId)
+    /// and reported as a [`DeliveryOutcome`], including the ones the recipient never reads.
+    fn consultation(&self, user: &Registered<T>, to: &T::Identifier, msg: T::Message) -> Result<DeliveryOutcome, Error> {
+        self.consultation_as(sealed::Token::new(), user.id(), to, msg)
+    }
+    /// Quiet delivery attempt: `Ok(false)` when nothing was delivered, without notifying the
+    /// observer or touching the deadletter queue. Otherwise it goes through the same checks as
+    /// a consultation, from pauses and filters to quotas, and only fails on lock poisoning.
+    fn try_consultation(&self, from: &Registered<T>, to: &T::Identifier, msg: T::Message) -> Result<bool, Error>;
+    /// Consults each of `to` with its own clone of `msg`, carrying on past the ones that fail.
+    /// Recipients that aren't registered fail with [`Error::NotRegistered`], rejections with
+    /// [`Error::DeliveryRejected`], and everything else with whatever the consultation failed with.
+    fn consult_many(&self, from: &Registered<T>, to: &[T::Identifier], msg: T::Message) -> BatchResult<T::Identifier>
+        where T::Identifier: Clone + Debug,
+              T::Message: Clone
+    {
+        let mut batch = BatchResult { delivered: Vec::new(), failed: Vec::new() };
+        for id in to {
+            let result = match self.consultation(from, id, msg.clone()) {
+                Ok(DeliveryOutcome::Deadlettered(_)) => Err(Error::NotRegistered { id: format!("{:?}", id) }),
+                Ok(outcome) => outcome.ok_or_err(),
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(_) => batch.delivered.push(id.clone()),
+                Err(e) => batch.failed.push((id.clone(), e)),
+            }
+        }
+        batch
+    }
+    fn request(&self, from: &Registered<T>, to: &T::Identifier, req: T::Request) -> Result<T::Response, Error>;
+    fn broadcast(&self, from: &Registered<T>, msg: T::Message) -> Result<usize, Error>
+        where T::Message: Clone;
+    /// Puts the mediator behind a façade that takes ids of another type, converting each one
+    /// with `map` before passing it on, see [`MappedMediator`].
+    fn map_identifier<O, F>(self, map: F) -> MappedMediator<Self, F>
+        where Self: Sized,
+              F: Fn(O) -> T::Identifier
+    {
+        MappedMediator::new(self, map)
+    }
+}
@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::error::Error;
+
+/// Identifies a single consultation; ids handed out by a mediator increase monotonically.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MessageId(u64);
+
+impl MessageId {
+    pub fn new(id: u64) -> MessageId {
+        Self(id)
+    }
+    
+    pub fn as_u64(&self) -> u64 {
+        self.0
+    }
+    
+    pub(crate) fn next(sequence: &AtomicU64) -> MessageId {
+        Self(sequence.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+/// How urgently a message should be read by a colleague with a mailbox; colleagues without
+/// one read every message right away regardless.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Priority {
+    Low,
+    #[default]
+    Normal,
+    High,
+}
+
+/// Payload size of a message, for byte accounting and bandwidth limits.
+pub trait MessageSize {
+    fn size(&self) -> usize;
+}
+
+impl MessageSize for String {
+    fn size(&self) -> usize {
+        self.len()
+    }
+}
+
+impl MessageSize for Vec<u8> {
+    fn size(&self) -> usize {
+        self.len()
+    }
+}
+
+/// A message body together with headers such as a correlation id, sender role or timestamp.
+///
+/// Colleagues see the headers by overriding [`Colleague::read_with_headers`](crate::Colleague::read_with_headers);
+/// middlewares, observers, filters and the deadletter queue only ever see the body.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Message<T> {
+    pub headers: HashMap<String, String>,
+    /// Schema version of the body, checked against [`Colleague::accepts_version`](crate::Colleague::accepts_version)
+    /// before delivery; 0 unless set.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub version: u16,
+    pub body: T,
+}
+
+impl<T> Message<T> {
+    /// Wraps `body` with no headers.
+    pub fn new(body: T) -> Message<T> {
+        Self { headers: HashMap::new(), version: 0, body }
+    }
+    
+    pub fn with_version(self, version: u16) -> Self {
+        Self { version, ..self }
+    }
+    
+    pub fn with_header(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.insert(key.into(), value.into());
+        self
+    }
+    
+    pub fn header(&self, key: &str) -> Option<&str> {
+        self.headers.get(key).map(String::as_str)
+    }
+}
+
+/// What became of a consultation that the mediator accepted.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum DeliveryOutcome {
+    /// Read by the recipient, or queued in its mailbox or channel.
+    Delivered(MessageId),
+    /// The recipient wasn't registered and the message went to the deadletter queue.
+    Deadlettered(MessageId),
+    /// The recipient's filter wasn't interested in the message.
+    Filtered(MessageId),
+    /// A message with the same explicit id went through within the deduplication window, see
+    /// [`GenericMediator::consult_idempotent`](crate::GenericMediator::consult_idempotent).
+    Duplicate(MessageId),
+    /// The recipient isn't registered here, so the message was published on the
+    /// [transport](crate::Transport) for another process to deliver.
+    Published(MessageId),
+    /// The recipient's `read_msg` refused the message.
+    Rejected { id: MessageId, colleague: String, reason: String },
+}
+
+impl DeliveryOutcome {
+    pub fn message_id(&self) -> MessageId {
+        match self {
+            DeliveryOutcome::Delivered(id)
+            | DeliveryOutcome::Deadlettered(id)
+            | DeliveryOutcome::Filtered(id)
+            | DeliveryOutcome::Duplicate(id)
+            | DeliveryOutcome::Published(id)
+            | DeliveryOutcome::Rejected { id, .. } => *id,
+        }
+    }
+    
+    /// Turns a rejection into [`Error::DeliveryRejected`] and everything else into its
+    /// [`MessageId`], for callers that only care whether the recipient refused.
+    pub fn ok_or_err(self) -> Result<MessageId, Error> {
+        match self {
+            DeliveryOutcome::Rejected { colleague, reason, .. } => Err(Error::DeliveryRejected { id: colleague, reason }),
+            outcome => Ok(outcome.message_id()),
+        }
+    }
+}
+
+/// Who a [`Mediator::consult_many`](crate::Mediator::consult_many) reached and who it didn't.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BatchResult<I> {
+    pub delivered: Vec<I>,
+    pub failed: Vec<(I, Error)>,
+}
+
+impl<I> BatchResult<I> {
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// Where a consultation seen by a [tap](crate::GenericMediator::tap) is headed.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Tapped {
+    /// To a colleague, though its filter may still turn it down.
+    Routed,
+    /// To the deadletter queue.
+    Deadlettered,
+}
+
+/// Whether a colleague could take another message right now, see
+/// [`GenericMediator::poll_ready`](crate::GenericMediator::poll_ready).
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Readiness {
+    Ready,
+    /// Its mailbox or bounded inbox is at capacity.
+    Full,
+    /// Nobody would read it: no colleague answers to the id, or its consumer is gone.
+    Absent,
+}
+
+/// Where a consultation to a given id would end up, see
+/// [`GenericMediator::route_preview`](crate::GenericMediator::route_preview).
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum RouteDecision {
+    /// With the colleague registered under exactly that id.
+    Exact,
+    /// With the colleague standing in for the glob.
+    Pattern(String),
+    /// With the [fallback](crate::GenericMediator::set_fallback) colleague.
+    Fallback,
+    /// Up to the [parent](crate::GenericMediatorBuilder::parent) mediator, which resolves it its own way.
+    Parent,
+    /// Across a [`Bridge`](crate::Bridge) to the mediator the id lives on.
+    Bridge,
+    /// Out over the [transport](crate::Transport), to whichever process answers to the id.
+    Remote,
+    /// Nowhere; the message would go to the deadletter queue.
+    Deadletter,
+}
@@ -0,0 +1,151 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+#[cfg(feature = "prometheus")]
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+#[cfg(feature = "prometheus")]
+use prometheus::core::Desc;
+#[cfg(feature = "prometheus")]
+use prometheus::proto::MetricFamily;
+#[cfg(feature = "prometheus")]
+use prometheus::{IntCounter, IntGauge};
+
+/// Running totals a mediator keeps about the messages passing through it.
+#[derive(Debug, Default)]
+pub(crate) struct Metrics {
+    sent: AtomicU64,
+    delivered: AtomicU64,
+    bytes_delivered: AtomicU64,
+    deadlettered: AtomicU64,
+    filtered: AtomicU64,
+    fanout: AtomicU64,
+}
+
+impl Metrics {
+    pub(crate) fn sent(&self) {
+        self.sent.fetch_add(1, Ordering::Relaxed);
+    }
+    
+    pub(crate) fn delivered(&self) {
+        self.delivered.fetch_add(1, Ordering::Relaxed);
+    }
+    
+    pub(crate) fn delivered_bytes(&self, size: usize) {
+        self.bytes_delivered.fetch_add(size as u64, Ordering::Relaxed);
+    }
+    
+    pub(crate) fn deadlettered(&self) {
+        self.deadlettered.fetch_add(1, Ordering::Relaxed);
+    }
+    
+    pub(crate) fn filtered(&self) {
+        self.filtered.fetch_add(1, Ordering::Relaxed);
+    }
+    
+    pub(crate) fn fanned_out(&self, reached: usize) {
+        self.fanout.fetch_add(reached as u64, Ordering::Relaxed);
+    }
+    
+    pub(crate) fn snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot {
+            sent: self.sent.load(Ordering::Relaxed),
+            delivered: self.delivered.load(Ordering::Relaxed),
+            bytes_delivered: self.bytes_delivered.load(Ordering::Relaxed),
+            deadlettered: self.deadlettered.load(Ordering::Relaxed),
+            filtered: self.filtered.load(Ordering::Relaxed),
+            fanout: self.fanout.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Plain copy of a mediator's counters, see [`GenericMediator::metrics`](crate::GenericMediator::metrics).
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct MetricsSnapshot {
+    /// Point-to-point consultations that got past validation, whether delivered or not.
+    pub sent: u64,
+    /// Messages handed to a colleague, including those queued in a mailbox and those
+    /// fanned out by broadcasts and multicasts.
+    pub delivered: u64,
+    /// Combined [size](crate::MessageSize) of the delivered messages; zero unless enabled with
+    /// [`GenericMediatorBuilder::byte_accounting`](crate::GenericMediatorBuilder::byte_accounting).
+    pub bytes_delivered: u64,
+    pub deadlettered: u64,
+    /// Messages a recipient's filter wasn't interested in.
+    pub filtered: u64,
+    /// Colleagues reached by broadcasts and multicasts combined.
+    pub fanout: u64,
+}
+
+/// How long a colleague took over the consultations delivered to it, see
+/// [`GenericMediator::latency_stats`](crate::GenericMediator::latency_stats).
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct LatencyStats {
+    pub count: u64,
+    pub total: Duration,
+    pub max: Duration,
+}
+
+impl LatencyStats {
+    pub(crate) fn record(&mut self, took: Duration) {
+        self.count += 1;
+        self.total += took;
+        self.max = self.max.max(took);
+    }
+    
+    /// `None` before the first consultation.
+    pub fn mean(&self) -> Option<Duration> {
+        u32::try_from(self.count).ok()
+            .filter(|&count| count > 0)
+            .map(|count| self.total / count)
+    }
+}
+
+/// Mirrors a mediator's counters into a Prometheus registry, reading them afresh on every
+/// scrape, see [`GenericMediator::register_metrics`](crate::GenericMediator::register_metrics).
+#[cfg(feature = "prometheus")]
+pub(crate) struct Collector {
+    metrics: Arc<Metrics>,
+    registered: Box<dyn Fn() -> usize + Send + Sync>,
+    sent: IntCounter,
+    delivered: IntCounter,
+    deadlettered: IntCounter,
+    colleagues: IntGauge,
+    // Counters only go up, so a scrape adds what happened since the last one; two at once
+    // would both add it.
+    scrape: Mutex<()>,
+}
+
+#[cfg(feature = "prometheus")]
+impl Collector {
+    pub(crate) fn new(metrics: Arc<Metrics>, registered: impl Fn() -> usize + Send + Sync + 'static) -> prometheus::Result<Self> {
+        Ok(Self {
+            metrics,
+            registered: Box::new(registered),
+            sent: IntCounter::new("messages_sent_total", "Consultations that got past validation.")?,
+            delivered: IntCounter::new("messages_delivered_total", "Messages handed to a colleague.")?,
+            deadlettered: IntCounter::new("deadletters_total", "Messages put in the deadletter queue.")?,
+            colleagues: IntGauge::new("registered_colleagues", "Colleagues currently registered.")?,
+            scrape: Mutex::default(),
+        })
+    }
+}
+
+#[cfg(feature = "prometheus")]
+impl prometheus::core::Collector for Collector {
+    fn desc(&self) -> Vec<&Desc> {
+        [self.sent.desc(), self.delivered.desc(), self.deadlettered.desc(), self.colleagues.desc()]
+            .concat()
+    }
+    
+    fn collect(&self) -> Vec<MetricFamily> {
+        let _scrape = self.scrape.lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let snapshot = self.metrics.snapshot();
+        for (counter, total) in [(&self.sent, snapshot.sent), (&self.delivered, snapshot.delivered), (&self.deadlettered, snapshot.deadlettered)] {
+            counter.inc_by(total.saturating_sub(counter.get()));
+        }
+        self.colleagues.set(i64::try_from((self.registered)()).unwrap_or(i64::MAX));
+        [self.sent.collect(), self.delivered.collect(), self.deadlettered.collect(), self.colleagues.collect()]
+            .concat()
+    }
+}
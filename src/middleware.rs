@@ -0,0 +1,14 @@
+use crate::colleague::Colleague;
+
+/// What a [`Middleware`] decided to do with a message on its way to a recipient.
+#[derive(Debug, Clone)]
+pub enum MiddlewareResult<M> {
+    Pass(M),
+    Rewrite(M),
+    Reject { reason: String },
+}
+
+/// Cross-cutting step run by a mediator on every message before it reaches `read_msg`.
+pub trait Middleware<T: Colleague>: Send + Sync {
+    fn before_deliver(&self, from: &T::Identifier, to: &T::Identifier, msg: T::Message) -> MiddlewareResult<T::Message>;
+}
@@ -0,0 +1,60 @@
+use std::fmt::{Debug, Display};
+
+use crate::colleague::Colleague;
+
+/// Hook invoked by a mediator as messages are routed, in place of hardcoded logging.
+///
+/// `on_register` and `on_deregister` are only called once the mediator has released its
+/// registry lock, so they are free to call back into the mediator, e.g. to greet a newcomer.
+pub trait Observer<T: Colleague>: Send + Sync {
+    fn on_register(&self, _id: &T::Identifier) {}
+    fn on_deregister(&self, _id: &T::Identifier) {}
+    fn on_delivered(&self, _from: &T::Identifier, _to: &T::Identifier, _msg: &T::Message) {}
+    fn on_deadletter(&self, _from: &T::Identifier, _to: &T::Identifier, _msg: &T::Message) {}
+    /// `old` has just been replaced by a new colleague registered under the same `id`.
+    fn on_superseded(&self, _id: &T::Identifier, _old: &T) {}
+    /// The colleague under `old` was [renamed](crate::Mediator::rename) to `new`.
+    fn on_rename(&self, _old: &T::Identifier, _new: &T::Identifier) {}
+    /// A message still queued for `to` is discarded because the mediator is shutting down.
+    fn on_drop_message(&self, _from: &T::Identifier, _to: &T::Identifier, _msg: T::Message) {}
+}
+
+pub struct NoopObserver;
+
+impl<T: Colleague> Observer<T> for NoopObserver {}
+
+/// Prints routing activity to stdout, the way mediators used to before observers existed.
+pub struct PrintlnObserver;
+
+impl<T: Colleague> Observer<T> for PrintlnObserver
+    where T::Identifier: Debug,
+          T::Message: Display
+{
+    fn on_register(&self, id: &T::Identifier) {
+        println!("[Mediator] {:?} joined.", id);
+    }
+    
+    fn on_deregister(&self, id: &T::Identifier) {
+        println!("[Mediator] {:?} left.", id);
+    }
+    
+    fn on_delivered(&self, from: &T::Identifier, to: &T::Identifier, msg: &T::Message) {
+        println!("[Mediator] from:{:?} -> to:{:?}: {}", from, to, msg);
+    }
+    
+    fn on_deadletter(&self, from: &T::Identifier, _to: &T::Identifier, msg: &T::Message) {
+        println!("[Mediator] msg:{} from {:?} has drifted over to deadletter.", msg, from);
+    }
+    
+    fn on_superseded(&self, id: &T::Identifier, _old: &T) {
+        println!("[Mediator] {:?} was taken over by a new registration.", id);
+    }
+    
+    fn on_rename(&self, old: &T::Identifier, new: &T::Identifier) {
+        println!("[Mediator] {:?} was renamed to {:?}.", old, new);
+    }
+    
+    fn on_drop_message(&self, from: &T::Identifier, to: &T::Identifier, msg: T::Message) {
+        println!("[Mediator] msg:{} from {:?} to {:?} was dropped on shutdown.", msg, from, to);
+    }
+}
@@ -0,0 +1,54 @@
+use crate::colleague::{Colleague, Registered};
+
+/// A colleague standing in for every id matching a glob.
+pub(crate) struct Pattern<C: Colleague> {
+    glob: String,
+    key: fn(&C::Identifier) -> &str,
+    colleague: Registered<C>,
+}
+
+impl<C: Colleague> Pattern<C> {
+    pub(crate) fn new(glob: String, key: fn(&C::Identifier) -> &str, colleague: Registered<C>) -> Self {
+        Self { glob, key, colleague }
+    }
+    
+    pub(crate) fn glob(&self) -> &str {
+        &self.glob
+    }
+    
+    pub(crate) fn colleague(&self) -> &Registered<C> {
+        &self.colleague
+    }
+    
+    pub(crate) fn matches(&self, id: &C::Identifier) -> bool {
+        glob_match(self.glob.as_bytes(), (self.key)(id).as_bytes())
+    }
+}
+
+/// `*` matches any run of characters, `?` any single one; everything else matches itself.
+fn glob_match(glob: &[u8], text: &[u8]) -> bool {
+    let (mut g, mut t) = (0, 0);
+    // Where the last `*` was seen and how much text it has swallowed so far.
+    let mut star = None;
+    while t < text.len() {
+        match glob.get(g) {
+            Some(b'*') => {
+                star = Some((g, t));
+                g += 1;
+            }
+            Some(&c) if c == b'?' || c == text[t] => {
+                g += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_g, star_t)) => {
+                    g = star_g + 1;
+                    t = star_t + 1;
+                    star = Some((star_g, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    glob[g..].iter().all(|&c| c == b'*')
+}
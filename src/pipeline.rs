@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::breaker::Breakers;
+use crate::colleague::Colleague;
+use crate::deadletter::DeadletterQueue;
+#[cfg(feature = "tokio")]
+use crate::generic::GenericMediator;
+use crate::lock::{self, RwLock};
+use crate::metrics::LatencyStats;
+use crate::quota::Quotas;
+use crate::reliable::InFlight;
+#[cfg(feature = "tokio")]
+use crate::transport::Remote;
+
+#[cfg(feature = "tokio")]
+pub(crate) type RemoteEnd<C, R> = Remote<<C as Colleague>::Identifier, <C as Colleague>::Message, GenericMediator<C, R>>;
+
+/// What a [`GenericMediator`](crate::GenericMediator) keeps about the messages on their way
+/// through it, apart from the registry: the senders' quotas, the recipients' circuit breakers
+/// and latencies, the deadletter queue, the reliable deliveries waiting for an ack and the
+/// transport to other processes. A consultation is held against the sender's quota before
+/// anything else, and against the recipient's breaker once it is found here; if it isn't, it
+/// goes over a bridge, out on the transport, to the parent or to the deadletter queue.
+pub(crate) struct Pipeline<C: Colleague, R> {
+    pub(crate) quotas: Quotas<C::Identifier>,
+    breakers: Option<Breakers<C::Identifier>>,
+    latencies: RwLock<HashMap<C::Identifier, LatencyStats>>,
+    // Shared with the messages sent with a ttl, which expire into it.
+    pub(crate) deadletters: Arc<DeadletterQueue<C::Identifier, C::Message>>,
+    pub(crate) in_flight: InFlight<C::Identifier, C::Message>,
+    #[cfg(feature = "tokio")]
+    pub(crate) remote: Option<RemoteEnd<C, R>>,
+    // The registry is only named by the transport's delivery tasks.
+    _registry: PhantomData<fn() -> R>,
+}
+
+impl<C: Colleague, R> Pipeline<C, R> {
+    pub(crate) fn new(quotas: Quotas<C::Identifier>, breakers: Option<Breakers<C::Identifier>>, deadletters: DeadletterQueue<C::Identifier, C::Message>, in_flight: InFlight<C::Identifier, C::Message>) -> Self {
+        Self {
+            quotas,
+            breakers,
+            latencies: RwLock::default(),
+            deadletters: Arc::new(deadletters),
+            in_flight,
+            #[cfg(feature = "tokio")]
+            remote: None,
+            _registry: PhantomData,
+        }
+    }
+    
+    #[cfg(feature = "tokio")]
+    pub(crate) fn with_remote(self, remote: Option<RemoteEnd<C, R>>) -> Self {
+        Self { remote, ..self }
+    }
+}
+
+impl<C: Colleague, R> Pipeline<C, R>
+    where C::Identifier: Eq + Hash + Clone
+{
+    /// Whether `to`'s circuit lets a consultation through at `now`.
+    pub(crate) fn admit(&self, to: &C::Identifier, now: Instant) -> bool {
+        self.breakers.as_ref()
+            .is_none_or(|breakers| breakers.admit(to, now))
+    }
+    
+    /// Accounts for a delivery to `to` that took `took`: `read` tells whether `to` took it or
+    /// refused it, and is `None` if its handler panicked, which its breaker doesn't count.
+    pub(crate) fn handed(&self, to: &C::Identifier, took: Duration, read: Option<bool>, now: Instant) {
+        lock::recover_write(&self.latencies)
+            .entry(to.clone())
+            .or_default()
+            .record(took);
+        if let (Some(read), Some(breakers)) = (read, &self.breakers) {
+            breakers.record(to, read, now);
+        }
+    }
+    
+    pub(crate) fn latency_stats(&self, id: &C::Identifier) -> Option<LatencyStats> {
+        lock::recover_read(&self.latencies)
+            .get(id)
+            .copied()
+    }
+    
+    /// Forgets the latencies of `id` once it is deregistered.
+    pub(crate) fn left(&self, id: &C::Identifier) {
+        lock::recover_write(&self.latencies)
+            .remove(id);
+    }
+    
+    /// Moves everything kept about `old` over to `new` once it is renamed, except for the
+    /// transport's subscription, which takes the mediator to renew.
+    pub(crate) fn rename(&self, old: &C::Identifier, new: &C::Identifier) {
+        self.quotas.rename(old, new.clone());
+        let mut latencies = lock::recover_write(&self.latencies);
+        if let Some(stats) = latencies.remove(old) {
+            latencies.insert(new.clone(), stats);
+        }
+        drop(latencies);
+        if let Some(breakers) = &self.breakers {
+            breakers.rename(old, new);
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crate::error::{Error, Operation};
+use crate::lock::{self, RwLock};
+
+/// The quotas of every colleague that has one, see
+/// [`GenericMediator::set_quota`](crate::GenericMediator::set_quota), and the
+/// [window](crate::GenericMediatorBuilder::quota_window) they start over after, if any.
+pub(crate) struct Quotas<I> {
+    quotas: RwLock<HashMap<I, Quota>>,
+    window: Option<Duration>,
+}
+
+impl<I> Quotas<I> {
+    pub(crate) fn new(window: Option<Duration>) -> Self {
+        Self { quotas: RwLock::default(), window }
+    }
+}
+
+impl<I: Eq + Hash> Quotas<I> {
+    pub(crate) fn set(&self, id: I, limit: u32, now: Instant) -> Result<(), Error> {
+        lock::write(&self.quotas, Operation::Quota)?
+            .insert(id, Quota::new(limit, now));
+        Ok(())
+    }
+    
+    pub(crate) fn clear(&self, id: &I) -> Result<(), Error> {
+        lock::write(&self.quotas, Operation::Quota)?
+            .remove(id);
+        Ok(())
+    }
+    
+    /// Uses up one message of `id`'s allowance, and tells whether there was one left; always
+    /// so for colleagues without a quota.
+    pub(crate) fn take(&self, id: &I, now: Instant) -> Result<bool, Error> {
+        let allowed = lock::read(&self.quotas, Operation::Consultation)?
+            .get(id)
+            .is_none_or(|quota| quota.take(now, self.window));
+        Ok(allowed)
+    }
+    
+    pub(crate) fn reset(&self, now: Instant) -> Result<(), Error> {
+        lock::read(&self.quotas, Operation::Quota)?
+            .values()
+            .for_each(|quota| quota.reset(now));
+        Ok(())
+    }
+    
+    pub(crate) fn rename(&self, old: &I, new: I) {
+        let mut quotas = lock::recover_write(&self.quotas);
+        if let Some(quota) = quotas.remove(old) {
+            quotas.insert(new, quota);
+        }
+    }
+}
+
+/// Fixed-window send allowance of a single colleague; the window ends whenever the
+/// mediator's quotas are reset or, if the mediator has a quota window, once it has elapsed.
+struct Quota {
+    limit: u32,
+    window: Mutex<Window>,
+}
+
+struct Window {
+    start: Instant,
+    used: u32,
+}
+
+impl Quota {
+    fn new(limit: u32, now: Instant) -> Self {
+        Self { limit, window: Mutex::new(Window { start: now, used: 0 }) }
+    }
+    
+    /// Uses up one message of the allowance, unless it's spent already, starting a new
+    /// window first if `length` has passed since the current one started.
+    fn take(&self, now: Instant, length: Option<Duration>) -> bool {
+        let mut window = self.lock();
+        if length.is_some_and(|length| now.duration_since(window.start) >= length) {
+            *window = Window { start: now, used: 0 };
+        }
+        if window.used >= self.limit {
+            return false;
+        }
+        window.used += 1;
+        true
+    }
+    
+    fn reset(&self, now: Instant) {
+        *self.lock() = Window { start: now, used: 0 };
+    }
+    
+    fn lock(&self) -> MutexGuard<'_, Window> {
+        self.window.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
@@ -0,0 +1,92 @@
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+use futures::stream::{self, BoxStream, Stream, StreamExt};
+use redis::{Client, Connection, RedisError, RedisResult};
+
+use crate::error::ColleagueError;
+use crate::transport::Transport;
+
+/// How long a subscription waits before trying again to subscribe on a new connection.
+const RESUBSCRIBE_AFTER: Duration = Duration::from_secs(1);
+
+/// [`Transport`] over Redis pub/sub, on top of the `redis` crate.
+///
+/// Publishing goes through a single connection, opened on first use and again once it broke,
+/// right away for the payload that found it broken. Every subscription reads from a connection
+/// of its own, opened once the stream is first polled, and subscribes again on a new one
+/// whenever it is lost; what is published in between is missed.
+pub struct RedisTransport {
+    client: Client,
+    publisher: Mutex<Option<Connection>>,
+}
+
+impl RedisTransport {
+    /// Talks to the Redis server at `url`, e.g. `"redis://127.0.0.1:6379"`. Fails if `url`
+    /// isn't one; whether a server answers there only shows once it is used.
+    pub fn new(url: &str) -> Result<Self, ColleagueError> {
+        let client = Client::open(url).map_err(failed)?;
+        Ok(Self { client, publisher: Mutex::new(None) })
+    }
+    
+    fn publisher(&self) -> MutexGuard<'_, Option<Connection>> {
+        self.publisher.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Transport for RedisTransport {
+    fn publish(&self, topic: &str, payload: Vec<u8>) -> Result<(), ColleagueError> {
+        let mut publisher = self.publisher();
+        let mut reconnected = false;
+        loop {
+            let mut connection = match publisher.take() {
+                Some(connection) => connection,
+                None => {
+                    reconnected = true;
+                    self.client.get_connection().map_err(failed)?
+                }
+            };
+            match redis::cmd("PUBLISH").arg(topic).arg(&payload).query::<i64>(&mut connection) {
+                Ok(_) => {
+                    *publisher = Some(connection);
+                    return Ok(());
+                }
+                // A broken connection is left behind, to be opened again.
+                Err(e) if broken(&e) && !reconnected => continue,
+                Err(e) if broken(&e) => return Err(failed(e)),
+                Err(e) => {
+                    *publisher = Some(connection);
+                    return Err(failed(e));
+                }
+            }
+        }
+    }
+    
+    fn subscribe(&self, topic: &str) -> Result<BoxStream<'static, Vec<u8>>, ColleagueError> {
+        let connections = stream::unfold((self.client.clone(), topic.to_string()), |(client, topic)| async move {
+            let payloads = loop {
+                match listen(&client, &topic).await {
+                    Ok(payloads) => break payloads,
+                    Err(_) => tokio::time::sleep(RESUBSCRIBE_AFTER).await,
+                }
+            };
+            Some((payloads, (client, topic)))
+        });
+        Ok(connections.flatten().boxed())
+    }
+}
+
+/// Subscribes to `topic` on a connection of its own, whose payloads end once it is lost.
+async fn listen(client: &Client, topic: &str) -> RedisResult<impl Stream<Item = Vec<u8>>> {
+    let mut pubsub = client.get_async_pubsub().await?;
+    pubsub.subscribe(topic).await?;
+    Ok(pubsub.into_on_message().map(|msg| msg.get_payload_bytes().to_vec()))
+}
+
+fn broken(e: &RedisError) -> bool {
+    e.is_connection_dropped() || e.is_io_error()
+}
+
+fn failed(e: RedisError) -> ColleagueError {
+    ColleagueError::new(format!("redis connection failed: {}", e))
+}
@@ -0,0 +1,125 @@
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{BuildHasher, Hash};
+
+/// Map from ids to registrations behind a [`GenericMediator`](crate::GenericMediator).
+///
+/// `HashMap` is the default, with any `BuildHasher` in place of SipHash, see
+/// [`HashedMediator`](crate::HashedMediator); a `BTreeMap` costs a little on lookups but keeps the registry
+/// sorted, so [`registered_ids`](crate::Mediator::registered_ids), [`entries`](crate::Mediator::entries)
+/// and everything else walking it see ids in order, see [`OrderedMediator`](crate::OrderedMediator).
+pub trait Registry<K, V>: Default + IntoIterator<Item = (K, V)> {
+    fn with_capacity(capacity: usize) -> Self;
+    fn get(&self, id: &K) -> Option<&V>;
+    fn contains_key(&self, id: &K) -> bool {
+        self.get(id).is_some()
+    }
+    fn insert(&mut self, id: K, value: V) -> Option<V>;
+    fn remove(&mut self, id: &K) -> Option<V>;
+    fn retain(&mut self, keep: impl FnMut(&K, &mut V) -> bool);
+    fn len(&self) -> usize;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V)>
+        where K: 'a, V: 'a;
+    fn keys<'a>(&'a self) -> impl Iterator<Item = &'a K>
+        where K: 'a, V: 'a
+    {
+        self.iter().map(|(id, _)| id)
+    }
+    fn values<'a>(&'a self) -> impl Iterator<Item = &'a V>
+        where K: 'a, V: 'a
+    {
+        self.iter().map(|(_, value)| value)
+    }
+    /// How many entries fit without reallocating.
+    fn capacity(&self) -> usize;
+    /// Makes room for at least `additional` more entries.
+    fn reserve(&mut self, additional: usize);
+    fn shrink_to_fit(&mut self);
+}
+
+impl<K: Eq + Hash, V, S: BuildHasher + Default> Registry<K, V> for HashMap<K, V, S> {
+    fn with_capacity(capacity: usize) -> Self {
+        HashMap::with_capacity_and_hasher(capacity, S::default())
+    }
+    
+    fn get(&self, id: &K) -> Option<&V> {
+        HashMap::get(self, id)
+    }
+    
+    fn insert(&mut self, id: K, value: V) -> Option<V> {
+        HashMap::insert(self, id, value)
+    }
+    
+    fn remove(&mut self, id: &K) -> Option<V> {
+        HashMap::remove(self, id)
+    }
+    
+    fn retain(&mut self, keep: impl FnMut(&K, &mut V) -> bool) {
+        HashMap::retain(self, keep)
+    }
+    
+    fn len(&self) -> usize {
+        HashMap::len(self)
+    }
+    
+    fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V)>
+        where K: 'a, V: 'a
+    {
+        HashMap::iter(self)
+    }
+    
+    fn capacity(&self) -> usize {
+        HashMap::capacity(self)
+    }
+    
+    fn reserve(&mut self, additional: usize) {
+        HashMap::reserve(self, additional)
+    }
+    
+    fn shrink_to_fit(&mut self) {
+        HashMap::shrink_to_fit(self)
+    }
+}
+
+/// Never preallocates, so its capacity is its length and shrinking it does nothing.
+impl<K: Ord, V> Registry<K, V> for BTreeMap<K, V> {
+    fn with_capacity(_: usize) -> Self {
+        BTreeMap::new()
+    }
+    
+    fn get(&self, id: &K) -> Option<&V> {
+        BTreeMap::get(self, id)
+    }
+    
+    fn insert(&mut self, id: K, value: V) -> Option<V> {
+        BTreeMap::insert(self, id, value)
+    }
+    
+    fn remove(&mut self, id: &K) -> Option<V> {
+        BTreeMap::remove(self, id)
+    }
+    
+    fn retain(&mut self, keep: impl FnMut(&K, &mut V) -> bool) {
+        BTreeMap::retain(self, keep)
+    }
+    
+    fn len(&self) -> usize {
+        BTreeMap::len(self)
+    }
+    
+    fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V)>
+        where K: 'a, V: 'a
+    {
+        BTreeMap::iter(self)
+    }
+    
+    fn capacity(&self) -> usize {
+        BTreeMap::len(self)
+    }
+    
+    fn reserve(&mut self, _: usize) {}
+    
+    fn shrink_to_fit(&mut self) {}
+}
@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crate::audit::{AckOutcome, AckStore};
+use crate::message::{DeliveryOutcome, MessageId};
+
+/// Handed to [`Colleague::read_reliably`](crate::Colleague::read_reliably) along with a message
+/// sent by [`GenericMediator::consult_reliably`](crate::GenericMediator::consult_reliably).
+///
+/// Until it is acked the message stays in flight: a nack has it redelivered on the next
+/// [`GenericMediator::redeliver`](crate::GenericMediator::redeliver), and so does dropping the
+/// handle unsettled once the ack timeout has passed.
+pub struct AckHandle {
+    id: MessageId,
+    attempt: u32,
+    settled: Arc<Settled>,
+}
+
+impl AckHandle {
+    pub fn message_id(&self) -> MessageId {
+        self.id
+    }
+    
+    /// Which delivery of the message this is, starting at 1.
+    pub fn attempt(&self) -> u32 {
+        self.attempt
+    }
+    
+    /// The message was handled, so it is never delivered again.
+    pub fn ack(self) {
+        self.settle(true);
+    }
+    
+    /// The message wasn't handled and should be delivered again.
+    pub fn nack(self) {
+        self.settle(false);
+    }
+    
+    fn settle(self, acked: bool) {
+        lock(&self.settled).insert(self.id, acked);
+    }
+}
+
+/// Acks and nacks not yet taken into account, kept apart from the messages in flight so an
+/// [`AckHandle`] doesn't need to know their types.
+type Settled = Mutex<HashMap<MessageId, bool>>;
+
+struct Unacked<I, M> {
+    from: I,
+    to: I,
+    msg: M,
+    attempts: u32,
+    timeout: Duration,
+    deadline: Instant,
+    nacked: bool,
+}
+
+/// Messages sent at least once and not acked yet.
+pub(crate) struct InFlight<I, M> {
+    unacked: Mutex<HashMap<MessageId, Unacked<I, M>>>,
+    settled: Arc<Settled>,
+    store: Option<Arc<dyn AckStore<I>>>,
+}
+
+/// A message due for another delivery.
+pub(crate) struct Redelivery<I, M> {
+    pub(crate) from: I,
+    pub(crate) to: I,
+    pub(crate) msg: M,
+    pub(crate) ack: AckHandle,
+}
+
+impl<I, M> InFlight<I, M> {
+    /// Keeps track of messages in flight, recording deliveries, acks and nacks into `store`.
+    pub(crate) fn new(store: Option<Arc<dyn AckStore<I>>>) -> Self {
+        Self { unacked: Mutex::new(HashMap::new()), settled: Arc::default(), store }
+    }
+    
+    /// Puts `msg` in flight and hands out the handle for its first delivery.
+    pub(crate) fn push(&self, id: MessageId, from: I, to: I, msg: M, timeout: Duration, now: Instant) -> AckHandle {
+        self.audit(id, &to, AckOutcome::Resolved(DeliveryOutcome::Delivered(id)));
+        let unacked = Unacked { from, to, msg, attempts: 1, timeout, deadline: now + timeout, nacked: false };
+        self.lock().insert(id, unacked);
+        self.handle(id, 1)
+    }
+    
+    /// Takes out the messages that were nacked or not acked before their deadline, counting them
+    /// as delivered once more and rearming their deadline from `now`.
+    pub(crate) fn due(&self, now: Instant) -> Vec<Redelivery<I, M>>
+        where I: Clone,
+              M: Clone
+    {
+        let mut unacked = self.lock();
+        let mut due = Vec::new();
+        for (id, entry) in unacked.iter_mut().filter(|(_, entry)| entry.nacked || entry.deadline <= now) {
+            entry.attempts += 1;
+            entry.deadline = now + entry.timeout;
+            entry.nacked = false;
+            self.audit(*id, &entry.to, AckOutcome::Resolved(DeliveryOutcome::Delivered(*id)));
+            due.push(Redelivery { from: entry.from.clone(), to: entry.to.clone(), msg: entry.msg.clone(), ack: self.handle(*id, entry.attempts) });
+        }
+        due
+    }
+    
+    pub(crate) fn len(&self) -> usize {
+        self.lock().len()
+    }
+    
+    /// Takes the acks and nacks so far into account.
+    pub(crate) fn settle(&self) {
+        drop(self.lock());
+    }
+    
+    fn audit(&self, id: MessageId, to: &I, outcome: AckOutcome) {
+        if let Some(store) = &self.store {
+            store.record(id, to, outcome);
+        }
+    }
+    
+    fn handle(&self, id: MessageId, attempt: u32) -> AckHandle {
+        AckHandle { id, attempt, settled: Arc::clone(&self.settled) }
+    }
+    
+    /// Locks the messages in flight once the acks and nacks so far have been applied to them.
+    fn lock(&self) -> MutexGuard<'_, HashMap<MessageId, Unacked<I, M>>> {
+        let mut unacked = self.unacked.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        for (id, acked) in lock(&self.settled).drain() {
+            if acked {
+                if let Some(entry) = unacked.remove(&id) {
+                    self.audit(id, &entry.to, AckOutcome::Acked);
+                }
+            } else if let Some(entry) = unacked.get_mut(&id) {
+                entry.nacked = true;
+                self.audit(id, &entry.to, AckOutcome::Nacked);
+            }
+        }
+        unacked
+    }
+}
+
+fn lock(settled: &Settled) -> MutexGuard<'_, HashMap<MessageId, bool>> {
+    settled.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
@@ -0,0 +1,46 @@
+use crate::clock::Deadline;
+use crate::colleague::Colleague;
+use crate::error::Error;
+use crate::mediator::sealed::{self, ConsultAs};
+use crate::message::DeliveryOutcome;
+
+/// Where a message came from, handed to [`Colleague::read_with_context`] so the recipient
+/// can answer without looking up the sender itself.
+pub struct ReplyContext<'a, T: Colleague> {
+    from: &'a T::Identifier,
+    to: &'a T::Identifier,
+    mediator: &'a T::Mediator,
+    deadline: Option<&'a Deadline>,
+}
+
+impl<'a, T: Colleague> ReplyContext<'a, T> {
+    pub(crate) fn new(from: &'a T::Identifier, to: &'a T::Identifier, mediator: &'a T::Mediator, deadline: Option<&'a Deadline>) -> Self {
+        Self { from, to, mediator, deadline }
+    }
+    
+    pub fn from(&self) -> &T::Identifier {
+        self.from
+    }
+    
+    /// The colleague reading the message.
+    pub fn to(&self) -> &T::Identifier {
+        self.to
+    }
+    
+    /// The mediator the message came through, e.g. to
+    /// [pass it on](crate::GenericMediator::transfer_message).
+    pub fn mediator(&self) -> &T::Mediator {
+        self.mediator
+    }
+    
+    /// The deadline the message was sent with, if any, for the handler to give up on work that
+    /// can't finish in time or to pass on.
+    pub fn deadline(&self) -> Option<&Deadline> {
+        self.deadline
+    }
+    
+    /// Consults the original sender as the recipient.
+    pub fn reply(&self, msg: T::Message) -> Result<DeliveryOutcome, Error> {
+        self.mediator.consultation_as(sealed::Token::new(), self.to, self.from, msg)
+    }
+}
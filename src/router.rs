@@ -0,0 +1,52 @@
+use std::sync::Arc;
+
+use crate::colleague::Colleague;
+
+type Matcher<C> = Arc<dyn Fn(&<C as Colleague>::Message) -> bool + Send + Sync>;
+
+/// Sends messages wherever their content says, see
+/// [`GenericMediator::consult_routed`](crate::GenericMediator::consult_routed).
+pub struct Router<C: Colleague> {
+    rules: Vec<Rule<C>>,
+}
+
+struct Rule<C: Colleague> {
+    matcher: Matcher<C>,
+    target: C::Identifier,
+}
+
+impl<C: Colleague> Router<C> {
+    pub fn new() -> Self {
+        Self { rules: Vec::new() }
+    }
+    
+    /// Sends messages `matcher` holds for to `target`, unless an earlier rule took them already.
+    pub fn rule(mut self, matcher: impl Fn(&C::Message) -> bool + Send + Sync + 'static, target: C::Identifier) -> Self {
+        self.rules.push(Rule { matcher: Arc::new(matcher), target });
+        self
+    }
+    
+    /// Where the first matching rule sends `msg`.
+    pub(crate) fn route(&self, msg: &C::Message) -> Option<&C::Identifier> {
+        self.rules.iter()
+            .find(|rule| (rule.matcher)(msg))
+            .map(|rule| &rule.target)
+    }
+}
+
+impl<C: Colleague> Default for Router<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: Colleague> Clone for Router<C>
+    where C::Identifier: Clone
+{
+    fn clone(&self) -> Self {
+        let rules = self.rules.iter()
+            .map(|rule| Rule { matcher: Arc::clone(&rule.matcher), target: rule.target.clone() })
+            .collect();
+        Self { rules }
+    }
+}
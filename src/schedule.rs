@@ -0,0 +1,80 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Instant;
+
+/// Consultations waiting for their time to come, soonest first, in the order they were
+/// scheduled when due at the same instant.
+pub(crate) struct Schedule<I, M> {
+    queue: Mutex<Queue<I, M>>,
+}
+
+struct Queue<I, M> {
+    heap: BinaryHeap<Reverse<Pending<I, M>>>,
+    scheduled: u64,
+}
+
+pub(crate) struct Pending<I, M> {
+    at: Instant,
+    seq: u64,
+    pub(crate) from: I,
+    pub(crate) to: I,
+    pub(crate) msg: M,
+}
+
+impl<I, M> Schedule<I, M> {
+    pub(crate) fn push(&self, at: Instant, from: I, to: I, msg: M) {
+        let mut queue = self.lock();
+        let seq = queue.scheduled;
+        queue.scheduled += 1;
+        queue.heap.push(Reverse(Pending { at, seq, from, to, msg }));
+    }
+    
+    /// Takes out everything due at `now`, soonest first.
+    pub(crate) fn due(&self, now: Instant) -> Vec<Pending<I, M>> {
+        let mut queue = self.lock();
+        let mut due = Vec::new();
+        while queue.heap.peek().is_some_and(|Reverse(pending)| pending.at <= now) {
+            let Some(Reverse(pending)) = queue.heap.pop() else {
+                break;
+            };
+            due.push(pending);
+        }
+        due
+    }
+    
+    pub(crate) fn len(&self) -> usize {
+        self.lock().heap.len()
+    }
+    
+    fn lock(&self) -> MutexGuard<'_, Queue<I, M>> {
+        self.queue.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl<I, M> Default for Schedule<I, M> {
+    fn default() -> Self {
+        Self { queue: Mutex::new(Queue { heap: BinaryHeap::new(), scheduled: 0 }) }
+    }
+}
+
+impl<I, M> PartialEq for Pending<I, M> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<I, M> Eq for Pending<I, M> {}
+
+impl<I, M> PartialOrd for Pending<I, M> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<I, M> Ord for Pending<I, M> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.at.cmp(&other.at)
+            .then_with(|| self.seq.cmp(&other.seq))
+    }
+}
@@ -0,0 +1,44 @@
+use std::ops::Deref;
+use std::sync::Arc;
+
+use crate::colleague::{Colleague, Registered};
+use crate::mediator::Mediator;
+
+/// A [`Registered`] handle that deregisters its colleague once the last clone is dropped.
+///
+/// Plain `Registered` clones don't count: the mediator keeps one itself, so only clones of
+/// this wrapper decide when the colleague goes away. Deregistration is by id, so if the id was
+/// [re-registered](Mediator::re_register) in the meantime the replacement is removed instead.
+pub struct Scoped<T: Colleague>(Arc<Lease<T>>);
+
+struct Lease<T: Colleague> {
+    registered: Registered<T>,
+    mediator: T::Mediator,
+}
+
+impl<T: Colleague> Scoped<T> {
+    pub(crate) fn new(registered: Registered<T>, mediator: T::Mediator) -> Self {
+        Self(Arc::new(Lease { registered, mediator }))
+    }
+}
+
+impl<T: Colleague> Drop for Lease<T> {
+    fn drop(&mut self) {
+        // Errors only mean the mediator is already unusable, there is nobody to report them to.
+        let _ = self.mediator.deregister(self.registered.id());
+    }
+}
+
+impl<T: Colleague> Clone for Scoped<T> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<T: Colleague> Deref for Scoped<T> {
+    type Target = Registered<T>;
+    
+    fn deref(&self) -> &Self::Target {
+        &self.0.registered
+    }
+}
@@ -0,0 +1,35 @@
+use crate::colleague::Colleague;
+use crate::error::Error;
+use crate::mediator::sealed::{self, ConsultAs};
+use crate::message::DeliveryOutcome;
+
+/// Sends messages as a colleague through its mediator without holding the colleague.
+pub struct Sender<T: Colleague> {
+    from: T::Identifier,
+    mediator: T::Mediator,
+}
+
+impl<T: Colleague> Sender<T> {
+    /// Only ever made from a [`Registered`](crate::Registered) handle, see
+    /// [`Registered::sender`](crate::Registered::sender), so it sends as someone who registered.
+    pub(crate) fn new(from: T::Identifier, mediator: T::Mediator) -> Self {
+        Self { from, mediator }
+    }
+    
+    pub fn from(&self) -> &T::Identifier {
+        &self.from
+    }
+    
+    pub fn send_msg(&self, to: &T::Identifier, msg: impl Into<T::Message>) -> Result<DeliveryOutcome, Error> {
+        self.mediator.consultation_as(sealed::Token::new(), &self.from, to, msg.into())
+    }
+}
+
+impl<T: Colleague> Clone for Sender<T>
+    where T::Identifier: Clone,
+          T::Mediator: Clone
+{
+    fn clone(&self) -> Self {
+        Self { from: self.from.clone(), mediator: self.mediator.clone() }
+    }
+}
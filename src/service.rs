@@ -0,0 +1,79 @@
+use std::fmt::Debug;
+use std::future::{self, Ready};
+use std::hash::Hash;
+use std::task::{Context, Poll};
+
+use crate::colleague::{Colleague, Registered};
+use crate::error::Error;
+use crate::generic::GenericMediator;
+use crate::mediator::sealed::{self, ConsultAs};
+use crate::message::{DeliveryOutcome, Readiness};
+
+/// Consultations from one colleague to another, as a service taking the message and answering
+/// with its [`DeliveryOutcome`]. With the `tower` feature it is a `tower::Service`, so
+/// timeouts, retries or rate limits can be stacked around delivery.
+///
+/// [`MediatorService::poll_ready`] never waits: a recipient that can't take a message right
+/// now fails it with [`Error::MailboxFull`], and one that is gone with [`Error::NotRegistered`].
+pub struct MediatorService<C: Colleague> {
+    mediator: GenericMediator<C>,
+    from: C::Identifier,
+    to: C::Identifier,
+}
+
+impl<C: Colleague> MediatorService<C>
+    where C::Identifier: Eq + Hash + Clone + Debug,
+          C::Request: Debug
+{
+    /// Consults `to` as `from`, through the mediator `from` is registered with.
+    pub fn new(from: &Registered<C>, to: C::Identifier) -> Self
+        where C: Colleague<Mediator = GenericMediator<C>>
+    {
+        Self { mediator: from.mediator(), from: from.id().clone(), to }
+    }
+    
+    pub fn to(&self) -> &C::Identifier {
+        &self.to
+    }
+    
+    pub fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        let ready = match self.mediator.poll_ready(&self.to) {
+            Ok(Readiness::Ready) => Ok(()),
+            Ok(Readiness::Full) => Err(Error::MailboxFull { id: format!("{:?}", self.to) }),
+            Ok(Readiness::Absent) => Err(Error::NotRegistered { id: format!("{:?}", self.to) }),
+            Err(e) => Err(e),
+        };
+        Poll::Ready(ready)
+    }
+    
+    /// Consults right away; the future only hands over the outcome.
+    pub fn call(&mut self, msg: C::Message) -> Ready<Result<DeliveryOutcome, Error>> {
+        future::ready(self.mediator.consultation_as(sealed::Token::new(), &self.from, &self.to, msg))
+    }
+}
+
+#[cfg(feature = "tower")]
+impl<C: Colleague> tower::Service<C::Message> for MediatorService<C>
+    where C::Identifier: Eq + Hash + Clone + Debug,
+          C::Request: Debug
+{
+    type Response = DeliveryOutcome;
+    type Error = Error;
+    type Future = Ready<Result<DeliveryOutcome, Error>>;
+    
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        MediatorService::poll_ready(self, cx)
+    }
+    
+    fn call(&mut self, msg: C::Message) -> Self::Future {
+        MediatorService::call(self, msg)
+    }
+}
+
+impl<C: Colleague> Clone for MediatorService<C>
+    where C::Identifier: Clone
+{
+    fn clone(&self) -> Self {
+        Self { mediator: self.mediator.clone(), from: self.from.clone(), to: self.to.clone() }
+    }
+}
@@ -0,0 +1,9 @@
+/// What a mediator cleaned up while shutting down.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct ShutdownReport {
+    /// Colleagues removed from the registry.
+    pub deregistered: usize,
+    /// Messages still queued in mailboxes, discarded through
+    /// [`Observer::on_drop_message`](crate::Observer::on_drop_message).
+    pub flushed: usize,
+}
@@ -0,0 +1,32 @@
+use std::collections::HashMap;
+
+use crate::deadletter::DeadLetter;
+use crate::group::GroupId;
+
+/// Owned copy of a mediator's state at one point in time, meant for `{:#?}` while debugging.
+#[derive(Debug, Clone)]
+pub struct RegistrySnapshot<I> {
+    pub ids: Vec<I>,
+    pub groups: HashMap<GroupId, Vec<I>>,
+    pub deadletters: usize,
+    pub shutting_down: bool,
+}
+
+/// Everything a mediator knows besides the live colleagues, for carrying it over into a fresh
+/// mediator on a hot reload, see [`GenericMediator::export_state`](crate::GenericMediator::export_state).
+///
+/// Preserved are the registered ids, group memberships, tags, roles, the fallback, the
+/// deadletter queue and where message ids continue from. Not preserved are the colleagues
+/// themselves, which have to be registered again under `ids`, along with whatever sits in their
+/// mailboxes, weak and pattern registrations, filters, transforms, quotas, metrics and the event log.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MediatorState<I, M> {
+    pub ids: Vec<I>,
+    pub groups: HashMap<GroupId, Vec<I>>,
+    pub tags: Vec<(I, Vec<String>)>,
+    pub roles: HashMap<String, Vec<I>>,
+    pub fallback: Option<I>,
+    pub deadletters: Vec<DeadLetter<I, M>>,
+    pub next_message_id: u64,
+}
@@ -0,0 +1,204 @@
+//! Helpers for end-to-end tests of code built on the mediator.
+
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::sync::Mutex;
+
+use crate::colleague::{Colleague, Managed, Registered};
+use crate::error::{ColleagueError, Error};
+use crate::event::{Event, EventKind};
+use crate::generic::GenericMediator;
+use crate::mediator::Mediator;
+use crate::message::{DeliveryOutcome, Message};
+use crate::registry::Registry;
+
+/// A colleague that only writes down the messages it reads, so tests can check what arrived
+/// without a colleague of their own. Each message is recorded as its `Debug` output.
+pub struct TestRecorder<I, M> {
+    id: I,
+    recorded: Mutex<Vec<String>>,
+    _message: PhantomData<fn(M)>,
+}
+
+impl<I, M> TestRecorder<I, M> {
+    pub fn new(id: I) -> Self {
+        Self { id, recorded: Mutex::new(Vec::new()), _message: PhantomData }
+    }
+    
+    /// Everything read so far, oldest first.
+    pub fn recorded(&self) -> Vec<String> {
+        self.recorded.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+    }
+}
+
+impl<I: Debug, M> Debug for TestRecorder<I, M> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "TestRecorder id:{:?}", self.id)
+    }
+}
+
+impl<I, M> Colleague for TestRecorder<I, M>
+    where I: Eq + Hash + Clone + Debug,
+          M: Debug
+{
+    type Identifier = I;
+    type Mediator = GenericMediator<Self>;
+    type Message = M;
+    type Request = ();
+    type Response = ();
+    
+    fn id(&self) -> &Self::Identifier {
+        &self.id
+    }
+    
+    fn belong_to(self, mediator: Self::Mediator) -> Managed<Self> {
+        Managed::new(self, mediator)
+    }
+    
+    fn read_msg(&self, msg: Self::Message) -> Result<(), ColleagueError> {
+        self.recorded.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+            .push(format!("{:?}", msg));
+        Ok(())
+    }
+    
+    fn handle_request(&self, _: Self::Request) -> Self::Response {}
+}
+
+/// What [`replay`] did, and where it went differently from the log.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ReplayReport<I> {
+    pub registered: usize,
+    pub deregistered: usize,
+    pub consulted: usize,
+    pub broadcast: usize,
+    pub divergences: Vec<Divergence<I>>,
+}
+
+impl<I> ReplayReport<I> {
+    /// Whether every consultation and broadcast came out the way it was recorded.
+    pub fn is_faithful(&self) -> bool {
+        self.divergences.is_empty()
+    }
+}
+
+/// A consultation or broadcast that didn't come out on replay the way it was recorded.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Divergence<I> {
+    Consultation {
+        /// Where in the log the consultation was recorded.
+        index: usize,
+        from: I,
+        to: I,
+        recorded: Result<DeliveryOutcome, Error>,
+        replayed: Result<DeliveryOutcome, Error>,
+    },
+    Broadcast {
+        /// Where in the log the broadcast was recorded.
+        index: usize,
+        from: I,
+        /// How many colleagues the broadcast reached back then.
+        recorded: usize,
+        replayed: Result<usize, Error>,
+    },
+}
+
+impl<I> Divergence<I> {
+    /// Where in the log the diverging consultation or broadcast was recorded.
+    pub fn index(&self) -> usize {
+        match self {
+            Divergence::Consultation { index, .. } | Divergence::Broadcast { index, .. } => *index,
+        }
+    }
+}
+
+/// Re-runs the registrations, deregistrations, renames, consultations and broadcasts of an
+/// [event log](crate::GenericMediator::event_log) against `mediator`, in order, e.g. to
+/// reproduce something recorded elsewhere with [`TestRecorder`]s in place of the colleagues
+/// that were there. `colleague` makes one for every id that registers; weak registrations are
+/// replayed as weak ones, and let go of once the log has them deregistered.
+///
+/// Consultations are replayed with the message as it was sent, headers included, as a plain
+/// consultation: priority, acknowledgements, time-to-live and deadlines stay behind, and
+/// quiet attempts, multicasts and reliable deliveries aren't recorded to begin with. Each one
+/// is compared with how it came out back then, rejection reasons included but not message
+/// ids, and each broadcast with how many colleagues it reached. To have deadletters come out
+/// the same, give `mediator` a deadletter queue if the recorded one had it.
+pub fn replay<C, R>(events: &[Event<C::Identifier, C::Message>], mediator: &mut GenericMediator<C, R>, mut colleague: impl FnMut(&C::Identifier) -> C) -> Result<ReplayReport<C::Identifier>, Error>
+    where C: Colleague<Mediator = GenericMediator<C, R>>,
+          R: Registry<C::Identifier, Registered<C>>,
+          C::Identifier: Eq + Hash + Clone + Debug,
+          C::Message: Clone,
+          C::Request: Debug
+{
+    let mut report = ReplayReport { registered: 0, deregistered: 0, consulted: 0, broadcast: 0, divergences: Vec::new() };
+    // Handles to broadcast from. Weak registrations only live as long as their handle does,
+    // so theirs are kept apart and dropped when the log has them deregistered.
+    let mut strong = HashMap::new();
+    let mut weak = HashMap::new();
+    for (index, event) in events.iter().enumerate() {
+        match &event.kind {
+            EventKind::Registered { id, weak: false } => {
+                weak.remove(id);
+                // A registration for a taken id was recorded as a takeover.
+                let managed = colleague(id).belong_to(mediator.clone());
+                let (registered, _) = mediator.re_register(id.clone(), managed)?;
+                strong.insert(id.clone(), registered);
+                report.registered += 1;
+            }
+            EventKind::Registered { id, weak: true } => {
+                weak.remove(id);
+                let managed = colleague(id).belong_to(mediator.clone());
+                weak.insert(id.clone(), mediator.register_weak(id.clone(), managed)?);
+                report.registered += 1;
+            }
+            EventKind::Deregistered { id } => {
+                if weak.remove(id).is_none() {
+                    mediator.deregister(id)?;
+                }
+                report.deregistered += 1;
+            }
+            EventKind::Renamed { old, new } => {
+                mediator.rename(old, new.clone())?;
+                for handles in [&mut strong, &mut weak] {
+                    if let Some(handle) = handles.remove(old) {
+                        handles.insert(new.clone(), handle);
+                    }
+                }
+            }
+            EventKind::Consulted { from, to, msg, outcome } => {
+                let replayed = mediator.consult_recorded(from, to, Message { headers: msg.headers.clone(), version: msg.version, body: msg.body.clone() });
+                report.consulted += 1;
+                if !same_outcome(outcome, &replayed) {
+                    report.divergences.push(Divergence::Consultation { index, from: from.clone(), to: to.clone(), recorded: outcome.clone(), replayed });
+                }
+            }
+            EventKind::Broadcast { from, msg, reached } => {
+                let replayed = match strong.get(from).or_else(|| weak.get(from)) {
+                    Some(handle) => mediator.broadcast(handle, msg.clone()),
+                    None => Err(Error::NotRegistered { id: format!("{:?}", from) }),
+                };
+                report.broadcast += 1;
+                if replayed.as_ref().ok() != Some(reached) {
+                    report.divergences.push(Divergence::Broadcast { index, from: from.clone(), recorded: *reached, replayed });
+                }
+            }
+            // What became of each delivery is part of the consultation or broadcast it belongs
+            // to, and comes out again when that is replayed.
+            EventKind::Superseded { .. } | EventKind::Delivered { .. } | EventKind::Deadletter { .. } => {}
+        }
+    }
+    Ok(report)
+}
+
+/// Whether a replayed consultation came out like the recorded one, regardless of the ids its
+/// messages got.
+fn same_outcome(recorded: &Result<DeliveryOutcome, Error>, replayed: &Result<DeliveryOutcome, Error>) -> bool {
+    match (recorded, replayed) {
+        (Ok(DeliveryOutcome::Rejected { colleague, reason, .. }), Ok(DeliveryOutcome::Rejected { colleague: now, reason: why, .. })) => colleague == now && reason == why,
+        (Ok(recorded), Ok(replayed)) => std::mem::discriminant(recorded) == std::mem::discriminant(replayed),
+        (Err(recorded), Err(replayed)) => recorded == replayed,
+        _ => false,
+    }
+}
@@ -0,0 +1,71 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use futures::stream::BoxStream;
+use tokio::task::AbortHandle;
+
+use crate::error::ColleagueError;
+
+/// Carries consultations between mediators in different processes, e.g. over a message broker,
+/// see [`GenericMediatorBuilder::transport`](crate::GenericMediatorBuilder::transport).
+///
+/// Every colleague registered with a mediator gets a topic of its own, which the mediator
+/// subscribes to; consultations to ids nobody here answers to are published to theirs.
+pub trait Transport: Send + Sync {
+    fn publish(&self, topic: &str, payload: Vec<u8>) -> Result<(), ColleagueError>;
+    /// Payloads published to `topic` from now on, in order, until the stream is dropped.
+    fn subscribe(&self, topic: &str) -> Result<BoxStream<'static, Vec<u8>>, ColleagueError>;
+}
+
+pub(crate) type Encode<I, M> = fn(&I, &M) -> Vec<u8>;
+pub(crate) type Decode<I, M> = fn(&[u8]) -> Option<(I, M)>;
+/// Starts the task delivering what arrives on a subscription to the colleague it was made for,
+/// through the mediator it is handed.
+pub(crate) type Spawn<I, T> = Box<dyn Fn(T, I, BoxStream<'static, Vec<u8>>) -> AbortHandle + Send + Sync>;
+
+/// A mediator's end of its [`Transport`], with a delivery task for every topic it listens on.
+/// `T` is the mediator the tasks deliver through.
+pub(crate) struct Remote<I, M, T> {
+    pub(crate) transport: Arc<dyn Transport>,
+    pub(crate) encode: Encode<I, M>,
+    spawn: Spawn<I, T>,
+    subscriptions: Mutex<HashMap<I, AbortHandle>>,
+}
+
+impl<I, M, T> Remote<I, M, T> {
+    pub(crate) fn new(transport: Arc<dyn Transport>, encode: Encode<I, M>, spawn: Spawn<I, T>) -> Self {
+        Self { transport, encode, spawn, subscriptions: Mutex::new(HashMap::new()) }
+    }
+    
+    /// Subscribes to the topic of `id` and has what arrives on it delivered through `mediator`
+    /// until [`Remote::unsubscribe`].
+    pub(crate) fn subscribe(&self, mediator: T, id: I) -> Result<(), ColleagueError>
+        where I: Eq + Hash + Clone + Debug
+    {
+        let payloads = self.transport.subscribe(&topic(&id))?;
+        let task = (self.spawn)(mediator, id.clone(), payloads);
+        if let Some(previous) = self.subscriptions().insert(id, task) {
+            previous.abort();
+        }
+        Ok(())
+    }
+    
+    pub(crate) fn unsubscribe(&self, id: &I)
+        where I: Eq + Hash
+    {
+        if let Some(task) = self.subscriptions().remove(id) {
+            task.abort();
+        }
+    }
+    
+    fn subscriptions(&self) -> MutexGuard<'_, HashMap<I, AbortHandle>> {
+        self.subscriptions.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// The topic consultations to `id` are published to.
+pub(crate) fn topic<I: Debug>(id: &I) -> String {
+    format!("mediator:{:?}", id)
+}
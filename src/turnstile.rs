@@ -0,0 +1,40 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Condvar, Mutex, MutexGuard};
+
+/// Ticket lock letting callers through one at a time, in the order they arrived.
+pub(crate) struct Turnstile {
+    next: AtomicU64,
+    serving: Mutex<u64>,
+    turn: Condvar,
+}
+
+impl Turnstile {
+    pub(crate) fn new() -> Self {
+        Self { next: AtomicU64::new(0), serving: Mutex::new(0), turn: Condvar::new() }
+    }
+    
+    /// Blocks until every caller that entered earlier has dropped its [`Turn`].
+    pub(crate) fn enter(&self) -> Turn<'_> {
+        let ticket = self.next.fetch_add(1, Ordering::SeqCst);
+        let mut serving = self.lock();
+        while *serving != ticket {
+            serving = self.turn.wait(serving).unwrap_or_else(|poisoned| poisoned.into_inner());
+        }
+        Turn { turnstile: self }
+    }
+    
+    fn lock(&self) -> MutexGuard<'_, u64> {
+        self.serving.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+pub(crate) struct Turn<'a> {
+    turnstile: &'a Turnstile,
+}
+
+impl Drop for Turn<'_> {
+    fn drop(&mut self) {
+        *self.turnstile.lock() += 1;
+        self.turnstile.turn.notify_all();
+    }
+}
@@ -0,0 +1,152 @@
+use std::fmt::{Debug, Formatter};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+use mediator_derive::Colleague;
+
+use crate::colleague::{Colleague, Registered};
+use crate::error::{ColleagueError, Error};
+use crate::generic::{GenericMediator, GenericMediatorBuilder};
+use crate::mediator::Mediator;
+use crate::message::{DeliveryOutcome, Message};
+use crate::watch::WatchHandle;
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UserId(pub(crate) String);
+
+impl UserId {
+    pub fn new(id: impl Into<String>) -> UserId {
+        Self(id.into())
+    }
+    
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for UserId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+#[derive(Colleague)]
+#[colleague(mediator = UserMediator, message = String, request = UserRequest, response = UserResponse, read_with_headers = on_envelope, read_msg_ref = on_borrowed, on_shutdown = on_shutdown)]
+pub struct User {
+    #[colleague(id)]
+    pub(crate) id: UserId,
+    pub(crate) received: AtomicUsize,
+}
+
+impl User {
+    pub fn new(id: UserId) -> User {
+        Self { id, received: AtomicUsize::new(0) }
+    }
+    
+    pub fn received(&self) -> usize {
+        self.received.load(Ordering::Relaxed)
+    }
+    
+    fn on_message(&self, msg: String) -> Result<(), ColleagueError> {
+        self.on_borrowed(&msg)
+    }
+    
+    fn on_borrowed(&self, msg: &str) -> Result<(), ColleagueError> {
+        self.received.fetch_add(1, Ordering::Relaxed);
+        println!("[{}] {}", self.id.0, msg);
+        Ok(())
+    }
+    
+    fn on_envelope(&self, msg: Message<String>) -> Result<(), ColleagueError> {
+        match msg.header("trace-id") {
+            Some(trace) => {
+                self.received.fetch_add(1, Ordering::Relaxed);
+                println!("[{}] {} (trace-id {})", self.id.0, msg.body, trace);
+                Ok(())
+            }
+            None => self.on_message(msg.body),
+        }
+    }
+    
+    fn on_shutdown(&self) {
+        println!("[{}] signing off", self.id.0);
+    }
+    
+    fn on_request(&self, req: UserRequest) -> UserResponse {
+        match req {
+            UserRequest::MessageCount => UserResponse::MessageCount(self.received()),
+        }
+    }
+}
+
+impl Clone for User {
+    fn clone(&self) -> Self {
+        Self { id: self.id.clone(), received: AtomicUsize::new(self.received()) }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum UserRequest {
+    MessageCount,
+}
+
+#[derive(Debug, Clone)]
+pub enum UserResponse {
+    MessageCount(usize),
+}
+
+impl Debug for User {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "User id:{}", self.id.0)
+    }
+}
+
+impl Registered<User> {
+    /// Takes anything that turns into a `String`, string literals included.
+    pub fn send_msg(&self, id: &UserId, msg: impl Into<String>) -> Result<DeliveryOutcome, Error> {
+        self.as_mediator().consultation(self, id, msg.into())
+    }
+    
+    pub fn ask(&self, id: &UserId, req: UserRequest) -> Result<UserResponse, Error> {
+        self.as_mediator().request(self, id, req)
+    }
+    
+    /// Has `f` called with every message delivered to `target`, see [`GenericMediator::watch`].
+    pub fn watch(&self, target: &UserId, f: impl Fn(&str) + Send + Sync + 'static) -> Result<WatchHandle, Error> {
+        self.as_mediator().watch(target, move |msg: &String| f(msg))
+    }
+}
+
+pub type UserMediator = GenericMediator<User>;
+pub type UserMediatorBuilder = GenericMediatorBuilder<User>;
+
+impl UserMediator {
+    /// Registers a fresh [`User`] for every id that isn't registered yet, e.g. ids restored
+    /// from [`UserMediator::export_ids`], and returns their handles.
+    pub fn import_ids(&mut self, ids: Vec<UserId>) -> Result<Vec<Registered<User>>, Error> {
+        let mut imported = Vec::new();
+        for id in ids {
+            if self.is_registered(&id)? {
+                continue;
+            }
+            let managed = User::new(id.clone()).belong_to(self.clone());
+            imported.push(self.register(id, managed)?);
+        }
+        Ok(imported)
+    }
+    
+    /// Registers a fresh [`User`] under an id of its own, `anonymous-<n>`, for colleagues
+    /// without a natural one. The numbers are drawn from a process-wide counter, so concurrent
+    /// registrations never clash; ids someone registered by hand are skipped.
+    pub fn register_anonymous(&self) -> Result<(UserId, Registered<User>), Error> {
+        loop {
+            let id = UserId::new(format!("anonymous-{}", ANONYMOUS.fetch_add(1, Ordering::Relaxed)));
+            match self.register(id.clone(), User::new(id.clone()).belong_to(self.clone())) {
+                Err(Error::AlreadyRegistered { .. }) => continue,
+                registered => return Ok((id, registered?)),
+            }
+        }
+    }
+}
+
+static ANONYMOUS: AtomicU64 = AtomicU64::new(0);
@@ -0,0 +1,75 @@
+use std::sync::{Arc, Weak};
+
+use crate::error::{Error, Operation};
+use crate::lock::{self, RwLock};
+
+type Watcher<M> = Arc<dyn Fn(&M) + Send + Sync>;
+
+/// Keeps a watch installed with [`GenericMediator::watch`](crate::GenericMediator::watch)
+/// going; dropping it stops the watch.
+pub struct WatchHandle {
+    unwatch: Option<Box<dyn FnOnce() + Send>>,
+}
+
+impl Drop for WatchHandle {
+    fn drop(&mut self) {
+        if let Some(unwatch) = self.unwatch.take() {
+            unwatch();
+        }
+    }
+}
+
+/// Who is told about deliveries to which colleague.
+pub(crate) struct Watchers<I, M> {
+    installed: u64,
+    watching: Vec<(u64, I, Watcher<M>)>,
+}
+
+impl<I, M> Watchers<I, M> {
+    /// The watchers of `target`, cloned out so none of them runs with the lock held.
+    pub(crate) fn of(&self, target: &I) -> Vec<Watcher<M>>
+        where I: PartialEq
+    {
+        self.watching.iter()
+            .filter(|(_, id, _)| id.eq(target))
+            .map(|(_, _, watcher)| Arc::clone(watcher))
+            .collect()
+    }
+}
+
+impl<I: PartialEq + Clone, M> Watchers<I, M> {
+    /// Carries the watches on `old` over to `new`, e.g. once a colleague is renamed.
+    pub(crate) fn rename(&mut self, old: &I, new: &I) {
+        for (_, id, _) in self.watching.iter_mut().filter(|(_, id, _)| id.eq(old)) {
+            *id = new.clone();
+        }
+    }
+}
+
+impl<I, M> Default for Watchers<I, M> {
+    fn default() -> Self {
+        Self { installed: 0, watching: Vec::new() }
+    }
+}
+
+/// Installs `watcher` on `target` for as long as the returned handle lives.
+pub(crate) fn watch<I, M>(watchers: &Arc<RwLock<Watchers<I, M>>>, target: I, watcher: Watcher<M>) -> Result<WatchHandle, Error>
+    where I: Send + Sync + 'static,
+          M: 'static
+{
+    let mut installed = lock::write(watchers, Operation::Watch)?;
+    let key = installed.installed;
+    installed.installed += 1;
+    installed.watching.push((key, target, watcher));
+    drop(installed);
+    
+    let watchers = Arc::downgrade(watchers);
+    let unwatch = move || {
+        if let Some(watchers) = Weak::upgrade(&watchers) {
+            lock::recover_write(&watchers)
+                .watching
+                .retain(|(installed, _, _)| *installed != key);
+        }
+    };
+    Ok(WatchHandle { unwatch: Some(Box::new(unwatch)) })
+}
@@ -0,0 +1,81 @@
+use std::any::type_name_of_val;
+use std::time::Duration;
+
+use futures::StreamExt;
+use mediator_play::{AsyncColleague, AsyncMediator, AsyncRegistered, AsyncUserMediator, Colleague, Error, Mediator, User, UserId, UserMediator};
+
+async fn join(mediator: &mut AsyncUserMediator, id: &str) -> Result<AsyncRegistered<User>, Error> {
+    let id = UserId::new(id);
+    let managed = AsyncColleague::belong_to(User::new(id.clone()), mediator.clone());
+    mediator.register(id, managed).await
+}
+
+#[tokio::test]
+async fn consultations_are_unboxed_futures() -> Result<(), Error> {
+    let mut mediator = AsyncUserMediator::default();
+    let sender = join(&mut mediator, "user-1").await?;
+    let recipient = join(&mut mediator, "user-2").await?;
+    
+    let to = UserId::new("user-2");
+    let delivery = mediator.consultation(&sender, &to, "no box involved".to_string());
+    let future = type_name_of_val(&delivery);
+    assert!(!future.contains("Box"), "{}", future);
+    delivery.await?;
+    assert_eq!(recipient.received(), 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn streams_yield_what_was_sent() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    let sender = mediator.register(UserId::new("user-1"), Colleague::belong_to(User::new(UserId::new("user-1")), mediator.clone()))?;
+    let id = UserId::new("user-2");
+    let (recipient, stream) = mediator.register_stream(id.clone(), Colleague::belong_to(User::new(id.clone()), mediator.clone()))?;
+    
+    sender.send_msg(&id, "first")?;
+    sender.send_msg(&id, "second")?;
+    assert_eq!(stream.take(2).collect::<Vec<_>>().await, ["first", "second"]);
+    assert_eq!(recipient.received(), 0);
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn awaits_a_registration_made_later() -> Result<(), Error> {
+    let mediator = AsyncUserMediator::default();
+    let mut registrar = mediator.clone();
+    let late = tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        join(&mut registrar, "user-1").await
+    });
+    
+    let id = UserId::new("user-1");
+    let registered = mediator.await_registration(&id, Duration::from_secs(1)).await?;
+    assert_eq!(Colleague::id(&*registered), &id);
+    late.await.expect("registering task panicked")?;
+    
+    let absent = UserId::new("user-2");
+    let gave_up = mediator.await_registration(&absent, Duration::from_millis(100)).await;
+    assert_eq!(gave_up.err(), Some(Error::RegistrationTimeout { id: format!("{:?}", absent), timeout: Duration::from_millis(100) }));
+    Ok(())
+}
+
+#[tokio::test]
+async fn spawned_colleagues_read_until_they_are_let_go() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    let sender = mediator.register(UserId::new("user-1"), Colleague::belong_to(User::new(UserId::new("user-1")), mediator.clone()))?;
+    let id = UserId::new("user-2");
+    let (recipient, task) = mediator.spawn_colleague(id.clone(), Colleague::belong_to(User::new(id.clone()), mediator.clone()))?;
+    
+    sender.send_msg(&id, "first")?;
+    sender.send_msg(&id, "second")?;
+    while recipient.received() < 2 {
+        tokio::task::yield_now().await;
+    }
+    assert!(!task.is_finished());
+    mediator.deregister(&id)?;
+    drop(recipient);
+    tokio::time::timeout(Duration::from_secs(5), task).await
+        .expect("the colleague's task outlived its stream")
+        .expect("colleague task panicked");
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use mediator_play::{Colleague, ColleagueError, DeliveryOutcome, Error, GenericMediator, Mediator, MockClock};
+
+/// Turns jobs down while it is jammed.
+#[derive(Colleague)]
+#[colleague(mediator = GenericMediator<Printer>, message = String)]
+struct Printer {
+    #[colleague(id)]
+    id: u32,
+    jammed: AtomicBool,
+}
+
+impl Printer {
+    fn new(id: u32, jammed: bool) -> Self {
+        Self { id, jammed: AtomicBool::new(jammed) }
+    }
+    
+    fn on_message(&self, _: String) -> Result<(), ColleagueError> {
+        if self.jammed.load(Ordering::Relaxed) {
+            return Err(ColleagueError::new("paper jam"));
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn breakers_trip_and_recover() -> Result<(), Error> {
+    let clock = MockClock::new();
+    let mediator = GenericMediator::<Printer>::builder()
+        .clock(clock.clone())
+        .circuit_breaker(2, Duration::from_secs(30))
+        .build();
+    let desk = mediator.register(0, Printer::new(0, false).belong_to(mediator.clone()))?;
+    let printer = mediator.register(1, Printer::new(1, true).belong_to(mediator.clone()))?;
+    
+    for _ in 0..2 {
+        assert!(matches!(mediator.consultation(&desk, &1, "report".to_string())?, DeliveryOutcome::Rejected { .. }));
+    }
+    printer.jammed.store(false, Ordering::Relaxed);
+    assert_eq!(mediator.consultation(&desk, &1, "report".to_string()).unwrap_err(), Error::CircuitOpen { id: "1".to_string() });
+    
+    clock.advance(Duration::from_secs(29));
+    assert!(matches!(mediator.consultation(&desk, &1, "report".to_string()), Err(Error::CircuitOpen { .. })));
+    clock.advance(Duration::from_secs(1));
+    assert!(matches!(mediator.consultation(&desk, &1, "report".to_string())?, DeliveryOutcome::Delivered(_)));
+    assert!(matches!(mediator.consultation(&desk, &1, "report".to_string())?, DeliveryOutcome::Delivered(_)));
+    Ok(())
+}
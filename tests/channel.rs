@@ -0,0 +1,60 @@
+use std::sync::mpsc::RecvTimeoutError;
+use std::thread;
+use std::time::Duration;
+
+use mediator_play::{Colleague, Error, Mediator, Registered, User, UserId, UserMediator};
+
+fn join(mediator: &UserMediator, id: &str) -> Result<Registered<User>, Error> {
+    let id = UserId::new(id);
+    mediator.register(id.clone(), User::new(id).belong_to(mediator.clone()))
+}
+
+#[test]
+fn receives_off_a_channel() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    let sender = join(&mediator, "user-1")?;
+    let id = UserId::new("user-2");
+    let (recipient, rx) = mediator.register_channel(id.clone(), User::new(id.clone()).belong_to(mediator.clone()))?;
+    
+    sender.send_msg(&id, "pulled, not pushed")?;
+    assert_eq!(rx.recv_timeout(Duration::from_secs(1)).as_deref(), Ok("pulled, not pushed"));
+    assert_eq!(rx.recv_timeout(Duration::from_millis(10)), Err(RecvTimeoutError::Timeout));
+    assert_eq!(recipient.received(), 0);
+    
+    drop(rx);
+    assert!(matches!(sender.send_msg(&id, "hung up")?.ok_or_err(), Err(Error::DeliveryRejected { .. })));
+    Ok(())
+}
+
+#[test]
+fn inboxes_gather_from_every_thread() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    let id = UserId::new("collector");
+    let (collector, inbox) = mediator.register_bounded_inbox(id.clone(), User::new(id.clone()).belong_to(mediator.clone()), 2)?;
+    let consumer = thread::spawn(move || inbox.iter().collect::<Vec<_>>());
+    
+    let senders = (1..=3)
+        .map(|s| {
+            let sender = join(&mediator, &format!("user-{}", s))?;
+            let to = id.clone();
+            Ok(thread::spawn(move || {
+                for i in 0..4 {
+                    while let Err(Error::MailboxFull { .. }) = sender.send_msg(&to, format!("{}.{}", s, i)) {
+                        thread::yield_now();
+                    }
+                }
+            }))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    for sender in senders {
+        sender.join().unwrap();
+    }
+    mediator.deregister(&id)?;
+    drop(collector);
+    
+    let mut received = consumer.join().unwrap();
+    received.sort();
+    let sent: Vec<_> = (1..=3).flat_map(|s| (0..4).map(move |i| format!("{}.{}", s, i))).collect();
+    assert_eq!(received, sent);
+    Ok(())
+}
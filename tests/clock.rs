@@ -0,0 +1,138 @@
+use std::time::Duration;
+
+use mediator_play::{Clock, Colleague, DeadletterReason, DeliveryOutcome, Error, Mediator, MessageId, MockClock, Registered, User, UserId, UserMediator};
+
+fn join(mediator: &UserMediator, id: &str) -> Result<Registered<User>, Error> {
+    let id = UserId::new(id);
+    mediator.register(id.clone(), User::new(id).belong_to(mediator.clone()))
+}
+
+fn join_with_mailbox(mediator: &UserMediator, id: &str, capacity: usize) -> Result<Registered<User>, Error> {
+    let id = UserId::new(id);
+    mediator.register(id.clone(), User::new(id).belong_to(mediator.clone()).with_mailbox(capacity))
+}
+
+#[test]
+fn quotas_refill_with_each_window() -> Result<(), Error> {
+    let clock = MockClock::new();
+    let mediator = UserMediator::builder()
+        .clock(clock.clone())
+        .quota_window(Duration::from_secs(60))
+        .build();
+    let sender = join(&mediator, "user-1")?;
+    let recipient = join(&mediator, "user-2")?;
+    mediator.set_quota(&UserId::new("user-1"), 2)?;
+    
+    let to = UserId::new("user-2");
+    sender.send_msg(&to, "one")?;
+    sender.send_msg(&to, "two")?;
+    assert_eq!(sender.send_msg(&to, "three").unwrap_err(), Error::QuotaExceeded { id: format!("{:?}", UserId::new("user-1")) });
+    assert_eq!(recipient.received(), 2);
+    
+    clock.advance(Duration::from_secs(60));
+    sender.send_msg(&to, "three")?;
+    assert_eq!(recipient.received(), 3);
+    Ok(())
+}
+
+#[test]
+fn mock_clocks_only_move_when_told() {
+    let clock = MockClock::new();
+    let shared = clock.clone();
+    let start = clock.now();
+    assert_eq!(clock.now(), start);
+    
+    shared.advance(Duration::from_secs(5));
+    assert_eq!(clock.now() - start, Duration::from_secs(5));
+    clock.sleep(Duration::from_secs(3600));
+    assert_eq!(shared.now() - start, Duration::from_secs(3605));
+}
+
+#[test]
+fn stale_messages_expire_in_the_mailbox() -> Result<(), Error> {
+    let clock = MockClock::new();
+    let mediator = UserMediator::builder()
+        .clock(clock.clone())
+        .build();
+    let sender = join(&mediator, "user-1")?;
+    let recipient = join_with_mailbox(&mediator, "user-2", 4)?;
+    let to = UserId::new("user-2");
+    mediator.consult_with_ttl(&sender, &to, "quick".to_string(), Duration::from_secs(10))?;
+    mediator.consult_with_ttl(&sender, &to, "whenever".to_string(), Duration::from_secs(60))?;
+    
+    clock.advance(Duration::from_secs(30));
+    assert_eq!(recipient.drain(usize::MAX), 1);
+    assert_eq!(recipient.received(), 1);
+    let letters = mediator.deadletters()?;
+    assert_eq!(letters.len(), 1);
+    assert_eq!((letters[0].msg.as_str(), letters[0].reason), ("quick", DeadletterReason::Expired));
+    Ok(())
+}
+
+#[test]
+fn duplicates_are_dropped_within_the_window() -> Result<(), Error> {
+    let clock = MockClock::new();
+    let mediator = UserMediator::builder()
+        .clock(clock.clone())
+        .dedupe(Duration::from_secs(60), 16)
+        .build();
+    let sender = join(&mediator, "user-1")?;
+    let recipient = join(&mediator, "user-2")?;
+    let to = UserId::new("user-2");
+    let id = MessageId::new(7 << 32);
+    
+    assert!(matches!(mediator.consult_idempotent(&sender, &to, "retried".to_string(), id)?, DeliveryOutcome::Delivered(_)));
+    clock.advance(Duration::from_secs(30));
+    assert!(matches!(mediator.consult_idempotent(&sender, &to, "retried".to_string(), id)?, DeliveryOutcome::Duplicate(_)));
+    assert_eq!(recipient.received(), 1);
+    
+    clock.advance(Duration::from_secs(31));
+    assert!(matches!(mediator.consult_idempotent(&sender, &to, "retried".to_string(), id)?, DeliveryOutcome::Delivered(_)));
+    assert_eq!(recipient.received(), 2);
+    Ok(())
+}
+
+#[test]
+fn scheduled_messages_wait_for_their_time() -> Result<(), Error> {
+    let clock = MockClock::new();
+    let mediator = UserMediator::builder()
+        .clock(clock.clone())
+        .build();
+    let sender = join(&mediator, "user-1")?;
+    let recipient = join(&mediator, "user-2")?;
+    let to = UserId::new("user-2");
+    mediator.consult_after(&sender, &to, "stand up".to_string(), Duration::from_secs(60))?;
+    mediator.consult_at(&sender, &to, "lunch".to_string(), clock.now() + Duration::from_secs(120))?;
+    
+    assert_eq!(mediator.tick(clock.now())?, 0);
+    clock.advance(Duration::from_secs(59));
+    assert_eq!(mediator.tick(clock.now())?, 0);
+    assert_eq!(recipient.received(), 0);
+    clock.advance(Duration::from_secs(1));
+    assert_eq!(mediator.tick(clock.now())?, 1);
+    assert_eq!(recipient.received(), 1);
+    clock.advance(Duration::from_secs(600));
+    assert_eq!(mediator.tick(clock.now())?, 1);
+    assert_eq!(mediator.tick(clock.now())?, 0);
+    assert_eq!(recipient.received(), 2);
+    Ok(())
+}
+
+#[test]
+fn idle_colleagues_are_reaped() -> Result<(), Error> {
+    let clock = MockClock::new();
+    let mediator = UserMediator::builder()
+        .clock(clock.clone())
+        .build();
+    let sender = join(&mediator, "user-1")?;
+    join(&mediator, "user-2")?;
+    join(&mediator, "user-3")?;
+    
+    clock.advance(Duration::from_secs(90));
+    sender.send_msg(&UserId::new("user-2"), "still there?")?;
+    clock.advance(Duration::from_secs(30));
+    assert_eq!(mediator.reap_idle(Duration::from_secs(60))?, [UserId::new("user-3")]);
+    assert!(!mediator.is_registered(&UserId::new("user-3"))?);
+    assert_eq!(mediator.registered_count()?, 2);
+    Ok(())
+}
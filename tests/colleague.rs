@@ -0,0 +1,608 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use mediator_play::{Colleague, ColleagueError, DeliveryOutcome, Error, GenericMediator, Managed, MapMediator, Mediator, Message, OrderedMediator};
+
+/// A colleague keyed by a plain `u64`.
+#[derive(Colleague)]
+#[colleague(mediator = MapMediator<Sensor>, message = f64)]
+struct Sensor {
+    #[colleague(id)]
+    id: u64,
+    readings: Mutex<Vec<f64>>,
+}
+
+impl Sensor {
+    fn new(id: u64) -> Self {
+        Self { id, readings: Mutex::new(Vec::new()) }
+    }
+    
+    fn on_message(&self, reading: f64) -> Result<(), ColleagueError> {
+        self.readings.lock().unwrap().push(reading);
+        Ok(())
+    }
+}
+
+#[test]
+fn mapped_ids_reach_the_colleagues_behind_them() -> Result<(), Error> {
+    let mediator = MapMediator::<Sensor>::default();
+    let named = mediator.clone().map_identifier(|name: &str| name.trim_start_matches("sensor-").parse::<u64>().unwrap());
+    let first = named.register("sensor-1", Sensor::new(1).belong_to(mediator.clone()))?;
+    let second = named.register("sensor-2", Sensor::new(2).belong_to(mediator.clone()))?;
+    
+    named.consultation(&first, &"sensor-2", 19.5)?;
+    assert_eq!(*second.readings.lock().unwrap(), [19.5]);
+    assert!(mediator.is_registered(&2)?);
+    assert!(!named.is_registered(&"sensor-3")?);
+    Ok(())
+}
+
+/// Written out by hand rather than derived; all it takes to get a mediator is naming one.
+struct Thermostat {
+    room: String,
+    target: Mutex<f32>,
+}
+
+impl Colleague for Thermostat {
+    type Identifier = String;
+    type Mediator = MapMediator<Thermostat>;
+    type Message = f32;
+    type Request = ();
+    type Response = f32;
+    
+    fn id(&self) -> &String {
+        &self.room
+    }
+    
+    fn belong_to(self, mediator: Self::Mediator) -> Managed<Self> {
+        Managed::new(self, mediator)
+    }
+    
+    fn read_msg(&self, target: f32) -> Result<(), ColleagueError> {
+        *self.target.lock().unwrap() = target;
+        Ok(())
+    }
+    
+    fn handle_request(&self, _: ()) -> f32 {
+        *self.target.lock().unwrap()
+    }
+}
+
+#[test]
+fn map_mediators_serve_any_colleague() -> Result<(), Error> {
+    let mediator = MapMediator::<Thermostat>::default();
+    let thermostat = |room: &str| Thermostat { room: room.to_string(), target: Mutex::new(20.0) }.belong_to(mediator.clone());
+    let hall = mediator.register("hall".to_string(), thermostat("hall"))?;
+    mediator.register("kitchen".to_string(), thermostat("kitchen"))?;
+    
+    mediator.consultation(&hall, &"kitchen".to_string(), 18.5)?;
+    assert_eq!(mediator.request(&hall, &"kitchen".to_string(), ())?, 18.5);
+    assert_eq!(mediator.broadcast(&hall, 21.0)?, 1);
+    assert_eq!(mediator.request(&hall, &"kitchen".to_string(), ())?, 21.0);
+    Ok(())
+}
+
+/// Files every message that has something in it.
+#[derive(Colleague)]
+#[colleague(mediator = MapMediator<Clerk>, message = String)]
+struct Clerk {
+    #[colleague(id)]
+    id: &'static str,
+    filed: Mutex<Vec<String>>,
+}
+
+impl Clerk {
+    fn new(id: &'static str) -> Self {
+        Self { id, filed: Mutex::new(Vec::new()) }
+    }
+    
+    fn on_message(&self, msg: String) -> Result<(), ColleagueError> {
+        if msg.is_empty() {
+            return Err(ColleagueError::new("nothing to file"));
+        }
+        self.filed.lock().unwrap().push(msg);
+        Ok(())
+    }
+}
+
+#[test]
+fn delivers_between_u64_keyed_colleagues() -> Result<(), Error> {
+    let mediator = MapMediator::<Sensor>::default();
+    let first = mediator.register(1, Sensor::new(1).belong_to(mediator.clone()))?;
+    let second = mediator.register(2, Sensor::new(2).belong_to(mediator.clone()))?;
+    
+    mediator.consultation(&first, &2, 21.5)?;
+    assert_eq!(*second.readings.lock().unwrap(), [21.5]);
+    assert_eq!(mediator.registered_count()?, 2);
+    assert!(matches!(mediator.consultation(&first, &3, 0.0), Err(Error::NotRegistered { .. })));
+    Ok(())
+}
+
+#[test]
+fn rejections_reach_the_sender() -> Result<(), Error> {
+    let mediator = MapMediator::<Clerk>::default();
+    let sender = mediator.register("clerk-1", Clerk::new("clerk-1").belong_to(mediator.clone()))?;
+    let recipient = mediator.register("clerk-2", Clerk::new("clerk-2").belong_to(mediator.clone()))?;
+    
+    let outcome = mediator.consultation(&sender, &"clerk-2", String::new())?;
+    let DeliveryOutcome::Rejected { colleague, reason, .. } = outcome.clone() else {
+        panic!("an empty message was taken: {:?}", outcome);
+    };
+    assert_eq!((colleague.as_str(), reason.as_str()), (r#""clerk-2""#, "nothing to file"));
+    assert_eq!(outcome.ok_or_err().unwrap_err(), Error::DeliveryRejected { id: colleague, reason });
+    
+    mediator.consultation(&sender, &"clerk-2", "form 27b".to_string())?.ok_or_err()?;
+    assert_eq!(*recipient.filed.lock().unwrap(), ["form 27b"]);
+    Ok(())
+}
+
+/// Keeps the trace id of every message it reads, if it came with one.
+#[derive(Colleague)]
+#[colleague(mediator = MapMediator<Tracer>, message = String, read_with_headers = on_envelope)]
+struct Tracer {
+    #[colleague(id)]
+    id: &'static str,
+    traces: Mutex<Vec<Option<String>>>,
+}
+
+impl Tracer {
+    fn new(id: &'static str) -> Self {
+        Self { id, traces: Mutex::new(Vec::new()) }
+    }
+    
+    fn on_message(&self, _: String) -> Result<(), ColleagueError> {
+        self.traces.lock().unwrap().push(None);
+        Ok(())
+    }
+    
+    fn on_envelope(&self, msg: Message<String>) -> Result<(), ColleagueError> {
+        self.traces.lock().unwrap().push(msg.header("trace-id").map(str::to_string));
+        Ok(())
+    }
+}
+
+#[test]
+fn headers_reach_the_recipient() -> Result<(), Error> {
+    let mediator = MapMediator::<Tracer>::default();
+    let sender = mediator.register("tracer-1", Tracer::new("tracer-1").belong_to(mediator.clone()))?;
+    let recipient = mediator.register("tracer-2", Tracer::new("tracer-2").belong_to(mediator.clone()))?;
+    
+    let traced = Message::new("traced hello".to_string()).with_header("trace-id", "abc-123");
+    mediator.consult_with_headers(&sender, &"tracer-2", traced)?;
+    mediator.consult_with_headers(&sender, &"tracer-2", Message::new("untraced".to_string()))?;
+    assert_eq!(*recipient.traces.lock().unwrap(), [Some("abc-123".to_string()), None]);
+    Ok(())
+}
+
+/// A payload that counts how often it was cloned.
+#[derive(Debug)]
+struct Bulky {
+    body: String,
+    clones: Arc<AtomicUsize>,
+}
+
+impl Clone for Bulky {
+    fn clone(&self) -> Self {
+        self.clones.fetch_add(1, Ordering::SeqCst);
+        Self { body: self.body.clone(), clones: Arc::clone(&self.clones) }
+    }
+}
+
+/// Measures what it is lent without keeping it.
+#[derive(Colleague)]
+#[colleague(mediator = MapMediator<Reader>, message = Bulky, read_msg_ref = on_lent)]
+struct Reader {
+    #[colleague(id)]
+    id: &'static str,
+    read: AtomicUsize,
+}
+
+impl Reader {
+    fn new(id: &'static str) -> Self {
+        Self { id, read: AtomicUsize::new(0) }
+    }
+    
+    fn on_message(&self, msg: Bulky) -> Result<(), ColleagueError> {
+        self.on_lent(&msg)
+    }
+    
+    fn on_lent(&self, msg: &Bulky) -> Result<(), ColleagueError> {
+        self.read.fetch_add(msg.body.len(), Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+#[test]
+fn lent_broadcasts_clone_nothing() -> Result<(), Error> {
+    let mediator = MapMediator::<Reader>::default();
+    let sender = mediator.register("reader-1", Reader::new("reader-1").belong_to(mediator.clone()))?;
+    let recipients = ["reader-2", "reader-3", "reader-4"].into_iter()
+        .map(|id| mediator.register(id, Reader::new(id).belong_to(mediator.clone())))
+        .collect::<Result<Vec<_>, _>>()?;
+    
+    let clones = Arc::new(AtomicUsize::new(0));
+    let msg = Bulky { body: "x".repeat(1 << 20), clones: Arc::clone(&clones) };
+    assert_eq!(mediator.broadcast_ref(&sender, &msg)?, 3);
+    assert_eq!(clones.load(Ordering::SeqCst), 0);
+    for recipient in &recipients {
+        assert_eq!(recipient.read.load(Ordering::SeqCst), 1 << 20);
+    }
+    Ok(())
+}
+
+/// Falls over on messages it can't stand.
+#[derive(Colleague)]
+#[colleague(mediator = MapMediator<Fragile>, message = String)]
+struct Fragile {
+    #[colleague(id)]
+    id: &'static str,
+}
+
+impl Fragile {
+    fn on_message(&self, msg: String) -> Result<(), ColleagueError> {
+        assert_ne!(msg, "boom", "{} can't stand it", self.id);
+        Ok(())
+    }
+}
+
+#[test]
+fn panicking_handlers_are_deregistered() -> Result<(), Error> {
+    let mediator = MapMediator::<Fragile>::default();
+    let sender = mediator.register("fragile-1", Fragile { id: "fragile-1" }.belong_to(mediator.clone()))?;
+    mediator.register("fragile-2", Fragile { id: "fragile-2" }.belong_to(mediator.clone()))?;
+    
+    mediator.consultation(&sender, &"fragile-2", "fine".to_string())?.ok_or_err()?;
+    let panicked = mediator.consultation(&sender, &"fragile-2", "boom".to_string());
+    assert_eq!(panicked.unwrap_err(), Error::HandlerPanicked { id: r#""fragile-2""#.to_string() });
+    assert!(!mediator.is_registered(&"fragile-2")?);
+    assert!(matches!(mediator.consultation(&sender, &"fragile-2", "fine".to_string()), Err(Error::NotRegistered { .. })));
+    Ok(())
+}
+
+/// Keyed by a number, to be kept in order.
+#[derive(Colleague)]
+#[colleague(mediator = OrderedMediator<Station>, message = String)]
+struct Station {
+    #[colleague(id)]
+    id: u32,
+}
+
+impl Station {
+    fn on_message(&self, _: String) -> Result<(), ColleagueError> {
+        Ok(())
+    }
+}
+
+#[test]
+fn ordered_mediators_list_ids_sorted() -> Result<(), Error> {
+    let mediator = OrderedMediator::<Station>::default();
+    for id in [42, 7, 19, 3] {
+        mediator.register(id, Station { id }.belong_to(mediator.clone()))?;
+    }
+    
+    assert_eq!(mediator.registered_ids()?, [3, 7, 19, 42]);
+    let ids: Vec<_> = mediator.entries()?.into_iter().map(|(id, _)| id).collect();
+    assert_eq!(ids, [3, 7, 19, 42]);
+    Ok(())
+}
+
+/// Puts streamed payloads back together.
+#[derive(Colleague)]
+#[colleague(mediator = GenericMediator<Uploader>, message = String, read_chunk = on_chunk, read_end = on_end)]
+struct Uploader {
+    #[colleague(id)]
+    id: u32,
+    partial: Mutex<Vec<u8>>,
+    complete: Mutex<Vec<String>>,
+}
+
+impl Uploader {
+    fn new(id: u32) -> Self {
+        Self { id, partial: Mutex::new(Vec::new()), complete: Mutex::new(Vec::new()) }
+    }
+    
+    fn on_message(&self, _: String) -> Result<(), ColleagueError> {
+        Ok(())
+    }
+    
+    fn on_chunk(&self, chunk: Vec<u8>) -> Result<(), ColleagueError> {
+        self.partial.lock().unwrap().extend(chunk);
+        Ok(())
+    }
+    
+    fn on_end(&self) -> Result<(), ColleagueError> {
+        let payload = std::mem::take(&mut *self.partial.lock().unwrap());
+        self.complete.lock().unwrap().push(String::from_utf8(payload).map_err(|e| ColleagueError::new(e.to_string()))?);
+        Ok(())
+    }
+}
+
+#[test]
+fn streamed_chunks_are_reassembled() -> Result<(), Error> {
+    let mediator = GenericMediator::<Uploader>::builder()
+        .deadletter(true)
+        .build();
+    let client = mediator.register(0, Uploader::new(0).belong_to(mediator.clone()))?;
+    let server = mediator.register(1, Uploader::new(1).belong_to(mediator.clone()))?;
+    
+    let chunks = ["large ", "payload ", "in pieces"].map(|piece| piece.as_bytes().to_vec());
+    assert!(matches!(mediator.consult_stream(&client, &1, chunks.clone().into_iter())?, DeliveryOutcome::Delivered(_)));
+    assert_eq!(*server.complete.lock().unwrap(), ["large payload in pieces"]);
+    
+    let mut pulled = 0;
+    let counted = chunks.into_iter().inspect(|_| pulled += 1);
+    assert!(matches!(mediator.consult_stream(&client, &2, counted)?, DeliveryOutcome::Deadlettered(_)));
+    assert_eq!(pulled, 0);
+    Ok(())
+}
+
+/// Answers every question with its own id.
+#[derive(Colleague)]
+#[colleague(mediator = GenericMediator<Responder>, message = String, request = (), response = u32)]
+struct Responder {
+    #[colleague(id)]
+    id: u32,
+}
+
+impl Responder {
+    fn on_message(&self, _: String) -> Result<(), ColleagueError> {
+        Ok(())
+    }
+    
+    fn on_request(&self, _: ()) -> u32 {
+        self.id
+    }
+}
+
+#[test]
+fn scatter_gather_collects_every_answer() -> Result<(), Error> {
+    let mediator = GenericMediator::<Responder>::default();
+    let asker = mediator.register(0, Responder { id: 0 }.belong_to(mediator.clone()))?;
+    for id in 1..=3 {
+        mediator.register_worker("census", Responder { id }.belong_to(mediator.clone()))?;
+    }
+    
+    let mut answers = mediator.scatter_gather(&asker, "census", ())?;
+    answers.sort();
+    assert_eq!(answers, [1, 2, 3]);
+    assert!(mediator.scatter_gather(&asker, "nobody", ())?.is_empty());
+    Ok(())
+}
+
+/// Keeps every message it hears, to show they are all the same allocation.
+#[derive(Colleague)]
+#[colleague(mediator = MapMediator<Listener>, message = Arc<str>)]
+struct Listener {
+    #[colleague(id)]
+    id: u32,
+    heard: Mutex<Vec<Arc<str>>>,
+}
+
+impl Listener {
+    fn new(id: u32) -> Self {
+        Self { id, heard: Mutex::new(Vec::new()) }
+    }
+    
+    fn on_message(&self, msg: Arc<str>) -> Result<(), ColleagueError> {
+        self.heard.lock().unwrap().push(msg);
+        Ok(())
+    }
+}
+
+#[test]
+fn shared_messages_fan_out_without_copies() -> Result<(), Error> {
+    let mediator = MapMediator::<Listener>::default();
+    let speaker = mediator.register(0, Listener::new(0).belong_to(mediator.clone()))?;
+    let listeners = (1..=50)
+        .map(|id| mediator.register(id, Listener::new(id).belong_to(mediator.clone())))
+        .collect::<Result<Vec<_>, _>>()?;
+    
+    let news: Arc<str> = Arc::from("the same text for everyone");
+    assert_eq!(mediator.broadcast(&speaker, Arc::clone(&news))?, 50);
+    assert_eq!(Arc::strong_count(&news), 51);
+    assert!(listeners.iter().all(|listener| Arc::ptr_eq(&listener.heard.lock().unwrap()[0], &news)));
+    Ok(())
+}
+
+/// Answers pings only while it is well.
+#[derive(Colleague)]
+#[colleague(mediator = MapMediator<Patient>, message = String, on_ping = is_well)]
+struct Patient {
+    #[colleague(id)]
+    id: u32,
+    well: AtomicBool,
+}
+
+impl Patient {
+    fn on_message(&self, _: String) -> Result<(), ColleagueError> {
+        Ok(())
+    }
+    
+    fn is_well(&self) -> bool {
+        self.well.load(Ordering::Relaxed)
+    }
+}
+
+#[test]
+fn pings_ask_the_colleague_how_it_is() -> Result<(), Error> {
+    let mediator = MapMediator::<Patient>::default();
+    let patient = mediator.register(1, Patient { id: 1, well: AtomicBool::new(true) }.belong_to(mediator.clone()))?;
+    let sensors = MapMediator::<Sensor>::default();
+    sensors.register(1, Sensor::new(1).belong_to(sensors.clone()))?;
+    
+    assert!(mediator.ping(&1)?);
+    patient.well.store(false, Ordering::Relaxed);
+    assert!(!mediator.ping(&1)?);
+    assert_eq!(mediator.ping(&2).unwrap_err(), Error::NotRegistered { id: "2".to_string() });
+    // Colleagues that don't say otherwise are always well.
+    assert!(sensors.ping(&1)?);
+    Ok(())
+}
+
+/// Registers a newcomer under every id it is sent, through the mediator it was handed.
+#[derive(Colleague)]
+#[colleague(mediator = MapMediator<Recruiter>, message = u32)]
+struct Recruiter {
+    #[colleague(id)]
+    id: u32,
+    mediator: MapMediator<Recruiter>,
+}
+
+impl Recruiter {
+    fn new(id: u32, mediator: &MapMediator<Recruiter>) -> Self {
+        Self { id, mediator: mediator.clone() }
+    }
+    
+    fn on_message(&self, newcomer: u32) -> Result<(), ColleagueError> {
+        let recruit = Recruiter::new(newcomer, &self.mediator).belong_to(self.mediator.clone());
+        self.mediator.register(newcomer, recruit)
+            .map(drop)
+            .map_err(|e| ColleagueError::new(e.to_string()))
+    }
+}
+
+#[test]
+fn handlers_register_through_a_shared_mediator() -> Result<(), Error> {
+    let mediator = MapMediator::<Recruiter>::default();
+    let first = mediator.register(1, Recruiter::new(1, &mediator).belong_to(mediator.clone()))?;
+    mediator.register(2, Recruiter::new(2, &mediator).belong_to(mediator.clone()))?;
+    
+    mediator.consultation(&first, &2, 3)?.ok_or_err()?;
+    assert!(mediator.is_registered(&3)?);
+    let third = mediator.register(4, Recruiter::new(4, &mediator).belong_to(mediator.clone()))?;
+    mediator.consultation(&third, &3, 5)?.ok_or_err()?;
+    assert_eq!(mediator.registered_count()?, 5);
+    Ok(())
+}
+
+/// Answers every "ping" it reads with a "pong" to `peer`, from inside its handler.
+#[derive(Colleague)]
+#[colleague(mediator = MapMediator<Replier>, message = String)]
+struct Replier {
+    #[colleague(id)]
+    id: u32,
+    peer: u32,
+    mediator: MapMediator<Replier>,
+    read: Mutex<Vec<String>>,
+}
+
+impl Replier {
+    fn new(id: u32, peer: u32, mediator: &MapMediator<Replier>) -> Self {
+        Self { id, peer, mediator: mediator.clone(), read: Mutex::new(Vec::new()) }
+    }
+    
+    fn on_message(&self, msg: String) -> Result<(), ColleagueError> {
+        self.read.lock().unwrap().push(msg.clone());
+        if msg != "ping" {
+            return Ok(());
+        }
+        let reply = || -> Result<(), Error> {
+            let this = self.mediator.get(&self.id)?.expect("a colleague reading is registered");
+            self.mediator.consultation(&this, &self.peer, "pong".to_string())?.ok_or_err().map(drop)
+        };
+        reply().map_err(|e| ColleagueError::new(e.to_string()))
+    }
+}
+
+#[test]
+fn handlers_send_while_a_delivery_is_under_way() -> Result<(), Error> {
+    let mediator = MapMediator::<Replier>::default();
+    let first = mediator.register(1, Replier::new(1, 2, &mediator).belong_to(mediator.clone()))?;
+    let second = mediator.register(2, Replier::new(2, 1, &mediator).belong_to(mediator.clone()))?;
+    
+    mediator.consultation(&first, &2, "ping".to_string())?.ok_or_err()?;
+    assert_eq!(*second.read.lock().unwrap(), ["ping"]);
+    assert_eq!(*first.read.lock().unwrap(), ["pong"]);
+    Ok(())
+}
+
+/// Written against version 1 of its messages, and nothing else.
+struct Ledger {
+    id: u32,
+    entries: Mutex<Vec<String>>,
+}
+
+impl Colleague for Ledger {
+    type Identifier = u32;
+    type Mediator = MapMediator<Ledger>;
+    type Message = String;
+    type Request = ();
+    type Response = ();
+    
+    fn id(&self) -> &u32 {
+        &self.id
+    }
+    
+    fn belong_to(self, mediator: Self::Mediator) -> Managed<Self> {
+        Managed::new(self, mediator)
+    }
+    
+    fn read_msg(&self, entry: String) -> Result<(), ColleagueError> {
+        self.entries.lock().unwrap().push(entry);
+        Ok(())
+    }
+    
+    fn schema_version(&self) -> u16 {
+        1
+    }
+    
+    fn handle_request(&self, _: ()) {}
+}
+
+#[test]
+fn other_schema_versions_are_refused() -> Result<(), Error> {
+    let mediator = MapMediator::<Ledger>::default();
+    let ledger = |id| Ledger { id, entries: Mutex::new(Vec::new()) }.belong_to(mediator.clone());
+    let sender = mediator.register(1, ledger(1))?;
+    let recipient = mediator.register(2, ledger(2))?;
+    
+    let newer = mediator.consult_with_headers(&sender, &2, Message::new("v2 entry".to_string()).with_version(2));
+    assert_eq!(newer.unwrap_err(), Error::VersionMismatch { id: "2".to_string(), got: 2, expected: 1 });
+    mediator.consult_with_headers(&sender, &2, Message::new("v1 entry".to_string()).with_version(1))?.ok_or_err()?;
+    assert_eq!(*recipient.entries.lock().unwrap(), ["v1 entry"]);
+    Ok(())
+}
+
+/// Everything a terminal can be sent.
+#[derive(Debug, Clone)]
+enum Console {
+    Text(String),
+    Command(&'static str),
+}
+
+/// Shows texts and runs commands, both coming in as one message type.
+#[derive(Colleague)]
+#[colleague(mediator = MapMediator<Terminal>, message = Console)]
+struct Terminal {
+    #[colleague(id)]
+    id: u32,
+    shown: Mutex<Vec<String>>,
+    ran: Mutex<Vec<&'static str>>,
+}
+
+impl Terminal {
+    fn new(id: u32) -> Self {
+        Self { id, shown: Mutex::new(Vec::new()), ran: Mutex::new(Vec::new()) }
+    }
+    
+    fn on_message(&self, msg: Console) -> Result<(), ColleagueError> {
+        match msg {
+            Console::Text(text) => self.shown.lock().unwrap().push(text),
+            Console::Command(command) => self.ran.lock().unwrap().push(command),
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn enum_messages_carry_several_payloads() -> Result<(), Error> {
+    let mediator = MapMediator::<Terminal>::default();
+    let console = mediator.register(0, Terminal::new(0).belong_to(mediator.clone()))?;
+    let shell = mediator.register(1, Terminal::new(1).belong_to(mediator.clone()))?;
+    
+    mediator.consultation(&console, &1, Console::Text("hello".to_string()))?;
+    mediator.consultation(&console, &1, Console::Command("clear"))?;
+    assert_eq!(*shell.shown.lock().unwrap(), ["hello"]);
+    assert_eq!(*shell.ran.lock().unwrap(), ["clear"]);
+    Ok(())
+}
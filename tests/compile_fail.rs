@@ -0,0 +1,6 @@
+#[cfg_attr(feature = "no-std", ignore = "the embedded `Managed` changes how rustc names the types in the diagnostics")]
+#[test]
+fn misuse_fails_to_compile() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
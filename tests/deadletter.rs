@@ -0,0 +1,196 @@
+use mediator_play::{Colleague, DeadletterPolicy, DeadletterReason, DeliveryOutcome, Error, Mediator, MessageId, Registered, User, UserId, UserMediator};
+
+fn join(mediator: &UserMediator, id: &str) -> Result<Registered<User>, Error> {
+    let id = UserId::new(id);
+    mediator.register(id.clone(), User::new(id).belong_to(mediator.clone()))
+}
+
+#[test]
+fn deregistered_recipients_are_deadlettered() -> Result<(), Error> {
+    let mediator = UserMediator::with_deadletter();
+    let sender = join(&mediator, "user-1")?;
+    join(&mediator, "user-2")?;
+    
+    assert!(mediator.deregister(&UserId::new("user-2"))?.is_some());
+    let outcome = sender.send_msg(&UserId::new("user-2"), "are you still there?")?;
+    assert!(matches!(outcome, DeliveryOutcome::Deadlettered(_)));
+    Ok(())
+}
+
+#[test]
+fn queues_messages_to_unregistered_ids() -> Result<(), Error> {
+    let mediator = UserMediator::with_deadletter();
+    let sender = join(&mediator, "user-1")?;
+    
+    let outcome = sender.send_msg(&UserId::new("user-3"), "hi")?;
+    let letters = mediator.deadletters()?;
+    assert_eq!(letters.len(), 1);
+    assert_eq!(letters[0].id, outcome.message_id());
+    assert_eq!(letters[0].from, UserId::new("user-1"));
+    assert_eq!(letters[0].to, UserId::new("user-3"));
+    assert_eq!(letters[0].msg, "hi");
+    assert_eq!(letters[0].reason, DeadletterReason::NotRegistered);
+    Ok(())
+}
+
+#[test]
+fn quiet_sends_never_deadletter() -> Result<(), Error> {
+    let mediator = UserMediator::with_deadletter();
+    let sender = join(&mediator, "user-1")?;
+    let recipient = join(&mediator, "user-2")?;
+    
+    assert!(mediator.try_consultation(&sender, &UserId::new("user-2"), "psst".to_string())?);
+    assert!(!mediator.try_consultation(&sender, &UserId::new("user-3"), "psst".to_string())?);
+    assert_eq!(recipient.received(), 1);
+    assert!(mediator.deadletters()?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn weak_registrations_go_once_dropped() -> Result<(), Error> {
+    let mediator = UserMediator::with_deadletter();
+    let sender = join(&mediator, "user-1")?;
+    let id = UserId::new("user-2");
+    let weak = mediator.register_weak(id.clone(), User::new(id.clone()).belong_to(mediator.clone()))?;
+    
+    assert!(matches!(sender.send_msg(&id, "still there?")?, DeliveryOutcome::Delivered(_)));
+    drop(weak);
+    assert!(matches!(sender.send_msg(&id, "still there?")?, DeliveryOutcome::Deadlettered(_)));
+    assert!(!mediator.is_registered(&id)?);
+    Ok(())
+}
+
+#[test]
+fn outcomes_tell_what_became_of_a_message() -> Result<(), Error> {
+    let mediator = UserMediator::with_deadletter();
+    let sender = join(&mediator, "user-1")?;
+    join(&mediator, "user-2")?;
+    let (user2, user3, user4) = (UserId::new("user-2"), UserId::new("user-3"), UserId::new("user-4"));
+    let (_, hung_up) = mediator.register_channel(user4.clone(), User::new(user4.clone()).belong_to(mediator.clone()))?;
+    drop(hung_up);
+    
+    assert!(matches!(sender.send_msg(&user2, "hi")?, DeliveryOutcome::Delivered(_)));
+    assert!(matches!(sender.send_msg(&user3, "hi")?, DeliveryOutcome::Deadlettered(_)));
+    assert!(matches!(sender.send_msg(&user4, "hi")?, DeliveryOutcome::Rejected { .. }));
+    let once = MessageId::new(1 << 32);
+    assert!(matches!(mediator.consult_idempotent(&sender, &user2, "once".to_string(), once)?, DeliveryOutcome::Delivered(id) if id == once));
+    assert!(matches!(mediator.consult_idempotent(&sender, &user2, "once".to_string(), once)?, DeliveryOutcome::Duplicate(id) if id == once));
+    mediator.set_filter(user2.clone(), |_| false)?;
+    assert!(matches!(sender.send_msg(&user2, "hi")?, DeliveryOutcome::Filtered(_)));
+    Ok(())
+}
+
+#[test]
+fn retries_deliver_to_late_registrations() -> Result<(), Error> {
+    let mediator = UserMediator::with_deadletter();
+    let sender = join(&mediator, "user-1")?;
+    sender.send_msg(&UserId::new("user-2"), "see you when you get here")?;
+    sender.send_msg(&UserId::new("user-3"), "and you")?;
+    
+    let late = join(&mediator, "user-2")?;
+    let report = mediator.retry_deadletters()?;
+    assert_eq!((report.delivered, report.dead), (1, 1));
+    assert_eq!(late.received(), 1);
+    let letters = mediator.deadletters()?;
+    assert_eq!(letters.len(), 1);
+    assert_eq!(letters[0].to, UserId::new("user-3"));
+    Ok(())
+}
+
+#[test]
+fn callbacks_hear_how_the_send_went() -> Result<(), Error> {
+    let mediator = UserMediator::with_deadletter();
+    let sender = join(&mediator, "user-1")?;
+    join(&mediator, "user-2")?;
+    
+    let mut outcomes = Vec::new();
+    sender.send_msg_with(&UserId::new("user-2"), "hi", |outcome| outcomes.push(outcome))?;
+    sender.send_msg_with(&UserId::new("user-3"), "hi", |outcome| outcomes.push(outcome))?;
+    assert!(matches!(outcomes[..], [DeliveryOutcome::Delivered(_), DeliveryOutcome::Deadlettered(_)]), "{:?}", outcomes);
+    Ok(())
+}
+
+#[test]
+fn paused_colleagues_get_their_messages_on_resuming() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    let sender = join(&mediator, "user-1")?;
+    let away = join(&mediator, "user-2")?;
+    let id = UserId::new("user-2");
+    
+    mediator.pause(&id)?;
+    assert!(matches!(sender.send_msg(&id, "while you were out")?, DeliveryOutcome::Deadlettered(_)));
+    assert_eq!(away.received(), 0);
+    assert!(mediator.is_registered(&id)?);
+    
+    assert_eq!(mediator.resume(&id, true)?, 1);
+    assert_eq!(away.received(), 1);
+    assert!(mediator.deadletters()?.is_empty());
+    assert!(matches!(sender.send_msg(&id, "welcome back")?, DeliveryOutcome::Delivered(_)));
+    assert_eq!(away.received(), 2);
+    Ok(())
+}
+
+#[test]
+fn full_queues_drop_their_oldest_letter() -> Result<(), Error> {
+    let mediator = UserMediator::builder()
+        .deadletter(true)
+        .deadletter_capacity(2, DeadletterPolicy::DropOldest)
+        .build();
+    let sender = join(&mediator, "user-1")?;
+    
+    for msg in ["first", "second", "third"] {
+        assert!(matches!(sender.send_msg(&UserId::new("user-9"), msg)?, DeliveryOutcome::Deadlettered(_)));
+    }
+    let kept: Vec<_> = mediator.deadletters()?.into_iter().map(|letter| letter.msg).collect();
+    assert_eq!(kept, ["second", "third"]);
+    Ok(())
+}
+
+#[test]
+fn letters_say_why_they_were_not_delivered() -> Result<(), Error> {
+    let mediator = UserMediator::with_deadletter();
+    let sender = join(&mediator, "user-1")?;
+    let picky = join(&mediator, "user-2")?;
+    mediator.set_filter(UserId::new("user-2"), |msg: &String| !msg.contains("spam"))?;
+    
+    sender.send_msg(&UserId::new("user-3"), "anyone?")?;
+    sender.send_msg(&UserId::new("user-2"), "spam")?;
+    sender.send_msg(&UserId::new("user-2"), "hi")?;
+    let reasons: Vec<_> = mediator.deadletters()?.into_iter().map(|letter| (letter.to, letter.reason)).collect();
+    assert_eq!(reasons, [
+        (UserId::new("user-3"), DeadletterReason::NotRegistered),
+        (UserId::new("user-2"), DeadletterReason::Filtered),
+    ]);
+    assert_eq!(picky.received(), 1);
+    Ok(())
+}
+
+// parking_lot's locks aren't poisoned by a panic.
+#[cfg(not(feature = "parking_lot"))]
+mod poisoned {
+    use std::panic::{self, AssertUnwindSafe};
+    
+    use mediator_play::{Error, Observer, Operation, User, UserId, UserMediator};
+    
+    use super::join;
+    
+    /// Panics on hearing of a deadletter, while the deadletter queue is locked.
+    struct Clumsy;
+    
+    impl Observer<User> for Clumsy {
+        fn on_deadletter(&self, _from: &UserId, _to: &UserId, _msg: &String) {
+            panic!("dropped the letter");
+        }
+    }
+    
+    #[test]
+    fn queues_fail_the_consultation() -> Result<(), Error> {
+        let mediator = UserMediator::with_deadletter().observed_by(Clumsy);
+        let sender = join(&mediator, "user-1")?;
+        let nobody = UserId::new("user-2");
+        assert!(panic::catch_unwind(AssertUnwindSafe(|| sender.send_msg(&nobody, "hello?"))).is_err());
+        
+        assert_eq!(sender.send_msg(&nobody, "hello?").unwrap_err(), Error::LockPoison { during: Operation::Consultation });
+        Ok(())
+    }
+}
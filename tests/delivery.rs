@@ -0,0 +1,122 @@
+use std::thread;
+
+use mediator_play::{Colleague, Error, Mediator, Registered, User, UserId, UserMediator};
+
+fn join(mediator: &UserMediator, id: &str) -> Result<Registered<User>, Error> {
+    let id = UserId::new(id);
+    mediator.register(id.clone(), User::new(id).belong_to(mediator.clone()))
+}
+
+#[test]
+fn sends_from_another_thread() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    let sender = join(&mediator, "user-1")?;
+    let recipient = join(&mediator, "user-2")?;
+    
+    thread::spawn(move || sender.send_msg(&UserId::new("user-2"), "from another thread").map(drop))
+        .join()
+        .expect("sender thread panicked")?;
+    assert_eq!(recipient.received(), 1);
+    Ok(())
+}
+
+#[test]
+fn refuses_to_send_to_oneself() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    let user = join(&mediator, "user-1")?;
+    
+    let sent = user.send_msg(&UserId::new("user-1"), "talking to myself");
+    assert_eq!(sent.unwrap_err(), Error::SelfSend { id: format!("{:?}", UserId::new("user-1")) });
+    assert_eq!(user.received(), 0);
+    Ok(())
+}
+
+#[test]
+fn sends_through_a_detached_sender() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    let sender = join(&mediator, "user-1")?.sender();
+    let recipient = join(&mediator, "user-2")?;
+    
+    assert_eq!(sender.from(), &UserId::new("user-1"));
+    thread::spawn(move || sender.send_msg(&UserId::new("user-2"), "sent through a detached sender").map(drop))
+        .join()
+        .expect("sender thread panicked")?;
+    assert_eq!(recipient.received(), 1);
+    Ok(())
+}
+
+#[test]
+fn message_ids_increase() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    let sender = join(&mediator, "user-1")?;
+    join(&mediator, "user-2")?;
+    
+    let first = sender.send_msg(&UserId::new("user-2"), "hi")?.message_id();
+    let second = sender.send_msg(&UserId::new("user-2"), "hi again")?.message_id();
+    assert!(second > first, "{:?} should come after {:?}", second, first);
+    Ok(())
+}
+
+#[test]
+fn reports_each_recipient_of_a_batch() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    let sender = join(&mediator, "user-1")?;
+    let present = join(&mediator, "user-2")?;
+    
+    let to = [UserId::new("user-2"), UserId::new("user-3")];
+    let batch = mediator.consult_many(&sender, &to, "roll call".to_string());
+    assert!(!batch.is_complete());
+    assert_eq!(batch.delivered, [UserId::new("user-2")]);
+    assert_eq!(batch.failed, [(UserId::new("user-3"), Error::NotRegistered { id: format!("{:?}", UserId::new("user-3")) })]);
+    assert_eq!(present.received(), 1);
+    Ok(())
+}
+
+#[test]
+fn filters_pass_over_unwanted_broadcasts() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    let sender = join(&mediator, "user-1")?;
+    let picky = join(&mediator, "user-2")?;
+    let other = join(&mediator, "user-3")?;
+    mediator.set_filter(UserId::new("user-2"), |msg: &String| !msg.contains("spam"))?;
+    
+    assert_eq!(mediator.broadcast(&sender, "spam, spam and eggs".to_string())?, 1);
+    assert_eq!((picky.received(), other.received()), (0, 1));
+    assert_eq!(mediator.broadcast(&sender, "eggs".to_string())?, 2);
+    assert_eq!((picky.received(), other.received()), (1, 2));
+    Ok(())
+}
+
+#[test]
+fn sends_anything_that_turns_into_a_message() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    let sender = join(&mediator, "user-1")?;
+    let recipient = join(&mediator, "user-2")?;
+    let to = UserId::new("user-2");
+    
+    sender.send_msg(&to, "hi")?;
+    sender.send_msg(&to, String::from("hi"))?;
+    sender.send_msg(&to, '!')?;
+    sender.sender().send_msg(&to, "hi")?;
+    assert_eq!(recipient.received(), 4);
+    Ok(())
+}
+
+#[test]
+fn batches_sort_recipients_by_what_went_wrong() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    let sender = join(&mediator, "user-1")?;
+    join(&mediator, "user-2")?;
+    let busy = UserId::new("user-4");
+    mediator.register(busy.clone(), User::new(busy.clone()).belong_to(mediator.clone()).with_mailbox(1))?;
+    sender.send_msg(&busy, "already waiting")?;
+    
+    let to = [UserId::new("user-2"), UserId::new("user-3"), busy.clone()];
+    let batch = mediator.consult_many(&sender, &to, "roll call".to_string());
+    assert_eq!(batch.delivered, [UserId::new("user-2")]);
+    assert_eq!(batch.failed, [
+        (UserId::new("user-3"), Error::NotRegistered { id: format!("{:?}", UserId::new("user-3")) }),
+        (busy.clone(), Error::MailboxFull { id: format!("{:?}", busy) }),
+    ]);
+    Ok(())
+}
@@ -0,0 +1,105 @@
+//! The embedded core used from a crate that only has `core` and `alloc`; `std` is linked for
+//! the test harness alone.
+
+#![no_std]
+
+extern crate alloc;
+extern crate std;
+
+use alloc::string::String;
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+use mediator_play::embedded::{BTreeMediator, Colleague, Error, FixedMediator, Managed, Mediator};
+
+struct Led {
+    pin: u8,
+    toggles: AtomicU32,
+    on: AtomicBool,
+}
+
+impl Led {
+    fn new(pin: u8) -> Self {
+        Self { pin, toggles: AtomicU32::new(0), on: AtomicBool::new(false) }
+    }
+}
+
+impl Colleague for Led {
+    type Identifier = u8;
+    type Mediator = BTreeMediator<Led>;
+    type Message = bool;
+    
+    fn id(&self) -> &u8 {
+        &self.pin
+    }
+    
+    fn belong_to(self, mediator: Self::Mediator) -> Managed<Self> {
+        Managed::new(self, mediator)
+    }
+    
+    fn read_msg(&self, on: bool) -> Result<(), String> {
+        self.toggles.fetch_add(1, Ordering::Relaxed);
+        self.on.store(on, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+#[test]
+fn delivers_without_std() -> Result<(), Error> {
+    let mut bus = BTreeMediator::default();
+    let status = bus.register(13, Led::new(13).belong_to(bus.clone()))?;
+    let power = bus.register(2, Led::new(2).belong_to(bus.clone()))?;
+    
+    status.as_mediator().consultation(&status, &2, true)?;
+    assert_eq!(power.toggles.load(Ordering::Relaxed), 1);
+    assert!(power.on.load(Ordering::Relaxed));
+    assert!(matches!(bus.consultation(&status, &7, true), Err(Error::NotRegistered { .. })));
+    Ok(())
+}
+
+/// Wired to a board with room for two buttons.
+struct Button {
+    pin: u8,
+    presses: AtomicU32,
+}
+
+impl Colleague for Button {
+    type Identifier = u8;
+    type Mediator = &'static FixedMediator<Button, 2>;
+    type Message = ();
+    
+    fn id(&self) -> &u8 {
+        &self.pin
+    }
+    
+    fn belong_to(self, mediator: Self::Mediator) -> Managed<Self> {
+        Managed::new(self, mediator)
+    }
+    
+    fn read_msg(&self, _: ()) -> Result<(), String> {
+        self.presses.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+static BOARD: FixedMediator<Button, 2> = FixedMediator::new();
+
+fn button(pin: u8) -> Managed<Button> {
+    Button { pin, presses: AtomicU32::new(0) }.belong_to(&BOARD)
+}
+
+#[test]
+fn fixed_registries_refuse_one_too_many() -> Result<(), Error> {
+    let reset = BOARD.register(4, button(4))?;
+    let power = BOARD.register(5, button(5))?;
+    
+    assert_eq!(BOARD.register(6, button(6)).err(), Some(Error::RegistryFull { capacity: 2 }));
+    assert!(BOARD.deregister(&4).is_some_and(|gone| gone.pin == 4));
+    let spare = BOARD.register(6, button(6))?;
+    assert!(BOARD.registered_ids().eq([6, 5]));
+    // The freed slot went to the spare, which the old handle doesn't lead to.
+    assert_eq!(BOARD.with(&reset, |_| ()), None);
+    
+    BOARD.with(&spare, |spare| BOARD.consultation(spare, &5, ())).expect("the spare is registered")?;
+    assert_eq!(BOARD.with(&power, |power| power.presses.load(Ordering::Relaxed)), Some(1));
+    Ok(())
+}
@@ -0,0 +1,98 @@
+use std::io;
+use std::time::Duration;
+
+use mediator_play::{Error, Operation};
+
+/// One of every variant, with the message it displays.
+fn variants() -> Vec<(Error, &'static str)> {
+    let id = || "user-1".to_string();
+    vec![
+        (Error::LockPoison { during: Operation::Consultation }, "cannot lock during consultation"),
+        (Error::NotRegistered { id: id() }, "user-1 is not registered"),
+        (Error::AlreadyRegistered { id: id() }, "user-1 is already registered"),
+        (Error::SelfSend { id: id() }, "user-1 tried to send a message to itself"),
+        (Error::DeliveryRejected { id: id(), reason: "busy".to_string() }, "user-1 rejected the message: busy"),
+        (Error::DeliveryTimeout { id: id(), timeout: Duration::from_secs(1) }, "user-1 did not handle the message within 1s"),
+        (Error::RegistrationTimeout { id: id(), timeout: Duration::from_secs(1) }, "user-1 did not register within 1s"),
+        (Error::HandlerPanicked { id: id() }, "user-1 panicked while reading the message and was deregistered"),
+        (Error::PublishFailed { id: id(), reason: "offline".to_string() }, "publishing to user-1 failed: offline"),
+        (Error::CircuitOpen { id: id() }, "circuit of user-1 is open after repeated rejections"),
+        (Error::MailboxFull { id: id() }, "mailbox of user-1 is full"),
+        (Error::DeadletterFull { id: id() }, "no room left in the deadletter queue for a message to user-1"),
+        (Error::VersionMismatch { id: id(), got: 2, expected: 1 }, "user-1 expects messages of schema version 1, got version 2"),
+        (Error::Rejected { reason: "spam".to_string() }, "message rejected by middleware: spam"),
+        (Error::MessageTypeMismatch { id: id(), expected: "String", got: "u32" }, "user-1 expects messages of type String, got u32"),
+        (Error::QuotaExceeded { id: id() }, "user-1 has used up its send quota"),
+        (Error::GroupNotFound { group: "lobby".to_string() }, "group lobby does not exist"),
+        (Error::NoWorkers { role: "billing".to_string() }, "no worker is registered for role billing"),
+        (Error::ForwardDepthExceeded { depth: 8 }, "gave up forwarding to parent mediators after 8 hops"),
+        (Error::PrepareDeclined { id: id() }, "user-1 declined to take the message"),
+        (Error::NoRoute, "no routing rule matched the message"),
+        (Error::Cancelled, "the delivery was cancelled"),
+        (Error::ShuttingDown, "mediator is shutting down"),
+    ]
+}
+
+#[test]
+fn every_variant_names_what_went_wrong() {
+    for (e, message) in variants() {
+        let named = match &e {
+            Error::LockPoison { during } => *during == Operation::Consultation,
+            Error::NotRegistered { id }
+            | Error::AlreadyRegistered { id }
+            | Error::SelfSend { id }
+            | Error::DeliveryRejected { id, .. }
+            | Error::DeliveryTimeout { id, .. }
+            | Error::RegistrationTimeout { id, .. }
+            | Error::HandlerPanicked { id }
+            | Error::PublishFailed { id, .. }
+            | Error::CircuitOpen { id }
+            | Error::MailboxFull { id }
+            | Error::DeadletterFull { id }
+            | Error::VersionMismatch { id, .. }
+            | Error::MessageTypeMismatch { id, .. }
+            | Error::QuotaExceeded { id }
+            | Error::PrepareDeclined { id } => id == "user-1",
+            Error::Rejected { reason } => reason == "spam",
+            Error::GroupNotFound { group } => group == "lobby",
+            Error::NoWorkers { role } => role == "billing",
+            Error::ForwardDepthExceeded { depth } => *depth == 8,
+            Error::NoRoute | Error::Cancelled | Error::ShuttingDown => true,
+            _ => false,
+        };
+        assert!(named, "{:?} lost its context", e);
+        assert_eq!(e.to_string(), message);
+    }
+}
+
+#[test]
+fn errors_compare_by_value() {
+    let missing = Error::NotRegistered { id: "user-1".to_string() };
+    assert_eq!(missing.clone(), Error::NotRegistered { id: "user-1".to_string() });
+    assert_ne!(missing, Error::NotRegistered { id: "user-2".to_string() });
+    assert_ne!(missing, Error::AlreadyRegistered { id: "user-1".to_string() });
+    for (e, _) in variants() {
+        assert_eq!(e.clone(), e);
+    }
+}
+
+#[test]
+fn io_errors_keep_the_kind_and_the_error() {
+    for (e, _) in variants() {
+        let kind = match &e {
+            Error::NotRegistered { .. } | Error::GroupNotFound { .. } | Error::NoWorkers { .. } | Error::NoRoute => io::ErrorKind::NotFound,
+            Error::AlreadyRegistered { .. } => io::ErrorKind::AlreadyExists,
+            Error::SelfSend { .. } => io::ErrorKind::InvalidInput,
+            Error::MessageTypeMismatch { .. } | Error::VersionMismatch { .. } => io::ErrorKind::InvalidData,
+            Error::DeliveryTimeout { .. } | Error::RegistrationTimeout { .. } => io::ErrorKind::TimedOut,
+            Error::MailboxFull { .. } | Error::DeadletterFull { .. } | Error::QuotaExceeded { .. } => io::ErrorKind::WouldBlock,
+            Error::CircuitOpen { .. } => io::ErrorKind::ConnectionRefused,
+            Error::Rejected { .. } => io::ErrorKind::PermissionDenied,
+            Error::Cancelled => io::ErrorKind::Interrupted,
+            _ => io::ErrorKind::Other,
+        };
+        let converted = io::Error::from(e.clone());
+        assert_eq!(converted.kind(), kind, "{:?} was mapped to the wrong kind", e);
+        assert_eq!(converted.get_ref().and_then(|inner| inner.downcast_ref::<Error>()), Some(&e));
+    }
+}
@@ -0,0 +1,79 @@
+use std::sync::{Arc, Mutex};
+
+use mediator_play::{Colleague, DeadletterReason, DeliveryOutcome, Error, EventKind, Mediator, Registered, Tapped, User, UserId, UserMediator};
+
+fn join(mediator: &UserMediator, id: &str) -> Result<Registered<User>, Error> {
+    let id = UserId::new(id);
+    mediator.register(id.clone(), User::new(id).belong_to(mediator.clone()))
+}
+
+#[test]
+fn logs_activity_in_order() -> Result<(), Error> {
+    let mediator = UserMediator::builder()
+        .event_log(true)
+        .deadletter(true)
+        .build();
+    let sender = join(&mediator, "user-1")?;
+    join(&mediator, "user-2")?;
+    sender.send_msg(&UserId::new("user-2"), "hi")?;
+    sender.send_msg(&UserId::new("user-3"), "hi")?;
+    
+    let kinds: Vec<_> = mediator.event_log()?.into_iter().map(|event| event.kind).collect();
+    let (user1, user2, user3) = (UserId::new("user-1"), UserId::new("user-2"), UserId::new("user-3"));
+    assert!(matches!(&kinds[..], [
+        EventKind::Registered { id: first, weak: false },
+        EventKind::Registered { id: second, weak: false },
+        EventKind::Delivered { from: delivered_from, to: delivered_to, msg: delivered },
+        EventKind::Consulted { outcome: Ok(DeliveryOutcome::Delivered(_)), .. },
+        EventKind::Deadletter { to: dead_to, reason: DeadletterReason::NotRegistered, .. },
+        EventKind::Consulted { outcome: Ok(DeliveryOutcome::Deadlettered(_)), .. },
+    ] if (first, second, delivered_from, delivered_to, delivered.as_str(), dead_to) == (&user1, &user2, &user1, &user2, "hi", &user3)),
+        "unexpected events {:?}", kinds);
+    Ok(())
+}
+
+#[test]
+fn taps_see_delivered_and_deadlettered_traffic() -> Result<(), Error> {
+    let mediator = UserMediator::with_deadletter();
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let log = Arc::clone(&seen);
+    mediator.tap(move |_: &UserId, to: &UserId, msg: &String, tapped| {
+        log.lock().unwrap().push((to.clone(), msg.clone(), tapped));
+    })?;
+    let sender = join(&mediator, "user-1")?;
+    join(&mediator, "user-2")?;
+    
+    sender.send_msg(&UserId::new("user-2"), "hi")?;
+    sender.send_msg(&UserId::new("user-3"), "anyone?")?;
+    assert_eq!(*seen.lock().unwrap(), [
+        (UserId::new("user-2"), "hi".to_string(), Tapped::Routed),
+        (UserId::new("user-3"), "anyone?".to_string(), Tapped::Deadlettered),
+    ]);
+    Ok(())
+}
+
+#[test]
+fn draws_deliveries_as_counted_edges() -> Result<(), Error> {
+    let mediator = UserMediator::builder()
+        .event_log(true)
+        .deadletter(true)
+        .build();
+    let sender = join(&mediator, "user-1")?;
+    join(&mediator, "user-2")?;
+    sender.send_msg(&UserId::new("user-2"), "hi")?;
+    sender.send_msg(&UserId::new("user-2"), "again")?;
+    sender.send_msg(&UserId::new("user-3"), "anyone?")?;
+    
+    let dot = mediator.to_dot()?;
+    let (user1, user2, user3) = (quoted("user-1"), quoted("user-2"), quoted("user-3"));
+    assert!(dot.starts_with("digraph mediator {\n") && dot.ends_with("}\n"), "{}", dot);
+    assert!(dot.contains(&format!("    {} -> {} [label=\"2\"];\n", user1, user2)), "{}", dot);
+    assert!(dot.contains(&format!("    {} -> {} [label=\"1\", style=dashed];\n", user1, user3)), "{}", dot);
+    assert_eq!(dot.matches(" -> ").count(), 2, "{}", dot);
+    Ok(())
+}
+
+/// How the graph names the node of `id`.
+fn quoted(id: &str) -> String {
+    format!("\"{}\"", format!("{:?}", UserId::new(id)).replace('"', "\\\""))
+}
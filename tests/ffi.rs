@@ -0,0 +1,35 @@
+use std::ffi::{c_char, c_void, CStr};
+use std::ptr;
+use std::sync::Mutex;
+
+use mediator_play::ffi::{mediator_free, mediator_new, mediator_register, mediator_send, MEDIATOR_ALREADY_REGISTERED, MEDIATOR_FAILED, MEDIATOR_INVALID_ARGUMENT, MEDIATOR_NOT_REGISTERED, MEDIATOR_OK};
+
+type Deliveries = Mutex<Vec<[String; 3]>>;
+
+extern "C" fn record(user_data: *mut c_void, from: *const c_char, to: *const c_char, msg: *const c_char) {
+    let deliveries = unsafe { &*user_data.cast::<Deliveries>() };
+    let delivery = [from, to, msg].map(|s| unsafe { CStr::from_ptr(s) }.to_string_lossy().into_owned());
+    deliveries.lock().unwrap().push(delivery);
+}
+
+#[test]
+fn delivers_through_the_c_api() {
+    let deliveries = Deliveries::default();
+    let user_data = ptr::from_ref(&deliveries).cast_mut().cast();
+    
+    unsafe {
+        let mediator = mediator_new(record, user_data);
+        assert_eq!(mediator_register(mediator, c"user-1".as_ptr()), MEDIATOR_OK);
+        assert_eq!(mediator_register(mediator, c"user-2".as_ptr()), MEDIATOR_OK);
+        assert_eq!(mediator_register(mediator, c"user-2".as_ptr()), MEDIATOR_ALREADY_REGISTERED);
+        assert_eq!(mediator_register(mediator, ptr::null()), MEDIATOR_INVALID_ARGUMENT);
+        
+        assert_eq!(mediator_send(mediator, c"user-1".as_ptr(), c"user-2".as_ptr(), c"hello over C".as_ptr()), MEDIATOR_OK);
+        assert_eq!(mediator_send(mediator, c"user-1".as_ptr(), c"user-3".as_ptr(), c"anyone?".as_ptr()), MEDIATOR_NOT_REGISTERED);
+        assert_eq!(mediator_send(mediator, c"user-1".as_ptr(), c"user-1".as_ptr(), c"me?".as_ptr()), MEDIATOR_FAILED);
+        mediator_free(mediator);
+    }
+    
+    let delivered = deliveries.into_inner().unwrap();
+    assert_eq!(delivered, [["user-1", "user-2", "hello over C"].map(String::from)]);
+}
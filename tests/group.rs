@@ -0,0 +1,28 @@
+use mediator_play::{Colleague, Error, GroupId, Mediator, Registered, User, UserId, UserMediator};
+
+fn join(mediator: &UserMediator, id: &str) -> Result<Registered<User>, Error> {
+    let id = UserId::new(id);
+    mediator.register(id.clone(), User::new(id).belong_to(mediator.clone()))
+}
+
+#[test]
+fn multicasts_stay_within_their_group() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    let sender = join(&mediator, "user-1")?;
+    let lobby_member = join(&mediator, "user-2")?;
+    let admin = join(&mediator, "user-3")?;
+    let (lobby, admins) = (GroupId::new("lobby"), GroupId::new("admins"));
+    for id in ["user-1", "user-2"] {
+        mediator.join_group(&UserId::new(id), lobby.clone())?;
+    }
+    mediator.join_group(&UserId::new("user-3"), admins.clone())?;
+    
+    assert_eq!(mediator.multicast(&sender, &lobby, "welcome to the lobby".to_string())?, 1);
+    assert_eq!((sender.received(), lobby_member.received(), admin.received()), (0, 1, 0));
+    assert_eq!(mediator.multicast(&sender, &admins, "admins only".to_string())?, 1);
+    assert_eq!((lobby_member.received(), admin.received()), (1, 1));
+    
+    let missing = mediator.multicast(&sender, &GroupId::new("nobody"), "hello?".to_string());
+    assert!(matches!(missing, Err(Error::GroupNotFound { .. })));
+    Ok(())
+}
@@ -0,0 +1,58 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{BuildHasherDefault, Hasher};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use mediator_play::{Colleague, ColleagueError, Error, HashedMediator, Mediator};
+
+static HASHED: AtomicUsize = AtomicUsize::new(0);
+
+/// The std hasher, counting how often it's used.
+#[derive(Default)]
+struct Counting(DefaultHasher);
+
+impl Hasher for Counting {
+    fn finish(&self) -> u64 {
+        self.0.finish()
+    }
+    
+    fn write(&mut self, bytes: &[u8]) {
+        HASHED.fetch_add(1, Ordering::Relaxed);
+        self.0.write(bytes);
+    }
+}
+
+type CountingMediator<C> = HashedMediator<C, BuildHasherDefault<Counting>>;
+
+/// A port keeping what reached it.
+#[derive(Colleague)]
+#[colleague(mediator = CountingMediator<Port>, message = &'static str)]
+struct Port {
+    #[colleague(id)]
+    id: u16,
+    received: Mutex<Vec<&'static str>>,
+}
+
+impl Port {
+    fn on_message(&self, msg: &'static str) -> Result<(), ColleagueError> {
+        self.received.lock().unwrap().push(msg);
+        Ok(())
+    }
+}
+
+#[test]
+fn registries_hash_with_the_hasher_they_are_given() -> Result<(), Error> {
+    let mediator = CountingMediator::<Port>::default();
+    let port = |id| Port { id, received: Mutex::new(Vec::new()) }.belong_to(mediator.clone());
+    let http = mediator.register(80, port(80))?;
+    let https = mediator.register(443, port(443))?;
+    
+    let before = HASHED.load(Ordering::Relaxed);
+    assert!(before > 0);
+    mediator.consultation(&http, &443, "upgrade")?;
+    assert!(mediator.get(&443)?.is_some());
+    assert!(!mediator.is_registered(&8080)?);
+    assert!(HASHED.load(Ordering::Relaxed) > before);
+    assert_eq!(*https.received.lock().unwrap(), ["upgrade"]);
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+
+use mediator_play::testing::TestRecorder;
+use mediator_play::{Colleague, Error, GenericMediator, Mediator, Priority, Readiness, Registered, User, UserId, UserMediator};
+
+fn join(mediator: &UserMediator, id: &str) -> Result<Registered<User>, Error> {
+    let id = UserId::new(id);
+    mediator.register(id.clone(), User::new(id).belong_to(mediator.clone()))
+}
+
+fn join_with_mailbox(mediator: &UserMediator, id: &str, capacity: usize) -> Result<Registered<User>, Error> {
+    let id = UserId::new(id);
+    mediator.register(id.clone(), User::new(id).belong_to(mediator.clone()).with_mailbox(capacity))
+}
+
+#[test]
+fn full_mailboxes_push_back() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    let sender = join(&mediator, "user-1")?;
+    let recipient = join_with_mailbox(&mediator, "user-2", 1)?;
+    
+    sender.send_msg(&UserId::new("user-2"), "first")?;
+    let overflow = sender.send_msg(&UserId::new("user-2"), "second");
+    assert_eq!(overflow.unwrap_err(), Error::MailboxFull { id: format!("{:?}", UserId::new("user-2")) });
+    
+    assert_eq!(recipient.drain(usize::MAX), 1);
+    assert_eq!(recipient.received(), 1);
+    sender.send_msg(&UserId::new("user-2"), "third")?;
+    Ok(())
+}
+
+#[test]
+fn drains_higher_priorities_first() -> Result<(), Error> {
+    let mediator = GenericMediator::<TestRecorder<&str, &str>>::default();
+    let sender = mediator.register("sender", TestRecorder::new("sender").belong_to(mediator.clone()))?;
+    let recipient = mediator.register("recipient", TestRecorder::new("recipient").belong_to(mediator.clone()).with_mailbox(4))?;
+    
+    mediator.consult_with_priority(&sender, &"recipient", "low", Priority::Low)?;
+    mediator.consultation(&sender, &"recipient", "normal")?;
+    mediator.consult_with_priority(&sender, &"recipient", "high", Priority::High)?;
+    assert_eq!(recipient.drain(1), 1);
+    assert_eq!(recipient.recorded(), [r#""high""#]);
+    assert_eq!(recipient.drain(usize::MAX), 2);
+    assert_eq!(recipient.recorded(), [r#""high""#, r#""normal""#, r#""low""#]);
+    Ok(())
+}
+
+#[test]
+fn acks_once_the_message_is_read() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    let sender = join(&mediator, "user-1")?;
+    join(&mediator, "user-2")?;
+    let queued = join_with_mailbox(&mediator, "user-3", 4)?;
+    
+    let ack = mediator.consult_with_ack(&sender, &UserId::new("user-2"), "hi".to_string())?;
+    assert!(ack.wait().is_ok());
+    
+    let ack = mediator.consult_with_ack(&sender, &UserId::new("user-3"), "hi".to_string())?;
+    assert!(ack.try_wait().is_none());
+    queued.drain(usize::MAX);
+    assert!(matches!(ack.try_wait(), Some(Ok(()))));
+    Ok(())
+}
+
+#[test]
+fn readiness_follows_the_mailbox() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    let sender = join(&mediator, "user-1")?;
+    let recipient = join_with_mailbox(&mediator, "user-2", 2)?;
+    let to = UserId::new("user-2");
+    
+    assert_eq!(mediator.poll_ready(&to)?, Readiness::Ready);
+    sender.send_msg(&to, "one")?;
+    sender.send_msg(&to, "two")?;
+    assert_eq!(mediator.poll_ready(&to)?, Readiness::Full);
+    recipient.drain(1);
+    assert_eq!(mediator.poll_ready(&to)?, Readiness::Ready);
+    assert_eq!(mediator.poll_ready(&UserId::new("user-3"))?, Readiness::Absent);
+    Ok(())
+}
+
+#[test]
+fn depths_count_what_is_queued() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    let sender = join(&mediator, "user-1")?;
+    let recipient = join_with_mailbox(&mediator, "user-2", 4)?;
+    let id = UserId::new("user-2");
+    
+    sender.send_msg(&id, "first")?;
+    sender.send_msg(&id, "second")?;
+    assert_eq!(mediator.mailbox_depth(&id)?, Some(2));
+    assert_eq!(mediator.mailbox_depth(&UserId::new("user-1"))?, None);
+    assert_eq!(mediator.mailbox_depth(&UserId::new("user-3"))?, None);
+    assert_eq!(mediator.mailbox_depths()?, HashMap::from([(id.clone(), 2)]));
+    
+    recipient.drain(1);
+    assert_eq!(mediator.mailbox_depth(&id)?, Some(1));
+    Ok(())
+}
@@ -0,0 +1,72 @@
+use std::time::Duration;
+
+use mediator_play::{Colleague, ColleagueError, Error, LatencyStats, MapMediator, Mediator, MockClock, Registered, User, UserId, UserMediator};
+
+fn join(mediator: &UserMediator, id: &str) -> Result<Registered<User>, Error> {
+    let id = UserId::new(id);
+    mediator.register(id.clone(), User::new(id).belong_to(mediator.clone()))
+}
+
+#[test]
+fn counts_delivered_and_deadlettered_messages() -> Result<(), Error> {
+    let mediator = UserMediator::with_deadletter();
+    let sender = join(&mediator, "user-1")?;
+    join(&mediator, "user-2")?;
+    
+    sender.send_msg(&UserId::new("user-2"), "hi")?;
+    sender.send_msg(&UserId::new("user-3"), "hi")?;
+    let metrics = mediator.metrics();
+    assert_eq!(metrics.sent, 2);
+    assert_eq!(metrics.delivered, 1);
+    assert_eq!(metrics.deadlettered, 1);
+    Ok(())
+}
+
+#[test]
+fn adds_up_the_bytes_delivered() -> Result<(), Error> {
+    let mediator = UserMediator::builder()
+        .byte_accounting(true)
+        .build();
+    let sender = join(&mediator, "user-1")?;
+    join(&mediator, "user-2")?;
+    
+    sender.send_msg(&UserId::new("user-2"), "hello")?;
+    sender.send_msg(&UserId::new("user-2"), "world!")?;
+    assert_eq!(mediator.bytes_delivered(), ("hello".len() + "world!".len()) as u64);
+    assert_eq!(UserMediator::default().bytes_delivered(), 0);
+    Ok(())
+}
+
+/// Takes `pace` over every message, on the mediator's clock.
+#[derive(Colleague)]
+#[colleague(mediator = MapMediator<Worker>, message = ())]
+struct Worker {
+    #[colleague(id)]
+    name: &'static str,
+    pace: Duration,
+    clock: MockClock,
+}
+
+impl Worker {
+    fn on_message(&self, _: ()) -> Result<(), ColleagueError> {
+        self.clock.advance(self.pace);
+        Ok(())
+    }
+}
+
+#[test]
+fn times_how_long_handlers_take() -> Result<(), Error> {
+    let clock = MockClock::new();
+    let mediator = MapMediator::<Worker>::builder().clock(clock.clone()).build();
+    let worker = |name, millis| Worker { name, pace: Duration::from_millis(millis), clock: clock.clone() }.belong_to(mediator.clone());
+    let boss = mediator.register("boss", worker("boss", 0))?;
+    mediator.register("sluggish", worker("sluggish", 250))?;
+    
+    mediator.consultation(&boss, &"sluggish", ())?;
+    mediator.consultation(&boss, &"sluggish", ())?;
+    let stats = mediator.latency_stats(&"sluggish");
+    assert_eq!(stats, Some(LatencyStats { count: 2, total: Duration::from_millis(500), max: Duration::from_millis(250) }));
+    assert_eq!(stats.and_then(|stats| stats.mean()), Some(Duration::from_millis(250)));
+    assert_eq!(mediator.latency_stats(&"boss"), None);
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use mediator_play::testing::TestRecorder;
+use mediator_play::{Colleague, Decorate, Error, GenericMediator, Mediator, Middleware, MiddlewareResult, Registered};
+
+type Recorder = TestRecorder<&'static str, String>;
+
+fn join(mediator: &GenericMediator<Recorder>, id: &'static str) -> Result<Registered<Recorder>, Error> {
+    mediator.register(id, TestRecorder::new(id).belong_to(mediator.clone()))
+}
+
+struct Redact;
+
+impl Middleware<Recorder> for Redact {
+    fn before_deliver(&self, _: &&'static str, _: &&'static str, msg: String) -> MiddlewareResult<String> {
+        if msg.contains("secret") {
+            MiddlewareResult::Rewrite(msg.replace("secret", "***"))
+        } else {
+            MiddlewareResult::Pass(msg)
+        }
+    }
+}
+
+#[test]
+fn redacts_messages_on_their_way() -> Result<(), Error> {
+    let mediator = GenericMediator::default().with_middleware(Redact);
+    let sender = join(&mediator, "user-1")?;
+    let recipient = join(&mediator, "user-2")?;
+    
+    mediator.consultation(&sender, &"user-2", "my password is secret".to_string())?;
+    mediator.consultation(&sender, &"user-2", "nothing to hide".to_string())?;
+    assert_eq!(recipient.recorded(), [r#""my password is ***""#, r#""nothing to hide""#]);
+    Ok(())
+}
+
+#[test]
+fn transforms_only_touch_their_recipient() -> Result<(), Error> {
+    let mediator = GenericMediator::default();
+    let first = join(&mediator, "user-1")?;
+    let second = join(&mediator, "user-2")?;
+    let sender = join(&mediator, "user-3")?;
+    mediator.set_transform("user-2", |msg: String| msg.to_uppercase())?;
+    
+    mediator.consultation(&sender, &"user-1", "hello".to_string())?;
+    mediator.consultation(&sender, &"user-2", "hello".to_string())?;
+    assert_eq!(first.recorded(), [r#""hello""#]);
+    assert_eq!(second.recorded(), [r#""HELLO""#]);
+    Ok(())
+}
+
+#[test]
+fn stampers_mark_every_send_once() -> Result<(), Error> {
+    let mediator = GenericMediator::default();
+    let sender = join(&mediator, "user-1")?;
+    let second = join(&mediator, "user-2")?;
+    let third = join(&mediator, "user-3")?;
+    mediator.set_stamper(|from: &&'static str, msg: String| format!("[{}] {}", from, msg))?;
+    
+    mediator.consultation(&sender, &"user-2", "hi".to_string())?;
+    mediator.broadcast(&sender, "all".to_string())?;
+    assert_eq!(second.recorded(), [r#""[user-1] hi""#, r#""[user-1] all""#]);
+    assert_eq!(third.recorded(), [r#""[user-1] all""#]);
+    Ok(())
+}
+
+#[test]
+fn decorated_colleagues_read_what_their_decorator_made_of_it() -> Result<(), Error> {
+    type Shouting = Decorate<Recorder, fn(String) -> String>;
+    let shout: fn(String) -> String = |msg| msg.to_uppercase();
+    let mediator = GenericMediator::<Shouting>::default();
+    let sender = mediator.register("user-1", Decorate::new(TestRecorder::new("user-1"), shout).belong_to(mediator.clone()))?;
+    let recipient = mediator.register("user-2", Decorate::new(TestRecorder::new("user-2"), shout).belong_to(mediator.clone()))?;
+    
+    mediator.consultation(&sender, &"user-2", "keep it down".to_string())?;
+    assert_eq!(recipient.inner().recorded(), [r#""KEEP IT DOWN""#]);
+    assert!(sender.inner().recorded().is_empty());
+    Ok(())
+}
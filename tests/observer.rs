@@ -0,0 +1,59 @@
+use std::sync::{Arc, Mutex};
+
+use mediator_play::{Colleague, Error, Mediator, Observer, Registered, User, UserId, UserMediator};
+
+fn join(mediator: &UserMediator, id: &str) -> Result<Registered<User>, Error> {
+    let id = UserId::new(id);
+    mediator.register(id.clone(), User::new(id).belong_to(mediator.clone()))
+}
+
+/// Writes down who came and went, in order.
+#[derive(Clone, Default)]
+struct Lifecycle(Arc<Mutex<Vec<String>>>);
+
+impl Lifecycle {
+    fn seen(&self) -> Vec<String> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+impl Observer<User> for Lifecycle {
+    fn on_register(&self, id: &UserId) {
+        self.0.lock().unwrap().push(format!("joined {}", id.as_str()));
+    }
+    
+    fn on_deregister(&self, id: &UserId) {
+        self.0.lock().unwrap().push(format!("left {}", id.as_str()));
+    }
+}
+
+#[test]
+fn tells_who_joined_and_left() -> Result<(), Error> {
+    let lifecycle = Lifecycle::default();
+    let mediator = UserMediator::default().observed_by(lifecycle.clone());
+    join(&mediator, "user-1")?;
+    join(&mediator, "user-2")?;
+    mediator.deregister(&UserId::new("user-1"))?;
+    mediator.deregister(&UserId::new("user-3"))?;
+    
+    assert_eq!(lifecycle.seen(), ["joined user-1", "joined user-2", "left user-1"]);
+    Ok(())
+}
+
+#[test]
+fn watches_follow_one_colleague_until_dropped() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    let sender = join(&mediator, "user-1")?;
+    join(&mediator, "user-2")?;
+    join(&mediator, "user-3")?;
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let log = Arc::clone(&seen);
+    let watch = mediator.watch(&UserId::new("user-2"), move |msg: &String| log.lock().unwrap().push(msg.clone()))?;
+    
+    sender.send_msg(&UserId::new("user-2"), "for user-2")?;
+    sender.send_msg(&UserId::new("user-3"), "for user-3")?;
+    drop(watch);
+    sender.send_msg(&UserId::new("user-2"), "unwatched")?;
+    assert_eq!(*seen.lock().unwrap(), ["for user-2"]);
+    Ok(())
+}
@@ -0,0 +1,108 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use mediator_play::{Colleague, ColleagueError, Error, GenericMediator, Mediator};
+
+/// Records `(sender, seq)` pairs and panics if two of them are ever read at the same time.
+#[derive(Colleague)]
+#[colleague(mediator = GenericMediator<Ledger>, message = (u32, u32))]
+struct Ledger {
+    #[colleague(id)]
+    id: u32,
+    busy: AtomicBool,
+    log: Mutex<Vec<(u32, u32)>>,
+}
+
+impl Ledger {
+    fn new(id: u32) -> Ledger {
+        Self { id, busy: AtomicBool::new(false), log: Mutex::default() }
+    }
+    
+    fn on_message(&self, msg: (u32, u32)) -> Result<(), ColleagueError> {
+        assert!(!self.busy.swap(true, Ordering::SeqCst), "read_msg entered concurrently");
+        self.log.lock().unwrap().push(msg);
+        self.busy.store(false, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+#[test]
+fn reads_each_senders_messages_in_order() -> Result<(), Error> {
+    const THREADS: u32 = 4;
+    const PER_THREAD: u32 = 100;
+    
+    let mediator = GenericMediator::<Ledger>::default();
+    let ledger = mediator.register(0, Ledger::new(0).belong_to(mediator.clone()).ordered())?;
+    let sender = mediator.register(1, Ledger::new(1).belong_to(mediator.clone()))?.sender();
+    
+    let threads: Vec<_> = (0..THREADS)
+        .map(|thread| {
+            let sender = sender.clone();
+            thread::spawn(move || {
+                (0..PER_THREAD).try_for_each(|seq| sender.send_msg(&0, (thread, seq)).map(drop))
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().expect("sender thread panicked")?;
+    }
+    
+    let log = ledger.log.lock().unwrap();
+    assert_eq!(log.len(), (THREADS * PER_THREAD) as usize);
+    for thread in 0..THREADS {
+        let seqs = log.iter().filter(|(from, _)| *from == thread).map(|(_, seq)| *seq);
+        assert!(seqs.eq(0..PER_THREAD), "messages from thread {} arrived out of order", thread);
+    }
+    Ok(())
+}
+
+/// Writes its name down in a list shared with the others as it reads.
+#[derive(Colleague)]
+#[colleague(mediator = GenericMediator<Roster>, message = &'static str)]
+struct Roster {
+    #[colleague(id)]
+    id: &'static str,
+    order: Arc<Mutex<Vec<&'static str>>>,
+}
+
+impl Roster {
+    fn on_message(&self, _: &'static str) -> Result<(), ColleagueError> {
+        self.order.lock().unwrap().push(self.id);
+        Ok(())
+    }
+}
+
+#[test]
+fn broadcasts_reach_higher_priorities_first() -> Result<(), Error> {
+    let mediator = GenericMediator::<Roster>::default();
+    let order = Arc::new(Mutex::new(Vec::new()));
+    let roster = |id| Roster { id, order: Arc::clone(&order) }.belong_to(mediator.clone());
+    let sender = mediator.register("sender", roster("sender"))?;
+    mediator.register("user-1", roster("user-1"))?;
+    mediator.register("user-2", roster("user-2"))?;
+    mediator.register_with_priority("logger", roster("logger"), 10)?;
+    mediator.register_with_priority("audit", roster("audit"), 10)?;
+    mediator.register_with_priority("sampler", roster("sampler"), -1)?;
+    
+    assert_eq!(mediator.broadcast(&sender, "shift change")?, 5);
+    assert_eq!(*order.lock().unwrap(), ["logger", "audit", "user-1", "user-2", "sampler"]);
+    Ok(())
+}
+
+#[test]
+fn deterministic_broadcasts_go_by_id() -> Result<(), Error> {
+    let mediator = GenericMediator::<Roster>::builder()
+        .deterministic_broadcast(true)
+        .build();
+    let order = Arc::new(Mutex::new(Vec::new()));
+    let roster = |id| Roster { id, order: Arc::clone(&order) }.belong_to(mediator.clone());
+    for id in ["user-3", "user-1", "user-2"] {
+        mediator.register(id, roster(id))?;
+    }
+    let sender = mediator.register("sender", roster("sender"))?;
+    
+    assert_eq!(mediator.broadcast(&sender, "roll call")?, 3);
+    assert_eq!(*order.lock().unwrap(), ["user-1", "user-2", "user-3"]);
+    Ok(())
+}
@@ -0,0 +1,35 @@
+use mediator_play::{Colleague, Error, Mediator, Registered, User, UserId, UserMediator};
+use prometheus::{Encoder, Registry, TextEncoder};
+
+fn join(mediator: &UserMediator, id: &str) -> Result<Registered<User>, Error> {
+    let id = UserId::new(id);
+    mediator.register(id.clone(), User::new(id).belong_to(mediator.clone()))
+}
+
+fn scrape(registry: &Registry) -> String {
+    let mut text = Vec::new();
+    TextEncoder::new().encode(&registry.gather(), &mut text).unwrap();
+    String::from_utf8(text).unwrap()
+}
+
+#[test]
+fn scrapes_show_the_counters() -> Result<(), Error> {
+    let mediator = UserMediator::with_deadletter();
+    let registry = Registry::new();
+    mediator.register_metrics(&registry).unwrap();
+    let sender = join(&mediator, "user-1")?;
+    join(&mediator, "user-2")?;
+    
+    sender.send_msg(&UserId::new("user-2"), "scrape me")?;
+    sender.send_msg(&UserId::new("user-3"), "nobody home")?;
+    let text = scrape(&registry);
+    for line in ["messages_sent_total 2", "messages_delivered_total 1", "deadletters_total 1", "registered_colleagues 2"] {
+        assert!(text.lines().any(|scraped| scraped == line), "{:?} missing from\n{}", line, text);
+    }
+    assert!(text.contains("# TYPE messages_sent_total counter"));
+    
+    mediator.deregister(&UserId::new("user-2"))?;
+    assert!(scrape(&registry).contains("\nregistered_colleagues 1\n"));
+    assert!(mediator.register_metrics(&registry).is_err(), "registered the same names twice");
+    Ok(())
+}
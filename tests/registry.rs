@@ -0,0 +1,570 @@
+use std::collections::HashSet;
+use std::ops::ControlFlow;
+use std::sync::mpsc;
+use std::thread;
+
+use mediator_play::{Colleague, Error, EventKind, Mediator, Registered, TryRegisterError, User, UserId, UserMediator};
+
+fn join(mediator: &UserMediator, id: &str) -> Result<Registered<User>, Error> {
+    let id = UserId::new(id);
+    mediator.register(id.clone(), User::new(id).belong_to(mediator.clone()))
+}
+
+#[test]
+fn tells_present_from_absent_ids() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    join(&mediator, "user-1")?;
+    
+    assert!(mediator.is_registered(&UserId::new("user-1"))?);
+    assert!(!mediator.is_registered(&UserId::new("user-2"))?);
+    Ok(())
+}
+
+#[test]
+fn counts_registered_colleagues() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    assert!(mediator.is_empty()?);
+    for id in ["user-1", "user-2", "user-3"] {
+        join(&mediator, id)?;
+    }
+    assert_eq!(mediator.registered_count()?, 3);
+    
+    mediator.deregister(&UserId::new("user-2"))?;
+    assert_eq!(mediator.registered_count()?, 2);
+    assert!(!mediator.is_empty()?);
+    Ok(())
+}
+
+#[test]
+fn registers_after_a_panic_poisoned_the_registry() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    let poisoner = mediator.clone();
+    let panicked = thread::spawn(move || poisoner.with_registry_mut(|_| panic!("poisoning the registry")))
+        .join();
+    assert!(panicked.is_err());
+    
+    let registrar = mediator.clone();
+    thread::spawn(move || join(&registrar, "user-1").map(drop))
+        .join()
+        .expect("registering thread panicked")?;
+    assert!(mediator.is_registered(&UserId::new("user-1"))?);
+    Ok(())
+}
+
+#[test]
+fn lists_registered_ids() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    join(&mediator, "user-1")?;
+    join(&mediator, "user-2")?;
+    
+    let ids: HashSet<_> = mediator.registered_ids()?.into_iter().collect();
+    assert_eq!(ids, HashSet::from([UserId::new("user-1"), UserId::new("user-2")]));
+    Ok(())
+}
+
+#[test]
+fn builds_a_presized_mediator() -> Result<(), Error> {
+    let mediator = UserMediator::builder()
+        .capacity(16)
+        .deadletter(true)
+        .build();
+    assert!(mediator.capacity()? >= 16);
+    
+    let sender = join(&mediator, "user-1")?;
+    let recipient = join(&mediator, "user-2")?;
+    sender.send_msg(&UserId::new("user-2"), "hi")?;
+    sender.send_msg(&UserId::new("user-3"), "hi")?;
+    assert_eq!(recipient.received(), 1);
+    assert_eq!(mediator.deadletters()?.len(), 1);
+    Ok(())
+}
+
+#[test]
+fn refuses_to_register_an_id_twice() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    let first = join(&mediator, "user-1")?;
+    
+    let second = join(&mediator, "user-1");
+    assert_eq!(second.unwrap_err(), Error::AlreadyRegistered { id: format!("{:?}", UserId::new("user-1")) });
+    assert_eq!(mediator.get(&UserId::new("user-1"))?, Some(first));
+    Ok(())
+}
+
+#[test]
+fn registers_if_absent_only_once() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    let racers: Vec<_> = (0..4)
+        .map(|_| {
+            let mediator = mediator.clone();
+            thread::spawn(move || {
+                let id = UserId::new("user-1");
+                mediator.register_if_absent(id.clone(), User::new(id).belong_to(mediator.clone()))
+            })
+        })
+        .collect();
+    let handles = racers.into_iter()
+        .map(|racer| racer.join().expect("registering thread panicked"))
+        .collect::<Result<Vec<_>, _>>()?;
+    
+    assert_eq!(mediator.registered_count()?, 1);
+    let registered = mediator.get(&UserId::new("user-1"))?.expect("user-1 is registered");
+    assert!(handles.iter().all(|handle| std::ptr::eq(&**handle, &*registered)));
+    Ok(())
+}
+
+#[test]
+fn registers_through_a_mediator_taken_from_a_handle() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    join(&mediator, "user-1")?;
+    let user = join(&mediator, "user-2")?;
+    
+    let helper = user.mediator();
+    join(&helper, "user-3")?;
+    assert!(mediator.is_registered(&UserId::new("user-3"))?);
+    assert_eq!(user.as_mediator().registered_count()?, 3);
+    Ok(())
+}
+
+#[test]
+fn deregisters_once_the_last_scoped_handle_drops() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    let first = join(&mediator, "user-1")?.scoped();
+    let second = join(&mediator, "user-2")?.scoped();
+    let copy = first.clone();
+    
+    drop(first);
+    assert!(mediator.is_registered(&UserId::new("user-1"))?);
+    drop((copy, second));
+    assert!(mediator.is_empty()?);
+    Ok(())
+}
+
+#[test]
+fn registers_a_batch_all_or_nothing() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    let batch = ["user-1", "user-2", "user-3"]
+        .map(|id| (UserId::new(id), User::new(UserId::new(id)).belong_to(mediator.clone())));
+    assert_eq!(mediator.register_batch(batch.into())?.len(), 3);
+    for id in ["user-1", "user-2", "user-3"] {
+        assert!(mediator.is_registered(&UserId::new(id))?);
+    }
+    
+    let clashing = ["user-4", "user-2"]
+        .map(|id| (UserId::new(id), User::new(UserId::new(id)).belong_to(mediator.clone())));
+    assert!(matches!(mediator.register_batch(clashing.into()), Err(Error::AlreadyRegistered { .. })));
+    assert!(!mediator.is_registered(&UserId::new("user-4"))?);
+    Ok(())
+}
+
+#[test]
+fn finds_peers_by_predicate() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    for id in ["user-1", "user-2", "admin-1"] {
+        join(&mediator, id)?;
+    }
+    
+    let users: HashSet<_> = mediator.find(|user| user.id().as_str().starts_with("user-"))?
+        .iter()
+        .map(|user| user.id().clone())
+        .collect();
+    assert_eq!(users, HashSet::from([UserId::new("user-1"), UserId::new("user-2")]));
+    Ok(())
+}
+
+#[test]
+fn looks_up_a_colleague_by_id() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    let sender = join(&mediator, "user-1")?;
+    join(&mediator, "user-2")?;
+    sender.send_msg(&UserId::new("user-2"), "hi")?;
+    
+    let user = mediator.get(&UserId::new("user-2"))?.expect("user-2 is registered");
+    assert_eq!(user.received(), 1);
+    assert!(mediator.get(&UserId::new("user-3"))?.is_none());
+    Ok(())
+}
+
+#[test]
+fn takes_over_a_registered_id() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    let old = join(&mediator, "user-1")?;
+    
+    let id = UserId::new("user-1");
+    let (new, displaced) = mediator.takeover(id.clone(), User::new(id.clone()).belong_to(mediator.clone()))?;
+    let displaced = displaced.expect("the old session is handed back");
+    assert!(std::ptr::eq(&*displaced, &*old));
+    let current = mediator.get(&id)?.expect("user-1 is registered");
+    assert!(std::ptr::eq(&*current, &*new) && !std::ptr::eq(&*current, &*old));
+    assert_eq!(mediator.registered_count()?, 1);
+    Ok(())
+}
+
+#[test]
+fn clears_every_registration() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    join(&mediator, "user-1")?;
+    join(&mediator, "user-2")?;
+    
+    assert_eq!(mediator.clear()?, 2);
+    assert_eq!(mediator.registered_count()?, 0);
+    join(&mediator, "user-1")?;
+    Ok(())
+}
+
+#[test]
+fn shrinks_after_deregistering_everyone() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    for i in 0..64 {
+        join(&mediator, &format!("user-{}", i))?;
+    }
+    let grown = mediator.capacity()?;
+    for i in 0..64 {
+        mediator.deregister(&UserId::new(format!("user-{}", i)))?;
+    }
+    
+    mediator.shrink_to_fit()?;
+    assert!(mediator.capacity()? < grown, "capacity stayed at {}", grown);
+    Ok(())
+}
+
+#[test]
+fn collects_into_a_mediator() -> Result<(), Error> {
+    let setup = UserMediator::default();
+    let mut mediator: UserMediator = ["user-1", "user-2", "user-3"]
+        .map(UserId::new)
+        .into_iter()
+        .map(|id| (id.clone(), User::new(id).belong_to(setup.clone())))
+        .collect();
+    assert_eq!(mediator.registered_count()?, 3);
+    
+    let id = UserId::new("user-4");
+    mediator.extend([(id.clone(), User::new(id.clone()).belong_to(mediator.clone()))]);
+    assert_eq!(mediator.registered_count()?, 4);
+    assert!(mediator.is_registered(&id)?);
+    Ok(())
+}
+
+#[test]
+fn extending_replaces_by_deregistering() -> Result<(), Error> {
+    let mut mediator = UserMediator::builder()
+        .event_log(true)
+        .build();
+    let first = join(&mediator, "user-1")?;
+    
+    let id = UserId::new("user-1");
+    mediator.extend([(id.clone(), User::new(id.clone()).belong_to(mediator.clone()))]);
+    assert!(mediator.get(&id)?.is_some_and(|current| !std::ptr::eq(&*current, &*first)));
+    let kinds: Vec<_> = mediator.event_log()?.into_iter().map(|event| event.kind).collect();
+    assert!(matches!(&kinds[..], [
+        EventKind::Registered { id: added, weak: false },
+        EventKind::Deregistered { id: replaced },
+        EventKind::Registered { id: readded, weak: false },
+    ] if [added, replaced, readded] == [&id; 3]), "unexpected events {:?}", kinds);
+    Ok(())
+}
+
+#[test]
+fn stopped_mediators_are_not_extended() -> Result<(), Error> {
+    let mut mediator = UserMediator::default();
+    mediator.shutdown()?;
+    
+    let id = UserId::new("user-1");
+    mediator.extend([(id.clone(), User::new(id.clone()).belong_to(mediator.clone()))]);
+    assert!(!mediator.is_registered(&id)?);
+    assert!(mediator.is_empty()?);
+    Ok(())
+}
+
+#[test]
+fn clones_of_a_registration_hash_alike() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    let user = join(&mediator, "user-1")?;
+    let other = join(&mediator, "user-2")?;
+    
+    // Handles hash by id, which stays put however the colleague's counters change.
+    #[allow(clippy::mutable_key_type)]
+    let handles = HashSet::from([user.clone(), user, other]);
+    assert_eq!(handles.len(), 2);
+    Ok(())
+}
+
+#[test]
+fn weak_handles_outlive_nothing() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    let user = join(&mediator, "user-1")?;
+    let weak = user.downgrade();
+    assert!(weak.upgrade().is_some_and(|upgraded| std::ptr::eq(&*upgraded, &*user)));
+    
+    drop(user);
+    assert!(weak.upgrade().is_some(), "the mediator still holds user-1");
+    mediator.deregister(&UserId::new("user-1"))?;
+    assert_eq!(weak.strong_count(), 0);
+    assert!(weak.upgrade().is_none());
+    Ok(())
+}
+
+#[test]
+fn entries_pair_every_id_with_its_colleague() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    join(&mediator, "user-1")?;
+    join(&mediator, "user-2")?;
+    
+    let mut entries = mediator.entries()?;
+    entries.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+    let ids: Vec<_> = entries.iter().map(|(id, _)| id.as_str()).collect();
+    assert_eq!(ids, ["user-1", "user-2"]);
+    assert!(entries.iter().all(|(id, registered)| registered.id() == id));
+    Ok(())
+}
+
+#[test]
+fn tags_find_the_colleagues_wearing_them() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    let id = UserId::new("user-1");
+    let tags = HashSet::from(["admin".to_string(), "chat".to_string()]);
+    mediator.register_tagged(id.clone(), User::new(id).belong_to(mediator.clone()), tags)?;
+    join(&mediator, "user-2")?;
+    
+    let admins = mediator.by_tag("admin")?;
+    assert_eq!(admins.len(), 1);
+    assert_eq!(admins[0].id().as_str(), "user-1");
+    assert!(mediator.by_tag("moderator")?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn renamed_colleagues_answer_to_their_new_id() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    let sender = join(&mediator, "user-1")?;
+    let renamed = join(&mediator, "user-2")?;
+    join(&mediator, "user-3")?;
+    
+    mediator.rename(&UserId::new("user-2"), UserId::new("user-4"))?;
+    sender.send_msg(&UserId::new("user-4"), "hi again")?;
+    assert_eq!(renamed.received(), 1);
+    // The colleague itself still goes by the id it was made with.
+    assert_eq!(renamed.id().as_str(), "user-2");
+    assert!(!mediator.is_registered(&UserId::new("user-2"))?);
+    
+    let user2 = format!("{:?}", UserId::new("user-2"));
+    let user3 = format!("{:?}", UserId::new("user-3"));
+    assert_eq!(mediator.rename(&UserId::new("user-2"), UserId::new("user-5")).unwrap_err(), Error::NotRegistered { id: user2 });
+    assert_eq!(mediator.rename(&UserId::new("user-4"), UserId::new("user-3")).unwrap_err(), Error::AlreadyRegistered { id: user3 });
+    Ok(())
+}
+
+#[test]
+fn debug_output_lists_the_ids() -> Result<(), Error> {
+    let mediator = UserMediator::with_deadletter();
+    let sender = join(&mediator, "user-1")?;
+    join(&mediator, "user-2")?;
+    sender.send_msg(&UserId::new("user-3"), "anyone?")?;
+    
+    let debug = format!("{:?}", mediator);
+    assert!(debug.contains(r#"UserId("user-1")"#) && debug.contains(r#"UserId("user-2")"#), "{}", debug);
+    assert!(debug.contains("deadletters: 1"), "{}", debug);
+    assert_eq!(format!("{:?}", sender), "Registered(User id:user-1)");
+    // Formatting while the registry is locked doesn't wait for it.
+    let locked = mediator.with_registry_mut(|_| format!("{:?}", mediator))?;
+    assert!(locked.contains("ids: <locked>"), "{}", locked);
+    Ok(())
+}
+
+#[test]
+fn presized_registries_never_reallocate() -> Result<(), Error> {
+    let mediator = UserMediator::with_capacity(32);
+    let capacity = mediator.capacity()?;
+    assert!(capacity >= 32);
+    
+    for i in 0..32 {
+        join(&mediator, &format!("user-{}", i))?;
+        assert_eq!(mediator.capacity()?, capacity);
+    }
+    Ok(())
+}
+
+#[test]
+fn registry_closures_aggregate_under_the_lock() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    let sender = join(&mediator, "user-1")?;
+    join(&mediator, "user-2")?;
+    join(&mediator, "user-3")?;
+    mediator.broadcast(&sender, "hi all".to_string())?;
+    sender.send_msg(&UserId::new("user-2"), "and you")?;
+    
+    let (total, busiest) = mediator.with_registry(|users| {
+        let total: usize = users.values().map(|user| user.received()).sum();
+        let busiest = users.iter().max_by_key(|(_, user)| user.received()).map(|(id, _)| id.clone());
+        (total, busiest)
+    })?;
+    assert_eq!((total, busiest), (3, Some(UserId::new("user-2"))));
+    Ok(())
+}
+
+#[test]
+fn the_last_handle_gives_the_colleague_back() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    let sender = join(&mediator, "user-1")?;
+    let user = join(&mediator, "user-2")?;
+    sender.send_msg(&UserId::new("user-2"), "hi")?;
+    
+    let user = user.try_into_inner().expect_err("the mediator still holds user-2");
+    let copy = user.clone();
+    mediator.deregister(&UserId::new("user-2"))?;
+    let user = user.try_into_inner().expect_err("a clone is still around");
+    drop(copy);
+    let user = user.try_into_inner().unwrap();
+    assert_eq!((user.id().as_str(), user.received()), ("user-2", 1));
+    Ok(())
+}
+
+#[test]
+fn standalone_handles_stay_out_of_the_registry() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    let sender = join(&mediator, "user-1")?;
+    let loner = User::new(UserId::new("user-2")).belong_to(mediator.clone()).into_registered();
+    
+    assert_eq!(loner.id().as_str(), "user-2");
+    assert_eq!(loner.received(), 0);
+    assert!(!mediator.is_registered(&UserId::new("user-2"))?);
+    assert!(matches!(sender.send_msg(&UserId::new("user-2"), "hi"), Err(Error::NotRegistered { .. })));
+    loner.send_msg(&UserId::new("user-1"), "I can still talk")?;
+    assert_eq!(sender.received(), 1);
+    Ok(())
+}
+
+#[test]
+fn anonymous_ids_never_clash() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    let threads: Vec<_> = (0..4)
+        .map(|_| {
+            let mediator = mediator.clone();
+            thread::spawn(move || (0..25).map(|_| mediator.register_anonymous().map(|(id, _)| id)).collect::<Result<Vec<_>, _>>())
+        })
+        .collect();
+    let mut ids = HashSet::new();
+    for thread in threads {
+        ids.extend(thread.join().unwrap()?);
+    }
+    
+    assert_eq!(ids.len(), 100);
+    assert_eq!(mediator.registered_count()?, 100);
+    assert!(ids.iter().all(|id| id.as_str().starts_with("anonymous-")));
+    Ok(())
+}
+
+#[test]
+fn try_register_gives_up_on_a_held_lock() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    let (locked, is_locked) = mpsc::channel();
+    let (release, released) = mpsc::channel();
+    let holder = mediator.clone();
+    let holding = thread::spawn(move || holder.with_registry_mut(|_| {
+        locked.send(()).unwrap();
+        released.recv().unwrap();
+    }));
+    is_locked.recv().unwrap();
+    
+    let id = UserId::new("user-1");
+    let contended = mediator.try_register(id.clone(), User::new(id.clone()).belong_to(mediator.clone()));
+    assert!(matches!(contended, Err(TryRegisterError::WouldBlock)));
+    release.send(()).unwrap();
+    holding.join().expect("locking thread panicked")?;
+    
+    assert!(mediator.try_register(id.clone(), User::new(id.clone()).belong_to(mediator.clone())).is_ok());
+    assert!(mediator.is_registered(&id)?);
+    Ok(())
+}
+
+#[test]
+fn deregisters_everyone_a_predicate_picks() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    ["temp-1", "user-1", "temp-2", "user-2", "temp-3"].into_iter()
+        .map(|id| join(&mediator, id))
+        .collect::<Result<Vec<_>, _>>()?;
+    
+    let mut removed = mediator.deregister_where(|user| user.id().as_str().starts_with("temp-"))?;
+    removed.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+    assert_eq!(removed, [UserId::new("temp-1"), UserId::new("temp-2"), UserId::new("temp-3")]);
+    let mut left = mediator.registered_ids()?;
+    left.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+    assert_eq!(left, [UserId::new("user-1"), UserId::new("user-2")]);
+    Ok(())
+}
+
+#[test]
+fn registrations_show_through_every_clone_at_once() -> Result<(), Error> {
+    let writer = UserMediator::default();
+    let reader = writer.clone();
+    
+    for n in 0..100 {
+        let id = UserId::new(format!("user-{}", n));
+        writer.register(id.clone(), User::new(id.clone()).belong_to(writer.clone()))?;
+        assert!(reader.is_registered(&id)?, "{:?} is not visible through a clone", id);
+        assert!(reader.get(&id)?.is_some());
+    }
+    let from_another_thread = writer.clone();
+    let id = thread::spawn(move || join(&from_another_thread, "user-100").map(|user| user.id().clone()))
+        .join()
+        .expect("registering thread panicked")?;
+    assert!(reader.is_registered(&id)?);
+    assert_eq!(reader.registered_count()?, 101);
+    Ok(())
+}
+
+#[test]
+fn drains_every_colleague_into_another_mediator() -> Result<(), Error> {
+    let source = UserMediator::default();
+    let target = UserMediator::default();
+    let handles = ["user-1", "user-2", "user-3"].into_iter()
+        .map(|id| join(&source, id))
+        .collect::<Result<Vec<_>, _>>()?;
+    
+    assert_eq!(source.drain_into(&source.clone())?, 0);
+    assert_eq!(source.drain_into(&target)?, 3);
+    assert!(source.is_empty()?);
+    assert_eq!(target.registered_count()?, 3);
+    for handle in &handles {
+        assert!(target.get(handle.id())?.is_some_and(|moved| std::ptr::eq(&*moved, &**handle)));
+    }
+    Ok(())
+}
+
+#[test]
+fn stops_going_through_entries_once_told_to() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    (1..=5).map(|n| join(&mediator, &format!("user-{}", n))).collect::<Result<Vec<_>, _>>()?;
+    let target = UserId::new("user-3");
+    
+    let (mut visited, mut found) = (0, None);
+    let flow = mediator.for_each_entry(|id, user| {
+        visited += 1;
+        if *id == target {
+            found = Some(user.clone());
+            return ControlFlow::Break(());
+        }
+        ControlFlow::Continue(())
+    })?;
+    assert_eq!(flow, ControlFlow::Break(()));
+    assert!(found.is_some_and(|user| *user.id() == target));
+    assert!(visited <= 5);
+    
+    let mut all = 0;
+    assert_eq!(mediator.for_each_entry(|_, _| { all += 1; ControlFlow::Continue(()) })?, ControlFlow::Continue(()));
+    assert_eq!(all, 5);
+    Ok(())
+}
+
+#[test]
+fn reserved_registries_take_that_many_without_growing() -> Result<(), Error> {
+    let mediator = UserMediator::with_deadletter();
+    mediator.reserve(64)?;
+    let capacity = mediator.capacity()?;
+    assert!(capacity >= 64);
+    
+    for n in 0..64 {
+        join(&mediator, &format!("user-{}", n))?;
+        assert_eq!(mediator.capacity()?, capacity, "grew after {} registrations", n + 1);
+    }
+    Ok(())
+}
@@ -0,0 +1,73 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+use mediator_play::{AckHandle, AckOutcome, AckRecord, Clock, Colleague, ColleagueError, DeliveryOutcome, Error, GenericMediator, Mediator, MemoryAckStore, MockClock, Registered};
+
+/// Turns every job down the first time it gets it.
+#[derive(Colleague)]
+#[colleague(mediator = GenericMediator<Worker>, message = String, read_reliably = on_job)]
+struct Worker {
+    #[colleague(id)]
+    id: u32,
+    attempts: AtomicU32,
+}
+
+impl Worker {
+    fn on_message(&self, _: String) -> Result<(), ColleagueError> {
+        Ok(())
+    }
+    
+    fn on_job(&self, _: String, ack: AckHandle) -> Result<(), ColleagueError> {
+        self.attempts.fetch_add(1, Ordering::Relaxed);
+        if ack.attempt() == 1 {
+            ack.nack();
+        } else {
+            ack.ack();
+        }
+        Ok(())
+    }
+}
+
+fn hire(mediator: &GenericMediator<Worker>, id: u32) -> Result<Registered<Worker>, Error> {
+    mediator.register(id, Worker { id, attempts: AtomicU32::new(0) }.belong_to(mediator.clone()))
+}
+
+#[test]
+fn nacked_messages_come_back_until_acked() -> Result<(), Error> {
+    let clock = MockClock::new();
+    let mediator = GenericMediator::<Worker>::builder()
+        .clock(clock.clone())
+        .build();
+    let desk = hire(&mediator, 0)?;
+    let worker = hire(&mediator, 1)?;
+    
+    mediator.consult_reliably(&desk, &1, "backup".to_string(), Duration::from_secs(5))?;
+    assert_eq!(mediator.in_flight_count(), 1);
+    assert_eq!(mediator.redeliver(clock.now())?, 1);
+    assert_eq!(mediator.redeliver(clock.now())?, 0);
+    assert_eq!(worker.attempts.load(Ordering::Relaxed), 2);
+    assert_eq!(mediator.in_flight_count(), 0);
+    Ok(())
+}
+
+#[test]
+fn ack_stores_keep_the_trail_of_a_message() -> Result<(), Error> {
+    let clock = MockClock::new();
+    let mediator = GenericMediator::<Worker>::builder()
+        .clock(clock.clone())
+        .ack_store(MemoryAckStore::default())
+        .build();
+    let desk = hire(&mediator, 0)?;
+    hire(&mediator, 1)?;
+    
+    let id = mediator.consult_reliably(&desk, &1, "backup".to_string(), Duration::from_secs(5))?;
+    mediator.redeliver(clock.now())?;
+    let record = |outcome| AckRecord { message_id: id, recipient: 1, outcome };
+    assert_eq!(mediator.ack_history(id), [
+        record(AckOutcome::Resolved(DeliveryOutcome::Delivered(id))),
+        record(AckOutcome::Nacked),
+        record(AckOutcome::Resolved(DeliveryOutcome::Delivered(id))),
+        record(AckOutcome::Acked),
+    ]);
+    Ok(())
+}
@@ -0,0 +1,142 @@
+use std::sync::Mutex;
+use std::time::Duration;
+
+use mediator_play::{Clock, Colleague, ColleagueError, Error, EventKind, GenericMediator, Mediator, Message, MockClock, Registered, ReplyContext};
+
+/// Answers questions with "got it" and writes down who sent it what.
+#[derive(Colleague)]
+#[colleague(mediator = GenericMediator<Clerk>, message = String, read_with_context = on_mail)]
+struct Clerk {
+    #[colleague(id)]
+    name: &'static str,
+    mail: Mutex<Vec<(&'static str, String)>>,
+}
+
+impl Clerk {
+    fn new(name: &'static str) -> Clerk {
+        Self { name, mail: Mutex::new(Vec::new()) }
+    }
+    
+    fn mail(&self) -> Vec<(&'static str, String)> {
+        self.mail.lock().unwrap().clone()
+    }
+    
+    fn on_message(&self, _: String) -> Result<(), ColleagueError> {
+        Err(ColleagueError::new("clerks only read their mail with its sender"))
+    }
+    
+    fn on_mail(&self, msg: Message<String>, ctx: ReplyContext<'_, Self>) -> Result<(), ColleagueError> {
+        self.mail.lock().unwrap().push((ctx.from(), msg.body.clone()));
+        if msg.body.ends_with('?') {
+            ctx.reply("got it".to_string())
+                .map_err(|e| ColleagueError::new(e.to_string()))?;
+        }
+        Ok(())
+    }
+}
+
+fn clerks(names: &[&'static str]) -> Result<(GenericMediator<Clerk>, Vec<Registered<Clerk>>), Error> {
+    let mediator = GenericMediator::<Clerk>::default();
+    let clerks = names.iter()
+        .map(|&name| mediator.register(name, Clerk::new(name).belong_to(mediator.clone())))
+        .collect::<Result<_, _>>()?;
+    Ok((mediator, clerks))
+}
+
+#[test]
+fn replies_reach_the_sender() -> Result<(), Error> {
+    let (mediator, clerks) = clerks(&["alice", "bob"])?;
+    
+    mediator.consultation(&clerks[0], &"bob", "did you get the invoice?".to_string())?;
+    assert_eq!(clerks[1].mail(), [("alice", "did you get the invoice?".to_string())]);
+    assert_eq!(clerks[0].mail(), [("bob", "got it".to_string())]);
+    Ok(())
+}
+
+/// Passes everything it gets on to `next`, if it has one, and keeps the rest with who
+/// forwarded it.
+#[derive(Colleague)]
+#[colleague(mediator = GenericMediator<Relay>, message = String, read_with_context = on_mail)]
+struct Relay {
+    #[colleague(id)]
+    name: &'static str,
+    next: Option<&'static str>,
+    kept: Mutex<Vec<(&'static str, Option<String>)>>,
+}
+
+impl Relay {
+    fn on_message(&self, _: String) -> Result<(), ColleagueError> {
+        Ok(())
+    }
+    
+    fn on_mail(&self, msg: Message<String>, ctx: ReplyContext<'_, Self>) -> Result<(), ColleagueError> {
+        let Some(next) = self.next else {
+            self.kept.lock().unwrap().push((ctx.from(), msg.header("forwarded-by").map(str::to_string)));
+            return Ok(());
+        };
+        ctx.mediator().transfer_message(&ctx, &next, msg.body)
+            .map(drop)
+            .map_err(|e| ColleagueError::new(e.to_string()))
+    }
+}
+
+#[test]
+fn transferred_messages_keep_their_sender() -> Result<(), Error> {
+    let mediator = GenericMediator::<Relay>::builder().event_log(true).build();
+    let relay = |name, next| Relay { name, next, kept: Mutex::new(Vec::new()) }.belong_to(mediator.clone());
+    let alice = mediator.register("user-1", relay("user-1", None))?;
+    mediator.register("user-2", relay("user-2", Some("user-3")))?;
+    let carol = mediator.register("user-3", relay("user-3", None))?;
+    
+    mediator.consultation(&alice, &"user-2", "please pass this on".to_string())?;
+    assert_eq!(*carol.kept.lock().unwrap(), [("user-1", Some(r#""user-2""#.to_string()))]);
+    let delivered: Vec<_> = mediator.event_log()?.into_iter()
+        .filter_map(|event| match event.kind {
+            EventKind::Delivered { from, to, .. } => Some((from, to)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(delivered, [("user-1", "user-2"), ("user-1", "user-3")]);
+    Ok(())
+}
+
+/// Gives up on work whose sender stopped waiting, and notes down what it did.
+#[derive(Colleague)]
+#[colleague(mediator = GenericMediator<Solver>, message = u32, read_with_context = on_task)]
+struct Solver {
+    #[colleague(id)]
+    name: &'static str,
+    log: Mutex<Vec<Result<u32, &'static str>>>,
+}
+
+impl Solver {
+    fn on_message(&self, task: u32) -> Result<(), ColleagueError> {
+        self.log.lock().unwrap().push(Ok(task));
+        Ok(())
+    }
+    
+    fn on_task(&self, task: Message<u32>, ctx: ReplyContext<'_, Self>) -> Result<(), ColleagueError> {
+        if ctx.deadline().is_some_and(|deadline| deadline.is_expired()) {
+            self.log.lock().unwrap().push(Err("too late"));
+            return Ok(());
+        }
+        self.on_message(task.body)
+    }
+}
+
+#[test]
+fn handlers_see_the_deadline_of_a_send() -> Result<(), Error> {
+    let clock = MockClock::new();
+    let mediator = GenericMediator::<Solver>::builder().clock(clock.clone()).build();
+    let solver = |name| Solver { name, log: Mutex::new(Vec::new()) }.belong_to(mediator.clone());
+    let boss = mediator.register("boss", solver("boss"))?;
+    let worker = mediator.register("worker", solver("worker"))?;
+    let deadline = clock.now() + Duration::from_secs(1);
+    
+    mediator.consult_with_deadline(&boss, &"worker", 1, deadline)?;
+    clock.advance(Duration::from_secs(2));
+    mediator.consult_with_deadline(&boss, &"worker", 2, deadline)?;
+    mediator.consultation(&boss, &"worker", 3)?;
+    assert_eq!(*worker.log.lock().unwrap(), [Ok(1), Err("too late"), Ok(3)]);
+    Ok(())
+}
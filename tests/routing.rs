@@ -0,0 +1,143 @@
+use mediator_play::{Bridge, Colleague, DeliveryOutcome, Error, Mediator, Registered, RouteDecision, Router, User, UserId, UserMediator};
+
+fn join(mediator: &UserMediator, id: &str) -> Result<Registered<User>, Error> {
+    let id = UserId::new(id);
+    mediator.register(id.clone(), User::new(id).belong_to(mediator.clone()))
+}
+
+#[test]
+fn patterns_catch_ids_nobody_registered() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    let sender = join(&mediator, "admin-1")?;
+    let users = mediator.register_pattern("user-*".to_string(), User::new(UserId::new("users")).belong_to(mediator.clone()))?;
+    
+    assert!(matches!(sender.send_msg(&UserId::new("user-99"), "hi")?, DeliveryOutcome::Delivered(_)));
+    assert_eq!(users.received(), 1);
+    assert!(!mediator.is_registered(&UserId::new("user-99"))?);
+    assert!(matches!(sender.send_msg(&UserId::new("guest-1"), "hi"), Err(Error::NotRegistered { .. })));
+    Ok(())
+}
+
+#[test]
+fn keys_stick_to_one_worker() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    let sender = join(&mediator, "user-1")?;
+    let workers = ["worker-1", "worker-2"].into_iter()
+        .map(|id| mediator.register_worker("billing", User::new(UserId::new(id)).belong_to(mediator.clone())))
+        .collect::<Result<Vec<_>, _>>()?;
+    
+    for key in ["alice", "bob", "carol", "dave"] {
+        let before: Vec<_> = workers.iter().map(|worker| worker.received()).collect();
+        for _ in 0..5 {
+            mediator.consult_by_role(&sender, "billing", key, "invoice".to_string())?;
+        }
+        let took: Vec<_> = workers.iter().zip(&before).map(|(worker, before)| worker.received() - before).collect();
+        assert!(took == [5, 0] || took == [0, 5], "{} was split as {:?}", key, took);
+    }
+    assert!(matches!(mediator.consult_by_role(&sender, "support", "alice", "help".to_string()), Err(Error::NoWorkers { .. })));
+    Ok(())
+}
+
+#[test]
+fn previews_resolve_like_consultations() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    join(&mediator, "user-1")?;
+    mediator.register_pattern("guest-*".to_string(), User::new(UserId::new("guests")).belong_to(mediator.clone()))?;
+    
+    assert_eq!(mediator.route_preview(&UserId::new("user-1"))?, RouteDecision::Exact);
+    assert_eq!(mediator.route_preview(&UserId::new("guest-7"))?, RouteDecision::Pattern("guest-*".to_string()));
+    assert_eq!(mediator.route_preview(&UserId::new("user-2")).unwrap_err(), Error::NotRegistered { id: format!("{:?}", UserId::new("user-2")) });
+    assert_eq!(UserMediator::with_deadletter().route_preview(&UserId::new("user-2"))?, RouteDecision::Deadletter);
+    Ok(())
+}
+
+#[test]
+fn fallbacks_catch_unknown_ids() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    let sender = join(&mediator, "user-1")?;
+    let support = join(&mediator, "support")?;
+    mediator.set_fallback(UserId::new("support"))?;
+    
+    assert!(matches!(sender.send_msg(&UserId::new("user-99"), "help")?, DeliveryOutcome::Delivered(_)));
+    assert_eq!(support.received(), 1);
+    assert_eq!(mediator.route_preview(&UserId::new("user-99"))?, RouteDecision::Fallback);
+    // Nothing the fallback sends comes back to it.
+    assert!(matches!(support.send_msg(&UserId::new("user-99"), "echo"), Err(Error::NotRegistered { .. })));
+    assert_eq!(support.received(), 1);
+    Ok(())
+}
+
+#[test]
+fn parents_deliver_what_children_cannot() -> Result<(), Error> {
+    let parent = UserMediator::default();
+    let upstairs = join(&parent, "user-9")?;
+    let child = UserMediator::builder()
+        .parent(parent.clone())
+        .build();
+    let sender = join(&child, "user-1")?;
+    
+    assert_eq!(child.route_preview(&UserId::new("user-9"))?, RouteDecision::Parent);
+    assert!(matches!(sender.send_msg(&UserId::new("user-9"), "hello up there")?, DeliveryOutcome::Delivered(_)));
+    assert_eq!(upstairs.received(), 1);
+    assert!(matches!(sender.send_msg(&UserId::new("user-10"), "hello?"), Err(Error::NotRegistered { .. })));
+    Ok(())
+}
+
+#[test]
+fn forwarding_stops_at_the_depth_cap() -> Result<(), Error> {
+    let grandparent = UserMediator::default();
+    let far = join(&grandparent, "user-9")?;
+    let parent = UserMediator::builder()
+        .parent(grandparent)
+        .forward_depth(1)
+        .build();
+    let child = UserMediator::builder()
+        .parent(parent)
+        .build();
+    let sender = join(&child, "user-1")?;
+    
+    assert_eq!(sender.send_msg(&UserId::new("user-9"), "too far").unwrap_err(), Error::ForwardDepthExceeded { depth: 1 });
+    assert_eq!(far.received(), 0);
+    Ok(())
+}
+
+#[test]
+fn bridges_carry_messages_across() -> Result<(), Error> {
+    let east = UserMediator::default();
+    let west = UserMediator::default();
+    let sender = join(&east, "user-1")?;
+    let far = join(&west, "user-2")?;
+    let bridge = Bridge::connect(&east, &west)?;
+    
+    assert_eq!(east.route_preview(&UserId::new("user-2"))?, RouteDecision::Bridge);
+    sender.send_msg(&UserId::new("user-2"), "across the bridge")?;
+    assert_eq!(far.received(), 1);
+    
+    let late = join(&west, "user-3")?;
+    assert!(matches!(sender.send_msg(&UserId::new("user-3"), "hi"), Err(Error::NotRegistered { .. })));
+    bridge.refresh()?;
+    sender.send_msg(&UserId::new("user-3"), "hi")?;
+    assert_eq!(late.received(), 1);
+    
+    drop(bridge);
+    assert!(matches!(sender.send_msg(&UserId::new("user-2"), "still there?"), Err(Error::NotRegistered { .. })));
+    Ok(())
+}
+
+#[test]
+fn rules_route_by_content_in_order() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    let sender = join(&mediator, "triage")?;
+    let oncall = join(&mediator, "oncall")?;
+    let inbox = join(&mediator, "inbox")?;
+    
+    assert_eq!(mediator.consult_routed(&sender, "urgent: the build is on fire".to_string()).unwrap_err(), Error::NoRoute);
+    mediator.set_router(Router::new()
+        .rule(|msg: &String| msg.contains("urgent"), UserId::new("oncall"))
+        .rule(|_| true, UserId::new("inbox")))?;
+    mediator.consult_routed(&sender, "urgent: the build is on fire".to_string())?;
+    mediator.consult_routed(&sender, "lunch on friday?".to_string())?;
+    mediator.consult_routed(&sender, "not urgent, but still".to_string())?;
+    assert_eq!((oncall.received(), inbox.received()), (2, 1));
+    Ok(())
+}
@@ -0,0 +1,27 @@
+use std::collections::HashSet;
+
+use mediator_play::{Colleague, Error, Mediator, User, UserId, UserMediator};
+
+#[test]
+fn user_ids_round_trip_through_json() {
+    let id = UserId::new("user-1");
+    let json = serde_json::to_string(&id).expect("ids serialize");
+    assert_eq!(json, r#""user-1""#);
+    assert_eq!(serde_json::from_str::<UserId>(&json).expect("ids deserialize"), id);
+}
+
+#[test]
+fn exported_ids_import_into_a_fresh_mediator() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    for id in ["user-1", "user-2"].map(UserId::new) {
+        mediator.register(id.clone(), User::new(id).belong_to(mediator.clone()))?;
+    }
+    
+    let json = serde_json::to_string(&mediator.export_ids()).expect("ids serialize");
+    let mut restored = UserMediator::default();
+    let imported = restored.import_ids(serde_json::from_str(&json).expect("ids deserialize"))?;
+    assert_eq!(imported.len(), 2);
+    let ids: HashSet<_> = restored.registered_ids()?.into_iter().collect();
+    assert_eq!(ids, mediator.registered_ids()?.into_iter().collect());
+    Ok(())
+}
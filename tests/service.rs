@@ -0,0 +1,22 @@
+use mediator_play::{Colleague, DeliveryOutcome, Error, Mediator, MediatorService, Registered, User, UserId, UserMediator};
+use tower::ServiceExt;
+
+fn join(mediator: &UserMediator, id: &str) -> Result<Registered<User>, Error> {
+    let id = UserId::new(id);
+    mediator.register(id.clone(), User::new(id).belong_to(mediator.clone()))
+}
+
+#[tokio::test]
+async fn oneshots_deliver_through_the_service() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    let sender = join(&mediator, "user-1")?;
+    let recipient = join(&mediator, "user-2")?;
+    
+    let outcome = MediatorService::new(&sender, UserId::new("user-2")).oneshot("hi".to_string()).await?;
+    assert!(matches!(outcome, DeliveryOutcome::Delivered(_)), "{:?}", outcome);
+    assert_eq!(recipient.received(), 1);
+    
+    let absent = MediatorService::new(&sender, UserId::new("user-3")).oneshot("anyone?".to_string()).await;
+    assert_eq!(absent.unwrap_err(), Error::NotRegistered { id: format!("{:?}", UserId::new("user-3")) });
+    Ok(())
+}
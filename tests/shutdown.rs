@@ -0,0 +1,79 @@
+use std::sync::{Arc, Mutex};
+
+use mediator_play::{Colleague, ColleagueError, Error, GenericMediator, Mediator, Observer, Registered, ShutdownReport, User, UserId, UserMediator};
+
+fn join(mediator: &UserMediator, id: &str) -> Result<Registered<User>, Error> {
+    let id = UserId::new(id);
+    mediator.register(id.clone(), User::new(id).belong_to(mediator.clone()))
+}
+
+#[test]
+fn refuses_registrations_after_shutting_down() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    let sender = join(&mediator, "user-1")?;
+    join(&mediator, "user-2")?;
+    
+    assert_eq!(mediator.shutdown()?, ShutdownReport { deregistered: 2, flushed: 0 });
+    assert!(mediator.is_empty()?);
+    assert_eq!(join(&mediator, "user-3").unwrap_err(), Error::ShuttingDown);
+    assert_eq!(sender.send_msg(&UserId::new("user-2"), "anyone?").unwrap_err(), Error::ShuttingDown);
+    Ok(())
+}
+
+/// Keeps what was dropped on shutdown, as `(to, msg)` pairs.
+#[derive(Clone, Default)]
+struct Dropped(Arc<Mutex<Vec<(UserId, String)>>>);
+
+impl Observer<User> for Dropped {
+    fn on_drop_message(&self, _from: &UserId, to: &UserId, msg: String) {
+        self.0.lock().unwrap().push((to.clone(), msg));
+    }
+}
+
+#[test]
+fn reports_messages_left_in_mailboxes() -> Result<(), Error> {
+    let dropped = Dropped::default();
+    let mediator = UserMediator::default().observed_by(dropped.clone());
+    let sender = join(&mediator, "user-1")?;
+    let id = UserId::new("user-2");
+    mediator.register(id.clone(), User::new(id.clone()).belong_to(mediator.clone()).with_mailbox(4))?;
+    sender.send_msg(&id, "first")?;
+    sender.send_msg(&id, "second")?;
+    
+    assert_eq!(mediator.shutdown()?, ShutdownReport { deregistered: 2, flushed: 2 });
+    assert_eq!(*dropped.0.lock().unwrap(), [(id.clone(), "first".to_string()), (id, "second".to_string())]);
+    Ok(())
+}
+
+/// Notes down its id once it is stopped.
+#[derive(Colleague)]
+#[colleague(mediator = GenericMediator<Stage>, message = (), on_shutdown = stop)]
+struct Stage {
+    #[colleague(id)]
+    id: &'static str,
+    stopped: Arc<Mutex<Vec<&'static str>>>,
+}
+
+impl Stage {
+    fn on_message(&self, _: ()) -> Result<(), ColleagueError> {
+        Ok(())
+    }
+    
+    fn stop(&self) {
+        self.stopped.lock().unwrap().push(self.id);
+    }
+}
+
+#[test]
+fn stops_higher_priorities_first() -> Result<(), Error> {
+    let mediator = GenericMediator::<Stage>::default();
+    let stopped = Arc::new(Mutex::new(Vec::new()));
+    let stage = |id| Stage { id, stopped: Arc::clone(&stopped) }.belong_to(mediator.clone());
+    mediator.register_with_shutdown_priority("consumer", stage("consumer"), 0)?;
+    mediator.register_with_shutdown_priority("producer", stage("producer"), 10)?;
+    mediator.register_with_shutdown_priority("transformer", stage("transformer"), 5)?;
+    
+    assert_eq!(mediator.shutdown()?.deregistered, 3);
+    assert_eq!(*stopped.lock().unwrap(), ["producer", "transformer", "consumer"]);
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use std::collections::HashSet;
+
+use mediator_play::{Colleague, Error, GroupId, Mediator, Registered, User, UserId, UserMediator};
+
+fn join(mediator: &UserMediator, id: &str) -> Result<Registered<User>, Error> {
+    let id = UserId::new(id);
+    mediator.register(id.clone(), User::new(id).belong_to(mediator.clone()))
+}
+
+#[test]
+fn snapshots_registrations_and_deadletters() -> Result<(), Error> {
+    let mediator = UserMediator::with_deadletter();
+    let sender = join(&mediator, "user-1")?;
+    join(&mediator, "user-2")?;
+    sender.send_msg(&UserId::new("user-3"), "hi")?;
+    
+    let snapshot = mediator.snapshot()?;
+    let ids: HashSet<_> = snapshot.ids.into_iter().collect();
+    assert_eq!(ids, HashSet::from([UserId::new("user-1"), UserId::new("user-2")]));
+    assert_eq!(snapshot.deadletters, 1);
+    assert!(snapshot.groups.is_empty());
+    assert!(!snapshot.shutting_down);
+    Ok(())
+}
+
+#[test]
+fn exported_state_brings_a_fresh_mediator_up_to_date() -> Result<(), Error> {
+    let old = UserMediator::with_deadletter();
+    let sender = join(&old, "user-1")?;
+    join(&old, "user-2")?;
+    let lobby = GroupId::new("lobby");
+    old.join_group(&UserId::new("user-1"), lobby.clone())?;
+    old.join_group(&UserId::new("user-2"), lobby.clone())?;
+    sender.send_msg(&UserId::new("user-3"), "hi")?;
+    
+    let state = old.export_state()?;
+    let new = UserMediator::with_deadletter();
+    let users = state.ids.iter()
+        .map(|id| new.register(id.clone(), User::new(id.clone()).belong_to(new.clone())))
+        .collect::<Result<Vec<_>, _>>()?;
+    new.import_state(state)?;
+    
+    let old_ids: HashSet<_> = old.registered_ids()?.into_iter().collect();
+    let new_ids: HashSet<_> = new.registered_ids()?.into_iter().collect();
+    assert_eq!(new_ids, old_ids);
+    assert_eq!(new.deadletters()?.len(), 1);
+    let sender = users.iter().find(|user| user.id() == &UserId::new("user-1")).unwrap();
+    assert_eq!(new.multicast(sender, &lobby, "still here?".to_string())?, 1);
+    Ok(())
+}
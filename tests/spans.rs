@@ -0,0 +1,16 @@
+use mediator_play::{Colleague, Error, Mediator, User, UserId, UserMediator};
+use tracing_test::traced_test;
+
+#[test]
+#[traced_test]
+fn delivers_within_a_consultation_span() -> Result<(), Error> {
+    let mediator = UserMediator::default();
+    let (user1, user2) = (UserId::new("user-1"), UserId::new("user-2"));
+    let sender = mediator.register(user1.clone(), User::new(user1).belong_to(mediator.clone()))?;
+    mediator.register(user2.clone(), User::new(user2.clone()).belong_to(mediator.clone()))?;
+    
+    let id = sender.send_msg(&user2, "hi")?.message_id();
+    let span = format!(r#"consultation{{from=UserId("user-1") to=UserId("user-2") message_id={}}}"#, id.as_u64());
+    assert!(logs_contain(&format!("{}: mediator_play::generic: delivered", span)));
+    Ok(())
+}
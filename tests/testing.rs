@@ -0,0 +1,55 @@
+use mediator_play::testing::{self, Divergence, TestRecorder};
+use mediator_play::{Colleague, DeliveryOutcome, Error, GenericMediator, Mediator, Registered};
+
+type Recorders = GenericMediator<TestRecorder<&'static str, String>>;
+
+fn join(mediator: &Recorders, id: &'static str) -> Result<Registered<TestRecorder<&'static str, String>>, Error> {
+    mediator.register(id, TestRecorder::new(id).belong_to(mediator.clone()))
+}
+
+#[test]
+fn recorders_keep_what_they_read() -> Result<(), Error> {
+    let mediator = Recorders::default();
+    let probe = join(&mediator, "probe")?;
+    let sink = join(&mediator, "sink")?;
+    
+    mediator.consultation(&probe, &"sink", "first".to_string())?;
+    mediator.consultation(&probe, &"sink", "second".to_string())?;
+    assert_eq!(sink.recorded(), [r#""first""#, r#""second""#]);
+    assert!(probe.recorded().is_empty());
+    Ok(())
+}
+
+#[test]
+fn replays_come_out_as_recorded() -> Result<(), Error> {
+    let mediator = Recorders::builder()
+        .event_log(true)
+        .deadletter(true)
+        .build();
+    let probe = join(&mediator, "probe")?;
+    join(&mediator, "sink")?;
+    mediator.consultation(&probe, &"sink", "first".to_string())?;
+    mediator.consultation(&probe, &"nowhere", "lost".to_string())?;
+    mediator.broadcast(&probe, "all".to_string())?;
+    let events = mediator.event_log()?;
+    
+    let mut fresh = Recorders::builder()
+        .deadletter(true)
+        .build();
+    let report = testing::replay(&events, &mut fresh, |id| TestRecorder::new(*id))?;
+    assert!(report.is_faithful(), "{:?}", report);
+    assert_eq!((report.registered, report.consulted, report.broadcast), (2, 2, 1));
+    let sink = fresh.get(&"sink")?.expect("the replay registered the sink");
+    assert_eq!(sink.recorded(), [r#""first""#, r#""all""#]);
+    
+    // Without a deadletter queue, the consultation nobody took fails instead.
+    let mut strict = Recorders::default();
+    let report = testing::replay(&events, &mut strict, |id| TestRecorder::new(*id))?;
+    let [Divergence::Consultation { to, recorded, replayed, .. }] = &report.divergences[..] else {
+        panic!("expected the lost consultation alone to diverge: {:?}", report.divergences);
+    };
+    assert_eq!(*to, "nowhere");
+    assert!(matches!(recorded, Ok(DeliveryOutcome::Deadlettered(_))));
+    assert_eq!(*replayed, Err(Error::NotRegistered { id: format!("{:?}", "nowhere") }));
+    Ok(())
+}
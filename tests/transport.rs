@@ -0,0 +1,57 @@
+use std::sync::{Arc, Mutex};
+
+use futures::stream::{self, BoxStream, StreamExt};
+use mediator_play::{Colleague, ColleagueError, DeliveryOutcome, Error, Mediator, RouteDecision, Transport, User, UserId, UserMediator};
+
+/// Payloads by the topic they were published to.
+type Published = Vec<(String, Vec<u8>)>;
+
+/// Writes down what it is asked to do and never hears back from anyone.
+#[derive(Clone, Default)]
+struct Recording {
+    published: Arc<Mutex<Published>>,
+    subscribed: Arc<Mutex<Vec<String>>>,
+}
+
+impl Transport for Recording {
+    fn publish(&self, topic: &str, payload: Vec<u8>) -> Result<(), ColleagueError> {
+        self.published.lock().unwrap().push((topic.to_string(), payload));
+        Ok(())
+    }
+    
+    fn subscribe(&self, topic: &str) -> Result<BoxStream<'static, Vec<u8>>, ColleagueError> {
+        self.subscribed.lock().unwrap().push(topic.to_string());
+        Ok(stream::pending().boxed())
+    }
+}
+
+fn encode(from: &UserId, msg: &String) -> Vec<u8> {
+    format!("{}\n{}", from.as_str(), msg).into_bytes()
+}
+
+fn decode(payload: &[u8]) -> Option<(UserId, String)> {
+    let (from, msg) = std::str::from_utf8(payload).ok()?.split_once('\n')?;
+    Some((UserId::new(from), msg.to_string()))
+}
+
+#[tokio::test]
+async fn local_misses_are_published() -> Result<(), Error> {
+    let transport = Recording::default();
+    let mediator = UserMediator::builder()
+        .transport(transport.clone(), encode, decode)
+        .build();
+    let id = UserId::new("user-1");
+    let sender = mediator.register(id.clone(), User::new(id).belong_to(mediator.clone()))?;
+    let id = UserId::new("user-2");
+    let local = mediator.register(id.clone(), User::new(id).belong_to(mediator.clone()))?;
+    
+    assert!(matches!(sender.send_msg(&UserId::new("user-2"), "next door")?, DeliveryOutcome::Delivered(_)));
+    assert_eq!(local.received(), 1);
+    assert!(transport.published.lock().unwrap().is_empty());
+    
+    assert_eq!(mediator.route_preview(&UserId::new("user-9"))?, RouteDecision::Remote);
+    assert!(matches!(sender.send_msg(&UserId::new("user-9"), "far away")?, DeliveryOutcome::Published(_)));
+    assert_eq!(*transport.published.lock().unwrap(), [(r#"mediator:UserId("user-9")"#.to_string(), b"user-1\nfar away".to_vec())]);
+    assert_eq!(*transport.subscribed.lock().unwrap(), [r#"mediator:UserId("user-1")"#, r#"mediator:UserId("user-2")"#]);
+    Ok(())
+}
@@ -0,0 +1,84 @@
+use std::sync::Mutex;
+
+use mediator_play::{Colleague, ColleagueError, Error, MapMediator, Mediator, Registered};
+
+/// Books transfers, but only up to its limit.
+#[derive(Colleague)]
+#[colleague(mediator = MapMediator<Account>, message = u32, can_accept = within_limit)]
+struct Account {
+    #[colleague(id)]
+    name: &'static str,
+    limit: u32,
+    booked: Mutex<Vec<u32>>,
+}
+
+impl Account {
+    fn within_limit(&self, amount: &u32) -> bool {
+        *amount <= self.limit
+    }
+    
+    fn on_message(&self, amount: u32) -> Result<(), ColleagueError> {
+        self.booked.lock().unwrap().push(amount);
+        Ok(())
+    }
+}
+
+fn open(mediator: &MapMediator<Account>, name: &'static str, limit: u32) -> Result<Registered<Account>, Error> {
+    mediator.register(name, Account { name, limit, booked: Mutex::new(Vec::new()) }.belong_to(mediator.clone()))
+}
+
+#[test]
+fn one_refusal_keeps_everyone_from_reading() -> Result<(), Error> {
+    let mediator = MapMediator::<Account>::default();
+    let bank = open(&mediator, "bank", u32::MAX)?;
+    let checking = open(&mediator, "checking", 1_000)?;
+    let savings = open(&mediator, "savings", 100)?;
+    
+    let declined = mediator.consult_2pc(&bank, &["checking", "savings"], 500);
+    assert_eq!(declined.unwrap_err(), Error::PrepareDeclined { id: format!("{:?}", "savings") });
+    assert!(checking.booked.lock().unwrap().is_empty());
+    assert!(savings.booked.lock().unwrap().is_empty());
+    
+    assert_eq!(mediator.consult_2pc(&bank, &["checking", "savings"], 50)?.len(), 2);
+    assert_eq!(*checking.booked.lock().unwrap(), [50]);
+    assert_eq!(*savings.booked.lock().unwrap(), [50]);
+    Ok(())
+}
+
+#[test]
+fn a_recipient_that_cant_read_keeps_the_others_from_reading() -> Result<(), Error> {
+    let mediator = MapMediator::<Account>::default();
+    let bank = open(&mediator, "bank", u32::MAX)?;
+    let checking = open(&mediator, "checking", 1_000)?;
+    let savings = open(&mediator, "savings", 1_000)?;
+    let pension = Account { name: "pension", limit: 1_000, booked: Mutex::new(Vec::new()) }.belong_to(mediator.clone());
+    let pension = mediator.register("pension", pension.with_mailbox(1))?;
+    
+    mediator.pause(&"savings")?;
+    let paused = mediator.consult_2pc(&bank, &["checking", "savings"], 50);
+    assert_eq!(paused.unwrap_err(), Error::PrepareDeclined { id: format!("{:?}", "savings") });
+    
+    mediator.consultation(&bank, &"pension", 10)?;
+    let full = mediator.consult_2pc(&bank, &["checking", "pension"], 50);
+    assert_eq!(full.unwrap_err(), Error::MailboxFull { id: format!("{:?}", "pension") });
+    assert!(checking.booked.lock().unwrap().is_empty());
+    assert!(savings.booked.lock().unwrap().is_empty());
+    assert_eq!(pension.mailbox_depth(), Some(1));
+    Ok(())
+}
+
+#[test]
+fn a_send_takes_one_message_of_the_quota() -> Result<(), Error> {
+    let mediator = MapMediator::<Account>::default();
+    let bank = open(&mediator, "bank", u32::MAX)?;
+    let checking = open(&mediator, "checking", 1_000)?;
+    let savings = open(&mediator, "savings", 1_000)?;
+    mediator.set_quota(&"bank", 1)?;
+    
+    assert_eq!(mediator.consult_2pc(&bank, &["checking", "savings"], 50)?.len(), 2);
+    let spent = mediator.consult_2pc(&bank, &["checking", "savings"], 50);
+    assert_eq!(spent.unwrap_err(), Error::QuotaExceeded { id: format!("{:?}", "bank") });
+    assert_eq!(*checking.booked.lock().unwrap(), [50]);
+    assert_eq!(*savings.booked.lock().unwrap(), [50]);
+    Ok(())
+}
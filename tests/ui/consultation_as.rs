@@ -0,0 +1,8 @@
+use mediator_play::{Mediator, User, UserId};
+
+// A `Mediator` bound brings `consultation_as` along, but not the token it takes.
+fn speak_for<M: Mediator<User>>(mediator: &M, from: &UserId) {
+    mediator.consultation_as(mediator_play::sealed::Token::new(), from, &UserId::new("user-2"), "hi".to_string());
+}
+
+fn main() {}
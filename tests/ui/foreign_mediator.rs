@@ -0,0 +1,8 @@
+use mediator_play::{Mediator, User};
+
+// Only the crate's own mediators may implement `Mediator`.
+struct Rogue;
+
+impl Mediator<User> for Rogue {}
+
+fn main() {}
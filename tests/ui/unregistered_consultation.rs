@@ -0,0 +1,7 @@
+use mediator_play::{Mediator, User, UserId, UserMediator};
+
+fn main() {
+    let mediator = UserMediator::default();
+    let user = User::new(UserId::new("user-1"));
+    mediator.consultation(&user, &UserId::new("user-2"), "hi".to_string());
+}
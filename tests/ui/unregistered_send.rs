@@ -0,0 +1,7 @@
+use mediator_play::{Colleague, User, UserId, UserMediator};
+
+fn main() {
+    let mediator = UserMediator::default();
+    let managed = User::new(UserId::new("user-1")).belong_to(mediator);
+    managed.send_msg(&UserId::new("user-2"), "hi");
+}